@@ -0,0 +1,19 @@
+#!/usr/bin/env perl
+
+use v5.28.0;
+
+use Benchmark qw(cmpthese);
+
+use lib '.';
+use RSPM::Bench;
+
+my %hash = map { ("key-$_" => $_) } 1..10_000;
+
+die "owned and borrowed key deserialization disagree\n"
+    if RSPM::Bench::hash_keys_owned(\%hash) != RSPM::Bench::hash_keys_borrowed(\%hash);
+
+print("Deserializing a hash with 10000 entries:\n");
+cmpthese(-3, {
+    owned_keys => sub { RSPM::Bench::hash_keys_owned(\%hash) },
+    borrowed_keys => sub { RSPM::Bench::hash_keys_borrowed(\%hash) },
+});
@@ -8,7 +8,8 @@ fn main() {
         .output()
         .expect("failed to get perl usemultiplicity flag");
 
-    // pass the multiplicity cfg flag:
+    // declare the multiplicity cfg flag and pass it if enabled:
+    println!("cargo::rustc-check-cfg=cfg(perlmod, values(\"multiplicity\"))");
     if perl_multiplicity.stdout == b"define" {
         println!("cargo:rustc-cfg=perlmod=\"multiplicity\"");
     }
@@ -1,6 +1,7 @@
 use proc_macro2::{Ident, Span};
 
 use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
 use syn::{Error, Meta, Token};
 
 pub struct ModuleAttrs {
@@ -9,6 +10,9 @@ pub struct ModuleAttrs {
     pub lib_name: Option<String>,
     pub write: Option<bool>,
     pub boot: Option<syn::Path>,
+    pub pre_boot: Option<syn::Path>,
+    pub tests: Option<String>,
+    pub xs_file: Option<String>,
 }
 
 fn is_ident_check_dup<T>(path: &syn::Path, var: &Option<T>, what: &'static str) -> bool {
@@ -31,6 +35,9 @@ impl TryFrom<Punctuated<Meta, Token![,]>> for ModuleAttrs {
         let mut lib_name = None;
         let mut write = None;
         let mut boot = None;
+        let mut pre_boot = None;
+        let mut tests = None;
+        let mut xs_file = None;
 
         for arg in args {
             let (path, value) = match arg {
@@ -53,9 +60,18 @@ impl TryFrom<Punctuated<Meta, Token![,]>> for ModuleAttrs {
             } else if is_ident_check_dup(&path, &boot, "boot") {
                 let Some(litstr) = expect_lit_str(value) else { continue };
                 boot = Some(litstr.parse::<syn::Path>()?);
+            } else if is_ident_check_dup(&path, &pre_boot, "pre_boot") {
+                let Some(litstr) = expect_lit_str(value) else { continue };
+                pre_boot = Some(litstr.parse::<syn::Path>()?);
             } else if is_ident_check_dup(&path, &write, "write") {
                 let Some(litbool) = expect_lit_bool(value) else { continue };
                 write = Some(litbool.value());
+            } else if is_ident_check_dup(&path, &tests, "tests") {
+                let Some(litstr) = expect_lit_str(value) else { continue };
+                tests = Some(expand_env_vars(&litstr)?);
+            } else if is_ident_check_dup(&path, &xs_file, "xs_file") {
+                let Some(litstr) = expect_lit_str(value) else { continue };
+                xs_file = Some(expand_env_vars(&litstr)?);
             } else {
                 error!(path => "unknown argument");
             }
@@ -70,6 +86,9 @@ impl TryFrom<Punctuated<Meta, Token![,]>> for ModuleAttrs {
             lib_name,
             write,
             boot,
+            pre_boot,
+            tests,
+            xs_file,
         })
     }
 }
@@ -124,12 +143,19 @@ impl ModuleAttrs {
 #[derive(Default)]
 pub struct FunctionAttrs {
     pub perl_name: Option<Ident>,
+    pub aliases: Vec<Ident>,
     pub xs_name: Option<Ident>,
     pub raw_return: bool,
     pub cv_variable: Option<Ident>,
     pub prototype: Option<String>,
     pub serialize_error: bool,
     pub errno: bool,
+    pub return_ok_true: Option<Span>,
+    pub option_as_list: bool,
+    pub map_as_list: Option<Span>,
+    pub empty_as_undef: Option<Span>,
+    pub substr_return: Option<Span>,
+    pub accessor: Option<Ident>,
 }
 
 impl TryFrom<Punctuated<Meta, Token![,]>> for FunctionAttrs {
@@ -144,8 +170,26 @@ impl TryFrom<Punctuated<Meta, Token![,]>> for FunctionAttrs {
                     let Some(litstr) = expect_lit_str(value) else { continue };
                     if is_ident_check_dup(&path, &attrs.xs_name, "xs_name") {
                         attrs.xs_name = Some(Ident::new(&litstr.value(), litstr.span()));
-                    } else if is_ident_check_dup(&path, &attrs.perl_name, "name") {
-                        attrs.perl_name = Some(Ident::new(&litstr.value(), litstr.span()));
+                    } else if path.is_ident("name") {
+                        if attrs.accessor.is_some() {
+                            error!(path => "'name' cannot be combined with 'accessor'");
+                            continue;
+                        }
+                        // additional names are installed as aliases:
+                        let name = Ident::new(&litstr.value(), litstr.span());
+                        if attrs.perl_name.is_none() {
+                            attrs.perl_name = Some(name);
+                        } else {
+                            attrs.aliases.push(name);
+                        }
+                    } else if is_ident_check_dup(&path, &attrs.accessor, "accessor") {
+                        if attrs.accessor.is_none() && attrs.perl_name.is_some() {
+                            error!(path => "'accessor' cannot be combined with 'name'");
+                            continue;
+                        }
+                        let name = Ident::new(&litstr.value(), litstr.span());
+                        attrs.perl_name = Some(name.clone());
+                        attrs.accessor = Some(name);
                     } else if is_ident_check_dup(&path, &attrs.prototype, "prototype") {
                         attrs.prototype = Some(litstr.value());
                     } else {
@@ -160,6 +204,16 @@ impl TryFrom<Punctuated<Meta, Token![,]>> for FunctionAttrs {
                         attrs.serialize_error = true;
                     } else if path.is_ident("errno") {
                         attrs.errno = true;
+                    } else if path.is_ident("return_ok_true") {
+                        attrs.return_ok_true = Some(path.span());
+                    } else if path.is_ident("option_as_list") {
+                        attrs.option_as_list = true;
+                    } else if path.is_ident("map_as_list") {
+                        attrs.map_as_list = Some(path.span());
+                    } else if path.is_ident("empty_as_undef") {
+                        attrs.empty_as_undef = Some(path.span());
+                    } else if path.is_ident("substr_return") {
+                        attrs.substr_return = Some(path.span());
                     } else {
                         error!(path => "unknown attribute");
                     }
@@ -9,6 +9,7 @@ use crate::attribs::FunctionAttrs;
 pub struct XSub {
     pub rust_name: Ident,
     pub perl_name: Option<Ident>,
+    pub aliases: Vec<Ident>,
     pub xs_name: Ident,
     pub tokens: TokenStream,
     pub prototype: Option<String>,
@@ -19,11 +20,26 @@ struct ArgumentAttrs {
     /// This is the `CV` pointer.
     cv: Option<Span>,
 
+    /// Pass the calling context as a `perlmod::Context`.
+    context: Option<Span>,
+
     /// Skip the deserializer for this argument.
     raw: bool,
 
     /// Call `TryFrom<&Value>::try_from` for this argument instead of deserializing it.
     try_from_ref: bool,
+
+    /// Wrap the argument in a `perlmod::Callback` via `TryFrom<Value>`.
+    coderef: bool,
+
+    /// Call `perlmod::Validate::validate` on the deserialized argument.
+    validate: Option<Span>,
+
+    /// Pass the class name of a method's invocant via `Value::class_name`.
+    class: bool,
+
+    /// Override the prototype character(s) generated for this argument.
+    proto: Option<syn::LitStr>,
 }
 
 impl ArgumentAttrs {
@@ -32,8 +48,16 @@ impl ArgumentAttrs {
             self.raw = true;
         } else if path.is_ident("try_from_ref") {
             self.try_from_ref = true;
+        } else if path.is_ident("coderef") {
+            self.coderef = true;
         } else if path.is_ident("cv") {
             self.cv = Some(path.span());
+        } else if path.is_ident("context") {
+            self.context = Some(path.span());
+        } else if path.is_ident("validate") {
+            self.validate = Some(path.span());
+        } else if path.is_ident("class") {
+            self.class = true;
         } else {
             return false;
         }
@@ -42,7 +66,27 @@ impl ArgumentAttrs {
     }
 
     fn handle_attr(&mut self, attr: &syn::Attribute) -> bool {
-        if self.handle_path(attr.path()) {
+        if attr.path().is_ident("proto") {
+            match &attr.meta {
+                Meta::NameValue(syn::MetaNameValue {
+                    value:
+                        syn::Expr::Lit(syn::ExprLit {
+                            lit: syn::Lit::Str(litstr),
+                            ..
+                        }),
+                    ..
+                }) => {
+                    if self.proto.is_some() {
+                        error!(&attr.meta => "duplicate 'proto' attribute");
+                    } else if !is_prototype_fragment(&litstr.value()) {
+                        error!(litstr => "not a valid prototype for a single parameter");
+                    }
+                    self.proto = Some(litstr.clone());
+                }
+                _ => error!(&attr.meta => "expected `proto = \"...\"`"),
+            }
+            true
+        } else if self.handle_path(attr.path()) {
             if !matches!(attr.meta, Meta::Path(_)) {
                 error!(&attr.meta => "attribute does not take any value or parameter");
             }
@@ -53,12 +97,40 @@ impl ArgumentAttrs {
     }
 
     fn validate(&self, span: Span) -> Result<(), Error> {
-        if self.raw as usize + self.try_from_ref as usize + self.cv.is_some() as usize > 1 {
+        if self.raw as usize
+            + self.try_from_ref as usize
+            + self.coderef as usize
+            + self.class as usize
+            + self.cv.is_some() as usize
+            + self.context.is_some() as usize
+            > 1
+        {
             bail!(
                 span,
-                "`raw` and `try_from_ref` and `cv` attributes are mutually exclusive"
+                "`raw`, `try_from_ref`, `coderef`, `class`, `cv` and `context` attributes are \
+                 mutually exclusive"
             );
         }
+        if let Some(validate) = self.validate {
+            if self.raw
+                || self.try_from_ref
+                || self.coderef
+                || self.class
+                || self.cv.is_some()
+                || self.context.is_some()
+            {
+                bail!(
+                    validate,
+                    "`validate` can only be used on deserialized parameters"
+                );
+            }
+        }
+        if let (Some(proto), Some(_)) = (&self.proto, self.cv) {
+            bail!(proto => "the `cv` parameter is not passed from perl and has no prototype");
+        }
+        if let (Some(proto), Some(_)) = (&self.proto, self.context) {
+            bail!(proto => "the `context` parameter is not passed from perl and has no prototype");
+        }
         Ok(())
     }
 }
@@ -66,6 +138,15 @@ impl ArgumentAttrs {
 struct Return {
     result: bool,
     value: ReturnValue,
+
+    /// The single return value is a `Vec<(K, V)>`, possibly wrapped in an `Option`.
+    pairs: bool,
+
+    /// The single return value is an `Option`.
+    option: bool,
+
+    /// The extracted values of `&str` parameters, a returned string may be borrowed from.
+    str_sources: Vec<Ident>,
 }
 
 enum ReturnValue {
@@ -109,7 +190,11 @@ pub fn handle_function(
     let mut deserialized_arguments = TokenStream::new();
     let mut passed_arguments = TokenStream::new();
     let mut cv_arg_param = TokenStream::new();
-    for arg in &mut func.sig.inputs {
+    let mut prototype = Vec::new();
+    let mut str_sources = Vec::new();
+    let input_count = func.sig.inputs.len();
+    check_accessor_params(&attr, input_count.min(2))?;
+    for (index, arg) in func.sig.inputs.iter_mut().enumerate() {
         let mut argument_attrs = ArgumentAttrs::default();
 
         let pat_ty = match arg {
@@ -149,6 +234,69 @@ pub fn handle_function(
             continue;
         }
 
+        if argument_attrs.context.is_some() {
+            let context_name = Ident::new(&format!("context_arg_{arg_name}"), arg_name.span());
+            extract_arguments.extend(quote! {
+                let #context_name: #arg_type = ::perlmod::Context::current();
+            });
+            if passed_arguments.is_empty() {
+                passed_arguments.extend(quote! { #context_name });
+            } else {
+                passed_arguments.extend(quote! {, #context_name });
+            }
+            continue;
+        }
+
+        if let syn::Type::Reference(reference) = is_option_type(arg_type).unwrap_or(arg_type) {
+            if reference.mutability.is_some() && !argument_attrs.raw {
+                bail!(
+                    reference =>
+                    "`&mut` parameters would only borrow a temporary copy of the perl value, take \
+                     the parameter by value instead"
+                );
+            }
+        }
+
+        if let (Some(accessor), true) = (&attr.accessor, index + 1 == input_count) {
+            handle_accessor_value(
+                accessor,
+                arg_name,
+                arg_type,
+                &argument_attrs,
+                &mut extract_arguments,
+                &mut deserialized_arguments,
+            )?;
+            prototype.push(("$".to_string(), arg_type.span()));
+            trailing_options += 1;
+            if passed_arguments.is_empty() {
+                passed_arguments.extend(quote! { deserialized_arg_accessor_value });
+            } else {
+                passed_arguments.extend(quote! {, deserialized_arg_accessor_value });
+            }
+            continue;
+        }
+
+        let aggregate = aggregate_ref_type(arg_type);
+        if aggregate.is_some()
+            && (argument_attrs.raw
+                || argument_attrs.try_from_ref
+                || argument_attrs.coderef
+                || argument_attrs.class
+                || argument_attrs.validate.is_some())
+        {
+            bail!(arg_type => "`&perlmod::Array` and `&perlmod::Hash` parameters cannot be combined with other parameter attributes");
+        }
+
+        let (proto_fragment, proto_span) = match &argument_attrs.proto {
+            Some(proto) => (proto.value(), proto.span()),
+            None => match aggregate {
+                Some(Aggregate::Array) => ("\\@".to_string(), arg_type.span()),
+                Some(Aggregate::Hash) => ("\\%".to_string(), arg_type.span()),
+                None => ("$".to_string(), arg_type.span()),
+            },
+        };
+        prototype.push((proto_fragment, proto_span));
+
         let extracted_name = Ident::new(&format!("extracted_arg_{arg_name}"), arg_name.span());
         let deserialized_name =
             Ident::new(&format!("deserialized_arg_{arg_name}"), arg_name.span());
@@ -180,6 +328,33 @@ pub fn handle_function(
             };
         });
 
+        if let Some(aggregate) = aggregate {
+            let (variant, type_message) = match aggregate {
+                Aggregate::Array => (quote! { Array }, "an array"),
+                Aggregate::Hash => (quote! { Hash }, "a hash"),
+            };
+            let type_message = syn::LitStr::new(
+                &format!("parameter '{arg_name}' must be {type_message} reference\n"),
+                arg_name.span(),
+            );
+            deserialized_arguments.extend(quote! {
+                let #deserialized_name = match #extracted_name.dereference() {
+                    Some(::perlmod::Value::#variant(inner)) => inner,
+                    _ => {
+                        return Err(::perlmod::Value::new_string(#type_message)
+                            .into_mortal()
+                            .into_raw());
+                    }
+                };
+            });
+            if passed_arguments.is_empty() {
+                passed_arguments.extend(quote! { &#deserialized_name });
+            } else {
+                passed_arguments.extend(quote! {, &#deserialized_name });
+            }
+            continue;
+        }
+
         if argument_attrs.raw {
             deserialized_arguments.extend(quote! {
                 let #deserialized_name = #extracted_name;
@@ -196,7 +371,42 @@ pub fn handle_function(
                         }
                     };
             });
+        } else if argument_attrs.class {
+            deserialized_arguments.extend(quote! {
+                let #deserialized_name: #arg_type = match #extracted_name.class_name() {
+                    Ok(class) => class,
+                    Err(err) => {
+                        return Err(::perlmod::Value::new_string(&format!("{}\n", err))
+                            .into_mortal()
+                            .into_raw());
+                    }
+                };
+            });
+        } else if argument_attrs.coderef {
+            deserialized_arguments.extend(quote! {
+                let #deserialized_name: #arg_type =
+                    match ::std::convert::TryFrom::try_from(#extracted_name) {
+                        Ok(arg) => arg,
+                        Err(err) => {
+                            return Err(::perlmod::Value::new_string(&format!("{}\n", err))
+                                .into_mortal()
+                                .into_raw());
+                        }
+                    };
+            });
         } else {
+            if let Some(reference) =
+                unsupported_ref_type(is_option_type(arg_type).unwrap_or(arg_type))
+            {
+                bail!(
+                    reference =>
+                    "only `&str` and `&[u8]` can be borrowed from perl values, use an owned type \
+                     or add `#[raw]` or `#[try_from_ref]` to the parameter"
+                );
+            }
+            if is_str_ref_type(is_option_type(arg_type).unwrap_or(arg_type)) {
+                str_sources.push(extracted_name.clone());
+            }
             deserialized_arguments.extend(quote! {
                 let #deserialized_name: #arg_type =
                     match ::perlmod::from_ref_value(&#extracted_name) {
@@ -208,6 +418,19 @@ pub fn handle_function(
                         }
                     };
             });
+            if argument_attrs.validate.is_some() {
+                let invalid_message = syn::LitStr::new(
+                    &format!("invalid parameter '{arg_name}': {{}}\n"),
+                    arg_name.span(),
+                );
+                deserialized_arguments.extend(quote! {
+                    if let Err(err) = ::perlmod::Validate::validate(&#deserialized_name) {
+                        return Err(::perlmod::Value::new_string(&format!(#invalid_message, err))
+                            .into_mortal()
+                            .into_raw());
+                    }
+                });
+            }
         }
 
         if passed_arguments.is_empty() {
@@ -221,28 +444,61 @@ pub fn handle_function(
         syn::ReturnType::Default => Return {
             result: false,
             value: ReturnValue::None,
+            pairs: false,
+            option: false,
+            str_sources: Vec::new(),
         },
         syn::ReturnType::Type(_arrow, ty) => match get_result_type(ty) {
             (syn::Type::Tuple(tuple), result) if tuple.elems.is_empty() => Return {
                 result,
                 value: ReturnValue::None,
+                pairs: false,
+                option: false,
+                str_sources: Vec::new(),
             },
             (syn::Type::Tuple(tuple), result) => Return {
                 result,
                 value: ReturnValue::Tuple(tuple.elems.len()),
+                pairs: false,
+                option: false,
+                str_sources: Vec::new(),
             },
-            (_, result) => Return {
+            (ty, result) => Return {
                 result,
                 value: ReturnValue::Single,
+                pairs: is_pairs_type(is_option_type(ty).unwrap_or(ty)),
+                option: is_option_type(ty).is_some(),
+                str_sources,
             },
         },
     };
 
+    if attr.option_as_list {
+        let returns_option = match &func.sig.output {
+            syn::ReturnType::Type(_arrow, ty) => is_option_type(get_result_type(ty).0).is_some(),
+            syn::ReturnType::Default => false,
+        };
+        if !returns_option {
+            bail!(&func.sig.output => "option_as_list requires an `Option<T>` return type");
+        }
+    }
+
+    check_accessor_params(&attr, prototype.len())?;
+
+    if let Some((_, init)) = prototype.split_last() {
+        if let Some((_, span)) = init.iter().find(|(p, _)| p == "@" || p == "%") {
+            bail!(
+                *span,
+                "slurpy prototypes (`@` or `%`) are only allowed on the last parameter"
+            );
+        }
+    }
+
     let too_many_args_error = syn::LitStr::new(
         &format!(
             "too many parameters for function '{}', (expected {})\n",
             name,
-            func.sig.inputs.len() - (!cv_arg_param.is_empty()) as usize
+            prototype.len()
         ),
         Span::call_site(),
     );
@@ -262,11 +518,51 @@ pub fn handle_function(
         !cv_arg_param.is_empty(),
     )?;
 
+    let prototype = attr
+        .prototype
+        .clone()
+        .or_else(|| Some(gen_prototype(&prototype, trailing_options)));
+
+    // Standalone exports need to be registered manually, so provide their metadata:
+    let meta = match export_public {
+        Some(vis) => {
+            let meta_name = Ident::new(&format!("{name}_meta"), name.span());
+            let xs_name_str = xs_name.to_string();
+            let perl_name_str = attr.perl_name.as_ref().unwrap_or(&name).to_string();
+            let aliases = attr.aliases.iter().map(|alias| alias.to_string());
+            let meta_prototype = match &prototype {
+                Some(proto) => quote! { Some(#proto) },
+                None => quote! { None },
+            };
+            let doc = format!("Metadata for the xsub exported for [`{name}`](fn@{name}).");
+            quote! {
+                #[doc = #doc]
+                #[allow(dead_code)]
+                #vis mod #meta_name {
+                    /// The symbol name of the xsub.
+                    pub const XS_NAME: &str = #xs_name_str;
+                    /// The name the function should be installed as in perl.
+                    pub const PERL_NAME: &str = #perl_name_str;
+                    /// Additional names the function should be installed as.
+                    pub const ALIASES: &[&str] = &[#(#aliases),*];
+                    /// The prototype to use when registering the xsub.
+                    pub const PROTOTYPE: Option<&str> = #meta_prototype;
+                    /// The xsub itself, to be passed to `newXS`.
+                    pub const XSUB: ::perlmod::perl_fn!(extern "C" fn(*mut ::perlmod::ffi::CV)) =
+                        super::#xs_name;
+                }
+            }
+        }
+        None => TokenStream::new(),
+    };
+
     let tokens = quote! {
         #func
 
         #wrapper_func
 
+        #meta
+
         #[inline(never)]
         #[allow(non_snake_case)]
         fn #impl_xs_name(#cv_arg_param) -> Result<#return_type, *mut ::perlmod::ffi::SV> {
@@ -296,31 +592,214 @@ pub fn handle_function(
     Ok(XSub {
         rust_name: name,
         perl_name: attr.perl_name,
+        aliases: attr.aliases,
         xs_name,
         tokens,
-        prototype: attr
-            .prototype
-            .or_else(|| Some(gen_prototype(func.sig.inputs.len(), trailing_options))),
+        prototype,
     })
 }
 
-fn gen_prototype(arg_count: usize, trailing_options: usize) -> String {
-    let arg_count = arg_count - trailing_options;
+/// Accessors take the invocant and the value as parameters, plus optionally the `cv`.
+fn check_accessor_params(attr: &FunctionAttrs, count: usize) -> Result<(), Error> {
+    match &attr.accessor {
+        Some(accessor) if count != 2 => bail!(
+            accessor =>
+            "accessors take exactly 2 parameters: the invocant and an `Option` of the value"
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Handle the value parameter of an `accessor`, which must be the last parameter and an `Option`.
+///
+/// Contrary to other optional parameters, this is `Some` whenever a value was passed, even if it
+/// is `undef`, so `$obj->name(undef)` calls the setter rather than the getter.
+fn handle_accessor_value(
+    accessor: &Ident,
+    arg_name: &Ident,
+    arg_type: &syn::Type,
+    argument_attrs: &ArgumentAttrs,
+    extract_arguments: &mut TokenStream,
+    deserialized_arguments: &mut TokenStream,
+) -> Result<(), Error> {
+    let Some(inner_type) = is_option_type(arg_type) else {
+        bail!(arg_type => "the value parameter of an accessor must be an `Option`");
+    };
+
+    if argument_attrs.try_from_ref
+        || argument_attrs.coderef
+        || argument_attrs.class
+        || argument_attrs.proto.is_some()
+    {
+        bail!(
+            arg_name =>
+            "the value parameter of accessor '{accessor}' only supports the `raw` and `validate` \
+             attributes"
+        );
+    }
+
+    extract_arguments.extend(quote! {
+        let extracted_arg_accessor_value: Option<::perlmod::Value> =
+            args.next().map(::perlmod::Value::from);
+    });
+
+    if argument_attrs.raw {
+        deserialized_arguments.extend(quote! {
+            let deserialized_arg_accessor_value = extracted_arg_accessor_value;
+        });
+        return Ok(());
+    }
+
+    if let Some(reference) = unsupported_ref_type(inner_type) {
+        bail!(
+            reference =>
+            "only `&str` and `&[u8]` can be borrowed from perl values, use an owned type or add \
+             `#[raw]` to the parameter"
+        );
+    }
+
+    deserialized_arguments.extend(quote! {
+        let deserialized_arg_accessor_value: #arg_type = match &extracted_arg_accessor_value {
+            None => None,
+            Some(value) => match ::perlmod::from_ref_value(value) {
+                Ok(data) => Some(data),
+                Err(err) => {
+                    return Err(::perlmod::Value::new_string(&format!("{}\n", err))
+                        .into_mortal()
+                        .into_raw());
+                }
+            },
+        };
+    });
+
+    if argument_attrs.validate.is_some() {
+        let invalid_message = syn::LitStr::new(
+            &format!("invalid parameter '{arg_name}': {{}}\n"),
+            arg_name.span(),
+        );
+        deserialized_arguments.extend(quote! {
+            if let Err(err) = ::perlmod::Validate::validate(&deserialized_arg_accessor_value) {
+                return Err(::perlmod::Value::new_string(&format!(#invalid_message, err))
+                    .into_mortal()
+                    .into_raw());
+            }
+        });
+    }
+
+    Ok(())
+}
+
+fn gen_prototype(args: &[(String, Span)], trailing_options: usize) -> String {
+    let required = args.len() - trailing_options;
 
-    let mut proto = String::with_capacity(arg_count + trailing_options + 1);
+    let mut proto = String::with_capacity(args.len() + 1);
 
-    for _ in 0..arg_count {
-        proto.push('$');
+    for (fragment, _) in &args[..required] {
+        proto.push_str(fragment);
     }
     if trailing_options > 0 {
         proto.push(';');
-        for _ in 0..trailing_options {
-            proto.push('$');
+        for (fragment, _) in &args[required..] {
+            proto.push_str(fragment);
         }
     }
     proto
 }
 
+/// Check whether `proto` is a valid prototype for a single parameter, such as `$`, `\@` or
+/// `\[$@%]`.
+fn is_prototype_fragment(proto: &str) -> bool {
+    const REF_CHARS: &str = "$@%&*";
+
+    match proto {
+        "$" | "@" | "%" | "&" | "*" | "+" | "_" => true,
+        _ => match proto.strip_prefix('\\') {
+            Some(one) if one.len() == 1 => REF_CHARS.contains(one),
+            Some(group) => match group.strip_prefix('[').and_then(|g| g.strip_suffix(']')) {
+                Some(chars) => !chars.is_empty() && chars.chars().all(|c| REF_CHARS.contains(c)),
+                None => false,
+            },
+            None => false,
+        },
+    }
+}
+
+enum Aggregate {
+    Array,
+    Hash,
+}
+
+/// Check for `&perlmod::Array` and `&perlmod::Hash` parameter types. The path is required, so
+/// that other types which happen to be called `Array` or `Hash` are not affected.
+fn aggregate_ref_type(ty: &syn::Type) -> Option<Aggregate> {
+    let syn::Type::Reference(reference) = ty else {
+        return None;
+    };
+    if reference.mutability.is_some() {
+        return None;
+    }
+    let syn::Type::Path(path) = &*reference.elem else {
+        return None;
+    };
+    if path.qself.is_some() {
+        return None;
+    }
+    let segments = &path.path.segments;
+    if segments.len() != 2
+        || segments[0].ident != "perlmod"
+        || segments.iter().any(|segment| !segment.arguments.is_empty())
+    {
+        return None;
+    }
+    if segments[1].ident == "Array" {
+        Some(Aggregate::Array)
+    } else if segments[1].ident == "Hash" {
+        Some(Aggregate::Hash)
+    } else {
+        None
+    }
+}
+
+/// Check for a `&str` type.
+fn is_str_ref_type(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Reference(reference) => match &*reference.elem {
+            syn::Type::Path(path) => path.qself.is_none() && path.path.is_ident("str"),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Check for reference types which cannot be deserialized by borrowing from a perl value, which is
+/// anything other than `&str` and `&[u8]` (or `&serde_bytes::Bytes`).
+fn unsupported_ref_type(ty: &syn::Type) -> Option<&syn::TypeReference> {
+    let syn::Type::Reference(reference) = ty else {
+        return None;
+    };
+    if reference.mutability.is_some() {
+        return Some(reference);
+    }
+    let supported = match &*reference.elem {
+        syn::Type::Path(path) => {
+            path.qself.is_none()
+                && path.path.segments.last().is_some_and(|last| {
+                    last.arguments.is_empty() && (last.ident == "str" || last.ident == "Bytes")
+                })
+        }
+        syn::Type::Slice(slice) => match &*slice.elem {
+            syn::Type::Path(path) => path.qself.is_none() && path.path.is_ident("u8"),
+            _ => false,
+        },
+        _ => false,
+    };
+    if supported {
+        None
+    } else {
+        Some(reference)
+    }
+}
+
 struct ReturnHandling {
     return_type: TokenStream,
     handle_return: TokenStream,
@@ -355,9 +834,23 @@ fn handle_return_kind(
         (quote! { _cv }, TokenStream::new())
     };
 
+    let set_error_errno = if attr.errno {
+        quote! {
+            {
+                use ::perlmod::error::{ErrnoProbeFallback, ErrnoProbeHasErrno};
+                if let Some(errno) = (&::perlmod::error::ErrnoProbe(&err)).perlmod_errno() {
+                    ::perlmod::error::set_errno(errno);
+                }
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
     let return_error = if ret.result {
         if attr.serialize_error {
             quote! {
+                #set_error_errno
                 match ::perlmod::to_value(&err) {
                     Ok(err) => return Err(err.into_mortal().into_raw()),
                     Err(err) => {
@@ -369,6 +862,7 @@ fn handle_return_kind(
             }
         } else {
             quote! {
+                #set_error_errno
                 return Err(::perlmod::Value::new_string(&format!("{}\n", err))
                     .into_mortal()
                     .into_raw());
@@ -384,6 +878,45 @@ fn handle_return_kind(
         TokenStream::new()
     };
 
+    if let Some(span) = attr.return_ok_true {
+        if !(ret.result && matches!(ret.value, ReturnValue::None)) {
+            bail!(
+                span,
+                "return_ok_true requires a `Result<(), E>` return type"
+            );
+        }
+    }
+
+    if let Some(span) = attr.map_as_list {
+        if !matches!(ret.value, ReturnValue::Single) {
+            bail!(span, "map_as_list requires a single return value");
+        }
+    }
+
+    if let Some(span) = attr.empty_as_undef {
+        if attr.raw_return || !matches!(ret.value, ReturnValue::Single) {
+            bail!(
+                span,
+                "empty_as_undef requires a single serialized return value"
+            );
+        }
+    }
+
+    if let Some(span) = attr.substr_return {
+        if attr.raw_return || !matches!(ret.value, ReturnValue::Single) {
+            bail!(
+                span,
+                "substr_return requires a single serialized return value"
+            );
+        }
+        if ret.str_sources.is_empty() {
+            bail!(
+                span,
+                "substr_return requires a `&str` parameter to return substrings of"
+            );
+        }
+    }
+
     let pthx = crate::pthx_param();
     match ret.value {
         ReturnValue::None => {
@@ -410,6 +943,16 @@ fn handle_return_kind(
                 };
             }
 
+            let handle_ok = if attr.return_ok_true.is_some() {
+                quote! {
+                    ::perlmod::ffi::stack_push_raw(
+                        ::perlmod::Scalar::new_yes().into_mortal().into_raw(),
+                    )
+                }
+            } else {
+                quote! { () }
+            };
+
             wrapper_func = quote! {
                 #[doc(hidden)]
                 #vis extern "C" fn #xs_name(#pthx #cv_arg_name: *mut ::perlmod::ffi::CV) {
@@ -417,7 +960,7 @@ fn handle_return_kind(
                         let res = #impl_xs_name(#cv_arg_passed);
                         #copy_errno
                         match res {
-                            Ok(()) => (),
+                            Ok(()) => #handle_ok,
                             Err(sv) => ::perlmod::ffi::croak(sv),
                         }
                     }
@@ -440,13 +983,62 @@ fn handle_return_kind(
                 };
             }
 
-            if attr.raw_return {
+            if attr.option_as_list {
+                handle_return.extend(quote! {
+                    let result = match result {
+                        Some(result) => result,
+                        None if ::perlmod::ffi::gimme() == ::perlmod::ffi::Gimme::List => {
+                            return Ok(::std::ptr::null_mut());
+                        }
+                        None => {
+                            return Ok(::perlmod::Value::new_undef().into_mortal().into_raw());
+                        }
+                    };
+                });
+            }
+
+            if attr.raw_return && ret.option && !attr.option_as_list {
+                handle_return.extend(quote! {
+                    Ok(result
+                        .unwrap_or_else(::perlmod::Value::new_undef)
+                        .into_mortal()
+                        .into_raw())
+                });
+            } else if attr.raw_return {
                 handle_return.extend(quote! {
                     Ok(result.into_mortal().into_raw())
                 });
+            } else if attr.map_as_list.is_some() && ret.pairs {
+                // `Vec<(K, V)>` returns are turned into hashes
+                handle_return.extend(quote! {
+                    let flat = ::perlmod::ffi::gimme() == ::perlmod::ffi::Gimme::List;
+                    match ::perlmod::ser::pairs_to_value(&result, flat) {
+                        Ok(value) => Ok(value.into_mortal().into_raw()),
+                        Err(err) => Err(::perlmod::Value::new_string(&format!("{}\n", err))
+                            .into_mortal()
+                            .into_raw()),
+                    }
+                });
             } else {
+                let to_return_value = if attr.substr_return.is_some() {
+                    // a returned `&str` or `Cow<str>` may be a substring of a `&str` parameter
+                    let empty_as_undef = attr.empty_as_undef.is_some();
+                    let str_sources = &ret.str_sources;
+                    quote! {
+                        ::perlmod::ser::to_return_value_with(
+                            &result,
+                            #empty_as_undef,
+                            &[#(&#str_sources),*],
+                        )
+                    }
+                } else if attr.empty_as_undef.is_some() {
+                    quote! { ::perlmod::ser::to_return_value_empty_as_undef(&result) }
+                } else {
+                    quote! { ::perlmod::ser::to_return_value(&result) }
+                };
+                // tuple structs are returned as a list, see `stack_push_list`
                 handle_return.extend(quote! {
-                    match ::perlmod::to_value(&result) {
+                    match #to_return_value {
                         Ok(value) => Ok(value.into_mortal().into_raw()),
                         Err(err) => Err(::perlmod::Value::new_string(&format!("{}\n", err))
                             .into_mortal()
@@ -455,6 +1047,34 @@ fn handle_return_kind(
                 });
             };
 
+            let push = if attr.map_as_list.is_some() && ret.pairs {
+                // pairs are already flattened into an array in list context
+                quote! { ::perlmod::ffi::stack_push_list(sv) }
+            } else if attr.map_as_list.is_some() {
+                quote! {
+                    if ::perlmod::ffi::gimme() == ::perlmod::ffi::Gimme::List {
+                        ::perlmod::ffi::stack_push_flattened(sv)
+                    } else {
+                        ::perlmod::ffi::stack_push_raw(sv)
+                    }
+                }
+            } else if attr.raw_return {
+                quote! { ::perlmod::ffi::stack_push_raw(sv) }
+            } else {
+                quote! { ::perlmod::ffi::stack_push_list(sv) }
+            };
+
+            let handle_ok = if attr.option_as_list {
+                // a null pointer means an empty list
+                quote! {
+                    if !sv.is_null() {
+                        #push
+                    }
+                }
+            } else {
+                push
+            };
+
             wrapper_func = quote! {
                 #[doc(hidden)]
                 #vis extern "C" fn #xs_name(#pthx #cv_arg_name: *mut ::perlmod::ffi::CV) {
@@ -462,7 +1082,7 @@ fn handle_return_kind(
                         let res = #impl_xs_name(#cv_arg_passed);
                         #copy_errno
                         match res {
-                            Ok(sv) => ::perlmod::ffi::stack_push_raw(sv),
+                            Ok(sv) => #handle_ok,
                             Err(sv) => ::perlmod::ffi::croak(sv),
                         }
                     }
@@ -606,6 +1226,33 @@ fn simple_usize(i: usize, span: Span) -> syn::LitInt {
     syn::LitInt::new(&format!("{i}"), span)
 }
 
+/// Check for a `Vec<(K, V)>`.
+fn is_pairs_type(ty: &syn::Type) -> bool {
+    let syn::Type::Path(p) = ty else {
+        return false;
+    };
+    if p.qself.is_some() {
+        return false;
+    }
+    let segs = &p.path.segments;
+    let is_vec = match segs.len() {
+        1 => segs[0].ident == "Vec",
+        3 => segs[0].ident == "std" && segs[1].ident == "vec" && segs[2].ident == "Vec",
+        _ => false,
+    };
+    if !is_vec {
+        return false;
+    }
+
+    match &segs.last().unwrap().arguments {
+        syn::PathArguments::AngleBracketed(generic) if generic.args.len() == 1 => matches!(
+            generic.args.first(),
+            Some(syn::GenericArgument::Type(syn::Type::Tuple(tuple))) if tuple.elems.len() == 2
+        ),
+        _ => false,
+    }
+}
+
 /// Note that we cannot handle renamed imports at all here...
 pub fn is_option_type(ty: &syn::Type) -> Option<&syn::Type> {
     if let syn::Type::Path(p) = ty {
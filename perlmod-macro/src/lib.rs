@@ -50,7 +50,8 @@ pub(crate) fn pthx_param() -> TokenStream {
 /// Attribute for making a perl "package" out of a rust module.
 ///
 /// This can be used on an inline module (rust version 1.42 or later), and hopefully in the future
-/// as an inline attribute (`#![package(name = "Some::Package")]`).
+/// as an inline attribute (`#![package(name = "Some::Package")]`). To make a package out of
+/// functions defined elsewhere, see [`make_package!`](macro@make_package).
 ///
 /// This attribute takes the following parameters:
 /// * `name = "Perl::Packagee::Name"`. Required. The package name in perl.
@@ -58,8 +59,14 @@ pub(crate) fn pthx_param() -> TokenStream {
 ///   suffix) this is found in. Usually just the 'cdylib' name of the rust library.
 /// * `file = "/file/path.pm"`. Optional. The `.pm` file where this module is to be found.
 /// * `write = true`. Optional. Write a `file` at compile time. (Meant for testing only!).
+/// * `tests = "t/smoke.t"`. Optional. Write a minimal `Test::More` script to this path at compile
+///   time, which loads the package and checks that its exported functions exist.
 /// * `boot = "function_name"`. Optional. A function within the package that is executed at *load*
 ///   time by the `bootstrap` function.
+/// * `pre_boot = "function_name"`. Optional. Like `boot`, but executed *before* the package's
+///   functions are installed, for instance to set up state they depend on.
+/// * `xs_file = "lib/Foo.rs"`. Optional. The file name perl reports for the package's functions,
+///   for instance in `Carp` traces. Defaults to the rust source file containing the package.
 ///
 /// ```
 /// // 'lib' and 'file' are optional. We use 'file' here to prevent doc tests from writing out the
@@ -99,6 +106,45 @@ pub fn export(attr: TokenStream_1, item: TokenStream_1) -> TokenStream_1 {
     handle_error(export_impl(attr, item)).into()
 }
 
+/// Create a perl package out of standalone [`export`](macro@export)ed functions defined elsewhere,
+/// for instance spread over multiple files.
+///
+/// The package is declared like a [`package`](macro@package) module with the same parameters,
+/// except for `tests`, but the module only lists the paths to the functions. Since the module
+/// gets generated, the paths are relative to it, so usually start with `crate::` or `super::`.
+///
+/// The functions' perl names, aliases and prototypes are taken from their `<function>_meta`
+/// modules, which need to be reachable from the generated module.
+///
+/// ```
+/// mod functions {
+///     #[perlmod::export]
+///     pub fn double(value: u32) -> u32 {
+///         value * 2
+///     }
+///
+///     #[perlmod::export(name = "half")]
+///     pub fn halve(value: u32) -> u32 {
+///         value / 2
+///     }
+/// }
+///
+/// perlmod::make_package! {
+///     #[package(name = "RSPM::Doc::Made", file = "/dev/null")]
+///     pub mod made {
+///         crate::functions::double,
+///         crate::functions::halve,
+///     }
+/// }
+/// # fn main() {}
+/// ```
+#[proc_macro]
+pub fn make_package(item: TokenStream_1) -> TokenStream_1 {
+    let _error_guard = init_local_error();
+    let input = syn::parse_macro_input!(item as module::MakePackage);
+    handle_error(module::handle_make_package(input)).into()
+}
+
 fn perlmod_impl(attr: TokenStream_1, item: TokenStream) -> Result<TokenStream, Error> {
     let attr = Punctuated::<Meta, Token![,]>::parse_terminated.parse(attr)?;
     let item: syn::Item = syn::parse2(item)?;
@@ -1,6 +1,7 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Ident, Span, TokenStream};
 
 use quote::quote;
+use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
 use syn::{Error, Meta, Token};
 
@@ -57,6 +58,7 @@ pub fn handle_module(
                         package.export_named(
                             func.rust_name,
                             func.perl_name,
+                            func.aliases,
                             func.xs_name,
                             func.prototype,
                         );
@@ -71,12 +73,77 @@ pub fn handle_module(
         items.push(syn::Item::Verbatim(package.bootstrap_function()));
     }
 
-    if package.attrs.write == Some(true)
-        || package.attrs.file_name.is_some()
-        || std::env::var("PERLMOD_WRITE_PACKAGES").ok().as_deref() == Some("1")
-    {
-        package.write()?;
-    }
+    package.write_requested()?;
 
     Ok(quote! { #module })
 }
+
+/// The input of `make_package!`: a `#[package(...)]` attribute on a module declaration listing
+/// standalone exports instead of items.
+pub struct MakePackage {
+    attr: Punctuated<Meta, Token![,]>,
+    vis: syn::Visibility,
+    ident: syn::Ident,
+    functions: Punctuated<syn::Path, Token![,]>,
+}
+
+impl Parse for MakePackage {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let span = input.span();
+        let mut attr = None;
+        for outer in input.call(syn::Attribute::parse_outer)? {
+            if !outer.path().is_ident("package") || attr.is_some() {
+                bail!(outer => "expected a single 'package' attribute");
+            }
+            attr = Some(outer.parse_args_with(Punctuated::parse_terminated)?);
+        }
+        let Some(attr) = attr else {
+            bail!(span, "missing 'package' attribute");
+        };
+
+        let vis = input.parse()?;
+        input.parse::<Token![mod]>()?;
+        let ident = input.parse()?;
+        let content;
+        syn::braced!(content in input);
+        let functions = Punctuated::parse_terminated(&content)?;
+
+        Ok(Self {
+            attr,
+            vis,
+            ident,
+            functions,
+        })
+    }
+}
+
+pub fn handle_make_package(input: MakePackage) -> Result<TokenStream, Error> {
+    let mut package = Package::with_attrs(input.attr)?;
+    if package.attrs.tests.is_some() {
+        bail!(
+            Span::call_site(),
+            "'tests' is not supported for packages of standalone exports"
+        );
+    }
+
+    for mut function in input.functions {
+        // the function's metadata lives in the `<function>_meta` module next to it
+        let last = function
+            .segments
+            .last_mut()
+            .ok_or_else(|| format_err!(Span::call_site(), "empty function path"))?;
+        last.ident = Ident::new(&format!("{}_meta", last.ident), last.ident.span());
+        package.export_meta(function);
+    }
+
+    package.write_requested()?;
+
+    let vis = &input.vis;
+    let ident = &input.ident;
+    let bootstrap = package.bootstrap_function();
+    Ok(quote! {
+        #vis mod #ident {
+            #bootstrap
+        }
+    })
+}
@@ -36,7 +36,7 @@ const MODULE_HEAD_2: &str = r#"@dirs, $mod_name);
 
     my $sym  = DynaLoader::dl_find_symbol($lib, $bootstrap_name);
     die "failed to locate '$bootstrap_name'\n" if !defined $sym;
-    my $boot = DynaLoader::dl_install_xsub($bootstrap_name, $sym, "src/FIXME.rs");
+    my $boot = DynaLoader::dl_install_xsub($bootstrap_name, $sym, {{XS_FILE}});
     $boot->();
 }
 
@@ -45,9 +45,20 @@ __PACKAGE__->bootstrap;
 1;
 "#;
 
+const TESTS_HEAD: &str = r#"#!/usr/bin/perl
+
+use strict;
+use warnings;
+
+use Test::More;
+
+use_ok('{{PACKAGE}}');
+"#;
+
 struct Export {
     rust_name: Ident,
     perl_name: Option<Ident>,
+    aliases: Vec<Ident>,
     xs_name: Ident,
     prototype: Option<String>,
 }
@@ -55,6 +66,8 @@ struct Export {
 pub struct Package {
     pub attrs: ModuleAttrs,
     exported: Vec<Export>,
+    /// Paths to the `<function>_meta` modules of standalone exports, see `make_package!`.
+    exported_meta: Vec<syn::Path>,
 }
 
 impl Package {
@@ -62,6 +75,7 @@ impl Package {
         Ok(Self {
             attrs: ModuleAttrs::try_from(attr)?,
             exported: Vec::new(),
+            exported_meta: Vec::new(),
         })
     }
 
@@ -69,23 +83,37 @@ impl Package {
         &mut self,
         rust_name: Ident,
         perl_name: Option<Ident>,
+        aliases: Vec<Ident>,
         xs_name: Ident,
         prototype: Option<String>,
     ) {
         self.exported.push(Export {
             rust_name,
             perl_name,
+            aliases,
             xs_name,
             prototype,
         });
     }
 
+    /// Register a standalone export via the path to its generated `<function>_meta` module.
+    pub fn export_meta(&mut self, meta: syn::Path) {
+        self.exported_meta.push(meta);
+    }
+
     pub fn bootstrap_function(&self) -> TokenStream {
+        let xs_file = match &self.attrs.xs_file {
+            Some(xs_file) => {
+                let xs_file =
+                    syn::LitByteStr::new(format!("{xs_file}\0").as_bytes(), Span::call_site());
+                quote! { #xs_file.as_ptr() as *const i8 }
+            }
+            None => quote! { concat!(::std::file!(), "\0").as_bytes().as_ptr() as *const i8 },
+        };
+
         let mut newxs = TokenStream::new();
         for export in &self.exported {
             let perl_name = export.perl_name.as_ref().unwrap_or(&export.rust_name);
-            let sub_name = format!("{}::{}\0", self.attrs.package_name, perl_name);
-            let sub_lit = syn::LitByteStr::new(sub_name.as_bytes(), perl_name.span());
 
             let xs_name = &export.xs_name;
 
@@ -96,14 +124,48 @@ impl Package {
                 None => quote!(::std::ptr::null()),
             };
 
+            for name in std::iter::once(perl_name).chain(&export.aliases) {
+                let sub_name = format!("{}::{}\0", self.attrs.package_name, name);
+                let sub_lit = syn::LitByteStr::new(sub_name.as_bytes(), name.span());
+
+                newxs.extend(quote! {
+                    RSPL_newXS_flags(
+                        #sub_lit.as_ptr() as *const i8,
+                        #xs_name as _,
+                        #xs_file,
+                        #prototype,
+                        0,
+                    );
+                });
+            }
+        }
+
+        // the metadata of standalone exports is only known at runtime
+        for meta in &self.exported_meta {
+            let package_name = &self.attrs.package_name;
             newxs.extend(quote! {
-                RSPL_newXS_flags(
-                    #sub_lit.as_ptr() as *const i8,
-                    #xs_name as _,
-                    concat!(::std::file!(), "\0").as_bytes().as_ptr() as *const i8,
-                    #prototype,
-                    0,
-                );
+                {
+                    use #meta as meta;
+
+                    let prototype =
+                        meta::PROTOTYPE.map(|proto| ::std::ffi::CString::new(proto).unwrap());
+                    let prototype = match &prototype {
+                        Some(proto) => proto.as_ptr(),
+                        None => ::std::ptr::null(),
+                    };
+                    let names = meta::ALIASES.iter().copied();
+                    for name in ::std::iter::once(meta::PERL_NAME).chain(names) {
+                        let sub_name = format!("{}::{}", #package_name, name);
+                        let sub_name = ::std::ffi::CString::new(sub_name).unwrap();
+                        RSPL_newXS_flags(
+                            sub_name.as_ptr(),
+                            meta::XSUB as _,
+                            #xs_file,
+                            prototype,
+                            0,
+                        );
+                    }
+                }
             });
         }
 
@@ -115,19 +177,34 @@ impl Package {
             None => TokenStream::new(),
         };
 
+        let pre_boot = match &self.attrs.pre_boot {
+            Some(pre_boot) => quote! { #pre_boot(); },
+            None => TokenStream::new(),
+        };
+
         quote! {
             #[no_mangle]
             pub extern "C" fn #bootstrap_ident(
                 _cv: Option<&::perlmod::ffi::CV>,
             ) {
+                unsafe {
+                    let argmark = ::perlmod::ffi::pop_arg_mark();
+                    argmark.set_stack();
+                }
+
+                perlmod_bootstrap();
+            }
+
+            /// Register this package's exported functions, see `perlmod::combined_bootstrap!`.
+            #[doc(hidden)]
+            pub fn perlmod_bootstrap() {
                 static ONCE: ::std::sync::Once = ::std::sync::Once::new();
                 ONCE.call_once(|| {
+                    #pre_boot
+
                     unsafe {
                         use ::perlmod::ffi::RSPL_newXS_flags;
 
-                        let argmark = ::perlmod::ffi::pop_arg_mark();
-                        argmark.set_stack();
-
                         #newxs
                     }
 
@@ -137,6 +214,22 @@ impl Package {
         }
     }
 
+    /// Write the `.pm` file and tests if requested via the attributes or the environment.
+    pub fn write_requested(&self) -> Result<(), Error> {
+        if self.attrs.write == Some(true)
+            || self.attrs.file_name.is_some()
+            || std::env::var("PERLMOD_WRITE_PACKAGES").ok().as_deref() == Some("1")
+        {
+            self.write()?;
+        }
+
+        if let Some(tests) = &self.attrs.tests {
+            self.write_tests(tests)?;
+        }
+
+        Ok(())
+    }
+
     pub fn write(&self) -> Result<(), Error> {
         let mut source = format!(
             "package {};\n{}{}{}",
@@ -144,12 +237,19 @@ impl Package {
         );
 
         if let Some(lib) = &self.attrs.lib_name {
-            source = source.replace("{{LIB_NAME}}", &format!("('{lib}')"));
+            source = source.replace("{{LIB_NAME}}", &format!("({})", perl_quote(lib)));
         } else {
             let lib_name = get_default_lib_name(Span::call_site())?;
-            source = source.replace("{{LIB_NAME}}", &format!("('{lib_name}')"));
+            source = source.replace("{{LIB_NAME}}", &format!("({})", perl_quote(&lib_name)));
         }
 
+        // like `file!()` used for `newXS` in the generated rust code
+        let xs_file = match &self.attrs.xs_file {
+            Some(xs_file) => xs_file.clone(),
+            None => proc_macro::Span::call_site().file(),
+        };
+        source = source.replace("{{XS_FILE}}", &perl_quote(&xs_file));
+
         let file_name = self
             .attrs
             .file_name
@@ -165,6 +265,32 @@ impl Package {
         Ok(())
     }
 
+    /// Write a `Test::More` smoke test loading the package and checking for its exported subs.
+    pub fn write_tests(&self, file_name: &str) -> Result<(), Error> {
+        let package = &self.attrs.package_name;
+
+        let mut names = Vec::new();
+        for export in &self.exported {
+            let name = export.perl_name.as_ref().unwrap_or(&export.rust_name);
+            names.push(format!("'{name}'"));
+            names.extend(export.aliases.iter().map(|alias| format!("'{alias}'")));
+        }
+
+        let mut source = TESTS_HEAD.replace("{{PACKAGE}}", package);
+        if !names.is_empty() {
+            source.push_str(&format!("can_ok('{package}', {});\n", names.join(", ")));
+        }
+        source.push_str("\ndone_testing();\n");
+
+        let path = std::path::Path::new(file_name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_err)?;
+        }
+        std::fs::write(path, source.as_bytes()).map_err(io_err)?;
+
+        Ok(())
+    }
+
     pub fn mangle_package_name(&self) -> String {
         self.attrs.mangle_package_name()
     }
@@ -174,6 +300,11 @@ fn io_err<E: ToString>(err: E) -> Error {
     Error::new(Span::call_site(), err.to_string())
 }
 
+/// Quote a string as a single-quoted perl string literal.
+fn perl_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
 pub fn get_default_lib_name(why: Span) -> Result<String, Error> {
     env::var("CARGO_PKG_NAME")
         .map(|s| s.replace('-', "_"))
@@ -0,0 +1,14 @@
+#[perlmod::package(name = "RSPM::Bench", lib = "perlmod_test", write = true)]
+mod export {
+    use std::collections::HashMap;
+
+    #[export]
+    fn hash_keys_owned(map: HashMap<String, u64>) -> usize {
+        map.keys().map(|key| key.len()).sum()
+    }
+
+    #[export]
+    fn hash_keys_borrowed(map: HashMap<&str, u64>) -> usize {
+        map.keys().map(|key| key.len()).sum()
+    }
+}
@@ -0,0 +1,21 @@
+//! Two packages loaded through a single combined bootstrap function.
+
+#[perlmod::package(name = "RSPM::CombinedA", lib = "perlmod_test")]
+mod first {
+    #[export]
+    fn name() -> &'static str {
+        "first"
+    }
+}
+
+#[perlmod::package(name = "RSPM::CombinedB", lib = "perlmod_test")]
+mod second {
+    #[export]
+    fn name() -> &'static str {
+        "second"
+    }
+}
+
+perlmod::combined_bootstrap! {
+    pub fn boot_RSPM__Combined { first, second }
+}
@@ -15,3 +15,15 @@ mod option;
 
 /// Tests for magic based blessed objects.
 mod magic;
+
+/// Exports used by `bench.pl`.
+mod bench;
+
+/// Standalone exports registered without the `#[package]` macro.
+mod manual;
+
+/// Packages registered through a combined bootstrap function.
+mod combined;
+
+/// A package made of standalone exports via `make_package!`.
+mod made;
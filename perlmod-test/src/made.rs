@@ -0,0 +1,20 @@
+//! A package assembled from standalone exports defined outside of it.
+
+#[perlmod::export]
+pub fn double(value: u32) -> u32 {
+    value * 2
+}
+
+#[perlmod::export(name = "half", name = "halve")]
+pub fn made_half(value: u32) -> u32 {
+    value / 2
+}
+
+perlmod::make_package! {
+    #[package(name = "RSPM::Made", lib = "perlmod_test")]
+    pub mod package {
+        super::double,
+        super::made_half,
+        crate::manual::manual_add_one,
+    }
+}
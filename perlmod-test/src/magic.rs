@@ -1,4 +1,9 @@
-#[perlmod::package(name = "RSPM::Magic", lib = "perlmod_test", write = true)]
+#[perlmod::package(
+    name = "RSPM::Magic",
+    lib = "perlmod_test",
+    write = true,
+    xs_file = "perlmod-test/Magic's.xs"
+)]
 mod export {
     use perlmod::{Error, Value};
 
@@ -19,6 +24,11 @@ mod export {
         Ok(perlmod::instantiate_magic!(&class, MAGIC => Box::new(Magic { content })))
     }
 
+    #[export(raw_return)]
+    fn new_direct(class: &str, content: String) -> Result<Value, Error> {
+        Value::bless_box_magic(class, Box::new(Magic { content }), &MAGIC)
+    }
+
     #[export]
     fn call(#[try_from_ref] this: &Magic) -> Result<(), Error> {
         println!("Calling magic with content {:?}", this.content);
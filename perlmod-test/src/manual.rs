@@ -0,0 +1,29 @@
+//! Standalone exports which are registered manually via their generated metadata.
+
+#[perlmod::export(name = "add_one")]
+pub fn manual_add_one(value: u32) -> u32 {
+    value + 1
+}
+
+/// Install the standalone exports into the `RSPM::Manual` package.
+pub fn register() {
+    use std::ffi::CString;
+
+    use manual_add_one_meta as meta;
+
+    let name = CString::new(format!("RSPM::Manual::{}", meta::PERL_NAME)).unwrap();
+    let prototype = meta::PROTOTYPE.map(|proto| CString::new(proto).unwrap());
+
+    unsafe {
+        perlmod::ffi::RSPL_newXS_flags(
+            name.as_ptr(),
+            meta::XSUB as _,
+            concat!(file!(), "\0").as_ptr() as *const i8,
+            prototype
+                .as_ref()
+                .map(|proto| proto.as_ptr())
+                .unwrap_or(std::ptr::null()),
+            0,
+        );
+    }
+}
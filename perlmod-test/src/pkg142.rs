@@ -12,6 +12,62 @@ pub struct RawRefs {
     reference: Value,
 }
 
+#[derive(Debug, Deserialize)]
+struct BorrowedEntry<'a> {
+    name: &'a str,
+    size: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Flattened {
+    name: String,
+    #[serde(flatten)]
+    extra: std::collections::HashMap<String, Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Timestamps {
+    unix_nanos: u64,
+    fractional_secs: f64,
+    wide: i128,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Blob {
+    name: String,
+    #[serde(with = "serde_bytes")]
+    data: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Output {
+    prefix: String,
+    handle: perlmod::FileHandle,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Pair(u32, u32);
+
+#[derive(Debug, Serialize)]
+pub struct WrappedPair(Pair);
+
+#[derive(Debug, Deserialize)]
+pub struct Limits {
+    min: u32,
+    max: u32,
+}
+
+impl perlmod::Validate for Limits {
+    type Error = String;
+
+    fn validate(&self) -> Result<(), String> {
+        if self.min > self.max {
+            return Err(format!("min ({}) exceeds max ({})", self.min, self.max));
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 enum AnEnum {
@@ -21,21 +77,58 @@ enum AnEnum {
     ResultB,
 }
 
+perlmod::perl_fn! {
+    /// A raw XSUB doubling its argument, installed at runtime by `test_install_xsub`.
+    extern "C" fn double_xsub(_cv: *mut perlmod::ffi::CV) {
+        unsafe {
+            let argmark = perlmod::ffi::pop_arg_mark();
+            let value = argmark.iter().next().map(|sv| Value::from(sv).iv()).unwrap_or(0);
+            argmark.set_stack();
+            perlmod::ffi::stack_push_raw(Value::new_int(value * 2).into_mortal().into_raw());
+        }
+    }
+}
+
 #[perlmod::package(
     name = "RSPM::Foo142",
     lib = "perlmod_test",
     write = true,
-    boot = "loaded"
+    tests = "RSPM/Foo142.t",
+    boot = "loaded",
+    pre_boot = "pre_boot"
 )]
 mod export {
     use anyhow::{bail, Error};
 
     use perlmod::Value;
 
-    use super::AnEnum;
+    use super::{
+        AnEnum, Blob, BorrowedEntry, Flattened, Limits, Output, Pair, Timestamps, WrappedPair,
+    };
+
+    static PRE_BOOT_DONE: std::sync::atomic::AtomicBool =
+        std::sync::atomic::AtomicBool::new(false);
+
+    fn pre_boot() {
+        let installed =
+            perlmod::eval("defined(&RSPM::Foo142::foo142)", perlmod::ffi::Gimme::Scalar)
+                .map(|result| result[0].is_true())
+                .unwrap_or(true);
+        println!("<pre-boot, subs installed: {}>", installed as u8);
+        PRE_BOOT_DONE.store(true, std::sync::atomic::Ordering::SeqCst);
+    }
 
     fn loaded() {
-        println!("<loaded>");
+        // the exported functions are already installed and can rely on the pre-boot state here
+        let state = perlmod::eval("RSPM::Foo142::test_pre_boot_state()", perlmod::ffi::Gimme::Scalar)
+            .map(|result| result[0].iv())
+            .unwrap_or(-1);
+        println!("<loaded, pre-boot state: {state}>");
+    }
+
+    #[export]
+    fn test_pre_boot_state() -> bool {
+        PRE_BOOT_DONE.load(std::sync::atomic::Ordering::SeqCst)
     }
 
     #[export]
@@ -95,6 +188,1069 @@ mod export {
         Ok(value.substr(3..6)?)
     }
 
+    #[export]
+    fn test_ref_count(#[raw] value: Value) -> Result<usize, Error> {
+        match value.dereference() {
+            // don't count the reference held by `inner` itself
+            Some(inner) => Ok(inner.ref_count() - 1),
+            None => bail!("not a reference"),
+        }
+    }
+
+    #[export(raw_return)]
+    fn test_closure(start: isize) -> Value {
+        let counter = std::cell::Cell::new(start);
+        Value::new_closure(move |args: &[Value]| -> Result<Vec<Value>, perlmod::Error> {
+            if !args.is_empty() {
+                return Err(perlmod::Error::new("counter takes no arguments"));
+            }
+            let value = counter.get();
+            counter.set(value + 1);
+            Ok(vec![Value::new_int(value), Value::new_int(value * 2)])
+        })
+    }
+
+    #[derive(serde::Serialize)]
+    struct BlessedData {
+        name: String,
+        count: u32,
+    }
+
+    #[export(raw_return)]
+    fn test_to_blessed_value(name: String) -> Result<Value, Error> {
+        let data = BlessedData { name, count: 3 };
+        Ok(perlmod::to_blessed_value(&data, "RSPM::Foo142")?)
+    }
+
+    #[export]
+    fn test_is_empty(#[raw] value: Value) -> Result<bool, Error> {
+        match value.dereference() {
+            Some(Value::Hash(hash)) => Ok(hash.is_empty()),
+            Some(Value::Array(array)) => Ok(array.is_empty()),
+            _ => bail!("expected a hash or array reference"),
+        }
+    }
+
+    #[export]
+    fn test_pseudo_block(class: &str, count: isize) -> Result<(), Error> {
+        for id in 0..count {
+            perlmod::ffi::pseudo_block(|| -> Result<(), Error> {
+                let hash = perlmod::Hash::new();
+                hash.insert("id", Value::new_int(id));
+                let _ = Value::new_ref(&hash).bless(class)?.into_mortal();
+                Ok(())
+            })?;
+            println!("iteration {id} done");
+        }
+        Ok(())
+    }
+
+    #[export]
+    fn test_borrowed_keys(map: std::collections::HashMap<&str, u32>) -> Vec<String> {
+        let mut keys: Vec<String> = map.iter().map(|(k, v)| format!("{k}={v}")).collect();
+        keys.sort();
+        keys
+    }
+
+    #[export]
+    fn test_aggregate_refs(
+        list: &perlmod::Array,
+        map: &perlmod::Hash,
+        #[raw]
+        #[proto = "\\[@%]"]
+        other: Value,
+    ) -> String {
+        format!(
+            "list has {} entries, map has {} keys, other is {}",
+            list.len(),
+            map.len(),
+            other.dereference().map(|v| v.reftype(false)).unwrap_or("not a reference"),
+        )
+    }
+
+    #[export]
+    fn test_svtype_name(#[raw] value: Value) -> &'static str {
+        match value.dereference() {
+            Some(inner) => inner.svtype_name(),
+            None => value.svtype_name(),
+        }
+    }
+
+    #[export(raw_return)]
+    fn test_extend_from_slice(values: Vec<i64>) -> Value {
+        let array = perlmod::Array::new();
+        array.push(Value::new_string("start"));
+        array.extend_from_slice(&values);
+        array.extend_from_slice(&["a", "b"]);
+        array.extend_from_slice(&[true, false]);
+        Value::new_ref(&array)
+    }
+
+    #[export(raw_return)]
+    fn test_bless_undeclared(package: &str) -> Result<Value, Error> {
+        let value = Value::new_ref(&perlmod::Hash::new());
+        if value.bless(package).is_ok() {
+            bail!("package {package:?} unexpectedly exists already");
+        }
+        perlmod::ffi::ensure_package(package);
+        Ok(value.bless(package)?)
+    }
+
+    #[export]
+    fn test_borrowed_value(#[raw] name: Value, #[raw] entries: Value) -> Result<String, Error> {
+        // Safety: neither value is modified while the borrowed data is in use.
+        let borrowed: &str = unsafe { perlmod::from_borrowed_value(&name)? };
+        let is_borrowed = borrowed.as_ptr() == name.pv_string_utf8().as_ptr();
+
+        let parsed: std::collections::BTreeMap<&str, BorrowedEntry> =
+            unsafe { perlmod::from_borrowed_value(&entries)? };
+        let hash = match entries.dereference() {
+            Some(Value::Hash(hash)) => hash,
+            _ => bail!("not a hash reference"),
+        };
+        let nested = parsed.iter().all(|(key, entry)| {
+            let name = hash
+                .get(key)
+                .and_then(|entry| entry.dereference())
+                .and_then(|entry| entry.as_hash()?.get("name"));
+            name.is_some_and(|name| name.pv_string_utf8().as_ptr() == entry.name.as_ptr())
+        });
+        let parsed: Vec<String> = parsed
+            .iter()
+            .map(|(key, entry)| format!("{key}={}:{}", entry.name, entry.size))
+            .collect();
+
+        Ok(format!(
+            "{borrowed} borrowed={is_borrowed}, entries [{}], nested borrowed={nested}",
+            parsed.join(", "),
+        ))
+    }
+
+    #[export]
+    fn test_hash_key(#[raw] value: Value) -> String {
+        let (bytes, utf8) = value.hash_key();
+        let hex: Vec<String> = bytes.iter().map(|b| format!("{b:02x}")).collect();
+        format!("[{}] utf8={utf8}", hex.join(" "))
+    }
+
+    #[export]
+    fn test_flatten(value: Flattened) -> String {
+        let mut extra: Vec<String> = value
+            .extra
+            .iter()
+            .map(|(key, value)| format!("{key}={value:?}"))
+            .collect();
+        extra.sort();
+        format!("name={}, extra: {}", value.name, extra.join(", "))
+    }
+
+    #[export(raw_return)]
+    fn test_deep_clone(#[raw] value: Value) -> Result<Value, Error> {
+        Ok(value.deep_clone()?)
+    }
+
+    #[export]
+    fn test_raw_fd(#[raw] handle: Value) -> Result<Option<String>, Error> {
+        use std::io::{BufRead, BufReader};
+        use std::os::unix::io::FromRawFd;
+
+        let fd = match handle.as_raw_fd() {
+            Some(fd) => fd,
+            None => return Ok(None),
+        };
+
+        // the descriptor still belongs to perl
+        let file = std::mem::ManuallyDrop::new(unsafe { std::fs::File::from_raw_fd(fd) });
+        let mut line = String::new();
+        BufReader::new(&*file).read_line(&mut line)?;
+        Ok(Some(line))
+    }
+
+    #[export(name = "test_aliased", name = "test_aliased_old")]
+    fn test_aliased(value: u32) -> u32 {
+        value * 2
+    }
+
+    #[export(map_as_list)]
+    fn test_map_as_list(count: u32) -> std::collections::HashMap<String, u32> {
+        (1..=count).map(|i| (format!("key{i}"), i)).collect()
+    }
+
+    #[export(raw_return)]
+    fn test_latin1() -> Value {
+        Value::new_latin1(b"caf\xe9")
+    }
+
+    #[export(map_as_list)]
+    fn test_pairs_as_list() -> Vec<(String, i64)> {
+        vec![("zeta".to_string(), 1), ("alpha".to_string(), 2), ("mid".to_string(), 3)]
+    }
+
+    #[export(map_as_list)]
+    fn test_list_not_flattened() -> Vec<u32> {
+        vec![1, 2, 3]
+    }
+
+    #[export]
+    fn test_timestamps(mut timestamps: Timestamps) -> Timestamps {
+        timestamps.unix_nanos += 1;
+        timestamps.wide -= 1;
+        timestamps
+    }
+
+    #[export]
+    fn test_json(#[raw] value: Value) -> Result<String, Error> {
+        Ok(value.to_json_string()?)
+    }
+
+    #[export]
+    fn test_debug_nested(#[raw] value: Value, depth: usize) -> String {
+        format!("{:?}", value.debug_nested(depth))
+    }
+
+    #[export(raw_return)]
+    fn test_get_path(#[raw] data: Value, index: usize) -> Option<Value> {
+        use perlmod::value::PathSeg;
+
+        data.get_path(&[PathSeg::Key("a"), PathSeg::Index(index), PathSeg::Key("b")])
+    }
+
+    #[export(raw_return)]
+    fn test_raw_option(some: bool) -> Option<Value> {
+        some.then(|| Value::new_string("some"))
+    }
+
+    #[export]
+    fn test_local(#[coderef] callback: perlmod::Callback) -> Result<(), Error> {
+        let local = perlmod::save_scalar("RSPM::Foo142::LOCALIZED");
+        callback.call(&[])?;
+        local.set(&Value::new_string("localized"));
+        callback.call(&[])?;
+        drop(local);
+        Ok(())
+    }
+
+    thread_local! {
+        static KEPT_LOCAL: std::cell::RefCell<Option<perlmod::LocalGuard>> =
+            const { std::cell::RefCell::new(None) };
+    }
+
+    /// Keep the guard around after returning, it gets dropped by the next call.
+    #[export]
+    fn test_keep_local(keep: bool) {
+        let local = keep.then(|| {
+            let local = perlmod::save_scalar("RSPM::Foo142::LOCALIZED");
+            local.set(&Value::new_string("kept"));
+            local
+        });
+        let previous = KEPT_LOCAL.with(|kept| kept.replace(local));
+        if let Some(previous) = previous {
+            let value = previous.pv_string_utf8();
+            println!("dropping previous guard, value: {value}");
+        }
+    }
+
+    /// Drop the outer guard first, which keeps the outer variable localized until perl leaves the
+    /// enclosing scope.
+    #[export]
+    fn test_local_out_of_order(#[coderef] callback: perlmod::Callback) -> Result<(), Error> {
+        let outer = perlmod::save_scalar("RSPM::Foo142::LOCALIZED");
+        outer.set(&Value::new_string("outer"));
+        let inner = perlmod::save_scalar("RSPM::Foo142::LOCALIZED_INNER");
+        inner.set(&Value::new_string("inner"));
+        drop(outer);
+        drop(inner);
+        callback.call(&[])?;
+        Ok(())
+    }
+
+    #[export]
+    fn test_tuple_struct() -> Pair {
+        Pair(17, 32)
+    }
+
+    #[export]
+    fn test_wrapped_tuple_struct() -> Result<WrappedPair, Error> {
+        Ok(WrappedPair(Pair(3, 4)))
+    }
+
+    #[export]
+    fn test_install_xsub() -> Value {
+        unsafe {
+            perlmod::ffi::install_xsub("RSPM::Runtime", "double", super::double_xsub, Some("$"))
+        }
+    }
+
+    #[export]
+    fn test_looks_like_number(#[raw] value: Value) -> (bool, bool) {
+        let looks_like_number = value.looks_like_number();
+        // the flags must be left alone
+        let still_string_only =
+            value.ty() == perlmod::scalar::Type::Scalar(perlmod::scalar::Flags::STRING);
+        (looks_like_number, still_string_only)
+    }
+
+    #[export]
+    fn test_strict_bool(#[raw] value: Value) -> Result<bool, Error> {
+        Ok(perlmod::from_value_strict_bool(value)?)
+    }
+
+    #[export]
+    fn test_set_undef(#[raw] out: Value) -> Result<(), Error> {
+        match out.dereference() {
+            Some(value) => {
+                value.set_undef();
+                Ok(())
+            }
+            None => bail!("expected a scalar reference"),
+        }
+    }
+
+    #[export]
+    fn test_float_round_trip(value: f64) -> Result<(f64, f32, bool), Error> {
+        let narrowed = value as f32;
+        let wide: f64 = perlmod::from_value(perlmod::to_value(&value)?)?;
+        let narrow: f32 = perlmod::from_value(perlmod::to_value(&narrowed)?)?;
+        let same = (wide == value || (wide.is_nan() && value.is_nan()))
+            && (narrow == narrowed || (narrow.is_nan() && narrowed.is_nan()));
+        Ok((value, narrowed, same))
+    }
+
+    #[export]
+    fn test_collect(list: &perlmod::Array, map: &perlmod::Hash) -> Result<String, Error> {
+        let list: Vec<Option<u32>> = list.into_vec()?;
+        let map: std::collections::BTreeMap<String, Vec<u32>> =
+            map.into_hashmap()?.into_iter().collect();
+        Ok(format!("{list:?} {map:?}"))
+    }
+
+    #[export]
+    fn test_overload(#[raw] a: Value, #[raw] b: Value) -> Result<(Value, Value, Value), Error> {
+        use perlmod::scalar::Overload;
+
+        Ok((
+            a.overload_op(Some(&b), Overload::Add)?,
+            a.overload_op(Some(&b), Overload::NumCompare)?,
+            a.overload_op(None, Overload::String)?,
+        ))
+    }
+
+    #[export(raw_return)]
+    fn test_overload_without_operand(#[raw] a: Value) -> Result<Value, Error> {
+        Ok(a.overload_op(None, perlmod::scalar::Overload::Add)?)
+    }
+
+    #[export]
+    fn test_into_ref(count: usize) -> (Value, Value) {
+        let list = perlmod::Array::new();
+        let map = perlmod::Hash::new();
+        for i in 0..count {
+            list.push(Value::new_uint(i));
+            map.insert(&format!("key{i}"), Value::new_uint(i * 10));
+        }
+        (list.into_ref(), map.into_ref())
+    }
+
+    #[export]
+    fn test_panic_in_deserialize() -> Result<(bool, bool, bool, bool), Error> {
+        struct Panicking;
+
+        impl<'de> serde::Deserialize<'de> for Panicking {
+            fn deserialize<D>(_deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                panic!("deserialization panicked");
+            }
+        }
+
+        // we're not serializing anything right now
+        let active_before = perlmod::ser::is_active();
+
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(|_| ()));
+        let panicked = std::panic::catch_unwind(|| {
+            perlmod::from_value_strict_bool::<Panicking>(Value::new_string("1"))
+        })
+        .is_err();
+        std::panic::set_hook(hook);
+
+        let active_after = perlmod::ser::is_active();
+        // strict bool mode must not leak into later non-strict deserialization either
+        let lenient: bool = perlmod::from_value(Value::new_int(2))?;
+
+        Ok((panicked, active_before, active_after, lenient))
+    }
+
+    #[export]
+    fn test_validate(#[validate] limits: Limits, #[validate] extra: Option<Limits>) -> u32 {
+        limits.max - limits.min + extra.map(|extra| extra.max - extra.min).unwrap_or(0)
+    }
+
+    #[export]
+    fn test_write_all(#[raw] fh: Value, data: &str) -> Result<(), Error> {
+        for line in data.split_inclusive('\n') {
+            fh.write_all(line.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    #[export]
+    fn test_return_list(count: usize) -> Result<perlmod::ReturnList, Error> {
+        use perlmod::ffi::{gimme, Gimme};
+
+        if gimme() == Gimme::Void {
+            println!("return list called in void context");
+            return Ok(perlmod::ReturnList::new());
+        }
+
+        let mut list = perlmod::ReturnList::new();
+        for i in 0..count {
+            list.push(Value::new_string(&format!("item{i}")));
+        }
+        Ok(list)
+    }
+
+    #[export]
+    fn test_return_list_fn(#[raw] values: Value) -> Result<perlmod::ReturnList, Error> {
+        match values.dereference() {
+            Some(Value::Array(array)) => Ok(perlmod::return_list(array.iter().collect())),
+            _ => bail!("expected an array reference"),
+        }
+    }
+
+    #[export]
+    fn test_new_ref_owned() -> (usize, usize, Value) {
+        let borrowed = perlmod::Array::new();
+        let borrowed_ref = Value::new_ref(&borrowed);
+        // both `borrowed` and the reference hold a count
+        let borrowed_count = borrowed.refcnt();
+        drop(borrowed_ref);
+
+        let owned = perlmod::Array::new();
+        owned.push(Value::new_int(1));
+        let raw = owned.av() as *mut perlmod::ffi::SV;
+        let owned_ref = Value::new_ref_owned(owned);
+        // only the reference holds a count
+        let owned_count = unsafe { perlmod::ffi::RSPL_SvREFCNT(raw) } as usize;
+
+        (borrowed_count, owned_count, owned_ref)
+    }
+
+    #[export]
+    fn test_class(#[class] class: &str) -> String {
+        format!("class: {class}")
+    }
+
+    #[export]
+    fn test_lazy_list(count: usize) -> perlmod::LazyList<impl Iterator<Item = String>> {
+        perlmod::LazyList::new((0..count).map(|i| format!("lazy{i}")))
+    }
+
+    #[export]
+    fn test_rename_key(hash: &perlmod::Hash, from: &str, to: &str) -> bool {
+        hash.rename_key(from, to)
+    }
+
+    #[export]
+    fn test_magic_triggers(#[raw] tied: Value) -> Result<(), Error> {
+        let Some(tied) = tied.dereference() else {
+            bail!("expected a scalar reference");
+        };
+        tied.get_magic();
+        tied.get_magic();
+        tied.set_magic();
+        Ok(())
+    }
+
+    #[export]
+    fn test_does(#[raw] value: Value, role: &str) -> bool {
+        value.does(role)
+    }
+
+    #[export]
+    fn test_callback_queue(
+        #[coderef] callback: perlmod::Callback,
+    ) -> Result<Vec<String>, perlmod::Error> {
+        use perlmod::{Error, PerlCallbackQueue, SendValue};
+
+        let mut log = Vec::new();
+        let queue = PerlCallbackQueue::new();
+
+        let shared = Value::new_string("shared");
+        let _shared_ref = shared.clone_ref();
+        if let Err(shared) = SendValue::new(shared) {
+            log.push(format!("shared value refused, refcount {}", shared.ref_count()));
+        }
+
+        let code = callback.into_inner().dereference().unwrap();
+        let callback = SendValue::new(Value::new_ref(&code))
+            .map_err(|_| Error::new("fresh reference refused"))?;
+        let hash = perlmod::Hash::new();
+        hash.insert("from", Value::new_string("interpreter"));
+        let hash = SendValue::new(Value::new_ref_owned(hash))
+            .map_err(|_| Error::new("fresh hash reference refused"))?;
+        let stray = SendValue::new(Value::new_string("stray")).unwrap();
+
+        let worker_queue = queue.clone();
+        let worker_log = std::thread::spawn(move || {
+            let mut log = Vec::new();
+            if let Err(err) = worker_queue.drain_on_interpreter_thread() {
+                log.push(format!("worker drain: {err}"));
+            }
+            if let Err(err) = stray.into_value() {
+                log.push(format!("worker into_value: {err}"));
+            }
+            for i in 1..=2 {
+                worker_queue.push(move || Err(Error::new_owned(format!("job {i} failed"))));
+            }
+            worker_queue.push(move || {
+                let callback = callback.into_value()?;
+                callback.call(&[hash.into_value()?, Value::new_string("from worker")])?;
+                Ok(())
+            });
+            log
+        })
+        .join()
+        .map_err(|_| Error::new("worker thread panicked"))?;
+        log.extend(worker_log);
+
+        log.push(format!("queued: {}", queue.len()));
+        while !queue.is_empty() {
+            match queue.drain_on_interpreter_thread() {
+                Ok(count) => log.push(format!("drained {count}")),
+                Err(err) => log.push(format!("drain: {err}")),
+            }
+        }
+        Ok(log)
+    }
+
+    #[export]
+    fn test_array_element(list: &perlmod::Array, index: usize, label: &str) {
+        if let Some(counter) = list.element(index) {
+            counter.set_int(counter.iv() + 1);
+        }
+        if let Some(slot) = list.element(index + 2) {
+            slot.set_string(label);
+        }
+    }
+
+    #[export]
+    fn test_array_element_bytes(list: &perlmod::Array, index: usize) {
+        if let Some(slot) = list.element(index) {
+            slot.set_bytes(b"raw\xff");
+        }
+    }
+
+    #[export]
+    fn test_empty_lists(count: usize) -> Vec<Vec<usize>> {
+        vec![(0..count).collect(), Vec::new()]
+    }
+
+    #[export(empty_as_undef)]
+    fn test_empty_as_undef(count: usize) -> Vec<Vec<usize>> {
+        vec![(0..count).collect(), Vec::new()]
+    }
+
+    #[export]
+    fn test_to_value_empty_as_undef() -> Result<(Value, Value), perlmod::Error> {
+        let empty: Vec<String> = Vec::new();
+        Ok((
+            perlmod::to_value(&empty)?,
+            perlmod::to_value_empty_as_undef(&empty)?,
+        ))
+    }
+
+    /// Serializes an empty list with a nested `to_value` call.
+    struct NestedToValue;
+
+    impl serde::Serialize for NestedToValue {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::Error;
+            perlmod::to_value(&Vec::<String>::new())
+                .map_err(S::Error::custom)?
+                .serialize(serializer)
+        }
+    }
+
+    #[export]
+    fn test_nested_to_value_empty_as_undef() -> Result<Value, perlmod::Error> {
+        let empty: Vec<String> = Vec::new();
+        perlmod::to_value_empty_as_undef(&(NestedToValue, empty))
+    }
+
+    #[export]
+    fn test_set_in_place(#[raw] target: Value, kind: &str) -> Result<(), Error> {
+        let Some(target) = target.dereference() else {
+            bail!("expected a scalar reference");
+        };
+        match kind {
+            "int" => target.set_int(-42),
+            "uint" => target.set_uint(42),
+            "float" => target.set_float(1.5),
+            "string" => target.set_string("caf\u{e9}"),
+            "bytes" => target.set_bytes(b"raw"),
+            "copy" => target.set(&Value::new_string("copied")),
+            "undef" => target.set_undef(),
+            _ => bail!("unknown kind {kind:?}"),
+        }
+        Ok(())
+    }
+
+    #[export]
+    fn test_cached_call(
+        #[coderef] lookup: perlmod::Callback,
+        keys: Vec<String>,
+    ) -> Result<Vec<String>, perlmod::Error> {
+        let lookup = lookup.cached();
+        let mut values = Vec::new();
+        for (i, key) in keys.iter().enumerate() {
+            if i == keys.len() - 1 {
+                lookup.clear();
+            }
+            for value in lookup.call_with(key)? {
+                values.push(value.pv_string_utf8().to_string());
+            }
+        }
+        Ok(values)
+    }
+
+    #[export(raw_return)]
+    fn test_iterator(count: usize, fail_at: Option<usize>) -> Value {
+        struct DropNotice;
+        impl Drop for DropNotice {
+            fn drop(&mut self) {
+                println!("iterator dropped");
+            }
+        }
+
+        let notice = DropNotice;
+        Value::new_iterator((0..count).map(move |i| {
+            let _ = &notice;
+            if Some(i) == fail_at {
+                return Err(perlmod::Error::new_owned(format!("item {i} failed")));
+            }
+            Ok(Value::new_string(&format!("item{i}")))
+        }))
+    }
+
+    #[export]
+    fn test_null_magic_drop() {
+        static TAG: perlmod::MagicTag<Box<u8>> = perlmod::MagicTag::DEFAULT;
+
+        let value = Value::new_int(0);
+        unsafe { value.add_raw_magic(None, None, Some(TAG.as_ref()), std::ptr::null(), 0) };
+    }
+
+    #[export(raw_return)]
+    fn test_bytes_mut(#[raw] from_perl: Value) -> Value {
+        let mut buffer = Value::new_bytes(b"hello world");
+        if let Some(bytes) = buffer.bytes_mut() {
+            bytes.make_ascii_uppercase();
+        }
+
+        let mut number = Value::new_int(1234);
+        if let Some(bytes) = number.bytes_mut() {
+            bytes.reverse();
+        }
+
+        let mut shared = Value::new_bytes(b"shared");
+        let other = shared.clone_ref();
+        let shared_editable = shared.bytes_mut().is_some();
+        drop(other);
+
+        let mut from_perl = from_perl.dereference().unwrap();
+        let perl_editable = from_perl.bytes_mut().is_some();
+
+        let mut utf8 = Value::new_string("\u{263a}");
+        let utf8_editable = utf8.bytes_mut().is_some();
+
+        let list = perlmod::Array::new();
+        list.push(buffer);
+        list.push(number);
+        list.push(Value::bool_value(shared_editable));
+        list.push(Value::bool_value(perl_editable));
+        list.push(Value::bool_value(utf8_editable));
+        list.into_ref()
+    }
+
+    #[export(accessor = "label")]
+    fn test_accessor(
+        #[raw] this: Value,
+        value: Option<Option<String>>,
+    ) -> Result<Option<String>, Error> {
+        let Some(Value::Hash(object)) = this.dereference() else {
+            bail!("expected a hash based object");
+        };
+        if let Some(value) = value {
+            match value {
+                Some(value) => object.insert("label", Value::new_string(&value)),
+                None => object.insert("label", Value::new_undef()),
+            }
+        }
+        Ok(object
+            .get("label")
+            .filter(|label| !label.is_undef())
+            .map(|label| label.pv_string_utf8().to_string()))
+    }
+
+    #[derive(serde::Deserialize)]
+    struct LenientSettings {
+        count: u32,
+        name: String,
+        enabled: bool,
+        ratio: f64,
+    }
+
+    #[export]
+    fn test_lenient(#[raw] value: Value) -> Result<(String, Vec<String>), perlmod::Error> {
+        let (settings, warnings): (LenientSettings, _) = perlmod::from_value_lenient(value)?;
+        let warnings = warnings.iter().map(|w| w.to_string()).collect();
+        let LenientSettings {
+            count,
+            name,
+            enabled,
+            ratio,
+        } = settings;
+        Ok((format!("{count}, {name:?}, {enabled}, {ratio}"), warnings))
+    }
+
+    #[export]
+    fn test_pairs(#[raw] value: Value) -> (String, String) {
+        use std::collections::BTreeMap;
+
+        let show = |result: Result<String, perlmod::Error>| match result {
+            Ok(text) => text,
+            Err(err) => err.to_string(),
+        };
+        let map = perlmod::from_value::<BTreeMap<String, u32>>(value.clone_ref());
+        let list = perlmod::from_value::<Vec<(String, u32)>>(value);
+        (
+            show(map.map(|map| format!("{map:?}"))),
+            show(list.map(|list| format!("{list:?}"))),
+        )
+    }
+
+    #[export(raw_return)]
+    fn test_context(#[raw] out: Value) -> Value {
+        let name = perlmod::ffi::gimme().void_map(|| "scalar", || "list", || "void");
+        if let Some(Value::Scalar(out)) = out.dereference() {
+            out.set_string(name);
+        }
+        perlmod::ffi::gimme().map(|| Value::new_string(name), || Value::new_string("in list"))
+    }
+
+    #[export]
+    fn test_context_param(#[context] context: perlmod::Context, #[raw] out: Value) -> u32 {
+        if let Some(Value::Scalar(out)) = out.dereference() {
+            out.set_string(&format!(
+                "list={} scalar={} void={}",
+                context.want_list() as u8,
+                context.want_scalar() as u8,
+                context.want_void() as u8,
+            ));
+        }
+        1
+    }
+
+    #[export]
+    fn test_string_hash(#[raw] value: Value) -> u32 {
+        value.string_hash()
+    }
+
+    #[derive(serde::Deserialize)]
+    enum Shape {
+        #[serde(rename = "RSPM::Circle")]
+        Circle { radius: f64 },
+        #[serde(rename = "RSPM::Polygon")]
+        Polygon(Vec<(i32, i32)>),
+    }
+
+    #[export]
+    fn test_blessed_enum(#[raw] object: Value) -> String {
+        match perlmod::from_blessed_value::<Shape>(object) {
+            Ok(Shape::Circle { radius }) => format!("circle with radius {radius}"),
+            Ok(Shape::Polygon(points)) => format!("polygon {points:?}"),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[export]
+    fn test_insert_if_absent(config: &perlmod::Hash) -> Vec<String> {
+        ["port", "host", "user"]
+            .into_iter()
+            .filter(|key| config.insert_if_absent(key, Value::new_string("default")))
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[export(raw_return)]
+    fn test_single_typed(#[raw] value: Value) -> Value {
+        let list = perlmod::Array::new();
+        list.push(Value::Scalar(value.to_number()));
+        list.push(Value::Scalar(value.to_string_scalar()));
+        list.into_ref()
+    }
+
+    #[export]
+    fn test_append(target: &perlmod::Array, other: &perlmod::Array) -> usize {
+        target.append(other);
+        target.len()
+    }
+
+    #[export]
+    fn test_array_insert(list: &perlmod::Array, index: usize, value: String) -> Result<(), Error> {
+        Ok(list.insert(index, Value::new_string(&value))?)
+    }
+
+    #[export(raw_return)]
+    fn test_array_swap_remove(list: &perlmod::Array, index: usize) -> Option<Value> {
+        list.swap_remove(index)
+    }
+
+    #[export]
+    fn test_file_handle_read(#[try_from_ref] handle: perlmod::FileHandle) -> Result<String, Error> {
+        use std::io::Read;
+
+        let mut head = [0u8; 4];
+        let got = handle.read(&mut head)?;
+        let mut rest = String::new();
+        (&handle).read_to_string(&mut rest)?;
+        Ok(format!("{}|{rest}", String::from_utf8_lossy(&head[..got])))
+    }
+
+    #[export]
+    fn test_file_handle_write(output: Output, text: &str) -> Result<(), Error> {
+        Ok(output
+            .handle
+            .write_all(format!("{}{text}", output.prefix).as_bytes())?)
+    }
+
+    #[export(raw_return)]
+    fn test_hash_with_capacity(capacity: usize) -> Value {
+        let hash = perlmod::Hash::with_capacity(capacity);
+        hash.insert("key", Value::new_int(1));
+        hash.into_ref()
+    }
+
+    #[export]
+    fn test_serialized_hash_size(size: usize) -> std::collections::HashMap<String, usize> {
+        (0..size).map(|i| (format!("key{i}"), i)).collect()
+    }
+
+    #[export]
+    fn test_enter_scope(#[coderef] callback: perlmod::Callback, scoped: bool) -> Result<(), Error> {
+        for i in 0..2 {
+            let _scope = scoped.then(perlmod::enter_scope);
+            // keep the results around until the end of the scope
+            for value in callback.call_with(&i)? {
+                value.into_mortal();
+            }
+        }
+        Ok(())
+    }
+
+    thread_local! {
+        static KEPT_SCOPE: std::cell::RefCell<Option<perlmod::ScopeGuard>> =
+            const { std::cell::RefCell::new(None) };
+    }
+
+    /// Keep the guard around after returning, it gets dropped by the next call.
+    #[export]
+    fn test_keep_scope(keep: bool) {
+        let scope = keep.then(perlmod::enter_scope);
+        if KEPT_SCOPE.with(|kept| kept.replace(scope)).is_some() {
+            println!("  dropping previous guard");
+        }
+    }
+
+    #[export]
+    fn test_hash_iter_sv(hash: &perlmod::Hash) -> Vec<String> {
+        let mut entries: Vec<String> = hash
+            .iter_sv()
+            .map(|(key, value)| {
+                let bytes: String = key.pv_bytes().iter().map(|b| format!("{b:02x}")).collect();
+                let utf8 = key.is_utf8();
+                format!("{bytes} (utf8: {utf8}) => {}", value.pv_string_utf8())
+            })
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    #[export]
+    fn test_multiplicity() -> (bool, bool) {
+        (perlmod::ffi::MULTIPLICITY, perlmod::ffi::has_multiplicity())
+    }
+
+    #[export]
+    fn test_blessed_class(#[raw] value: Value) -> (bool, Option<String>) {
+        (value.is_blessed(), value.blessed_class())
+    }
+
+    #[export]
+    fn test_perl_eq(#[raw] a: Value, #[raw] b: Value) -> Result<(bool, bool), Error> {
+        Ok((a.numeric_eq(&b)?, a.string_eq(&b)?))
+    }
+
+    #[export]
+    fn test_hash_modified(
+        map: std::collections::BTreeMap<String, String>,
+    ) -> std::collections::BTreeMap<String, String> {
+        map
+    }
+
+    #[export]
+    fn test_serde_bytes(mut blob: Blob) -> Blob {
+        blob.data.reverse();
+        blob
+    }
+
+    #[export]
+    fn test_copied_str_return(text: &str) -> &str {
+        text.trim()
+    }
+
+    #[export(substr_return)]
+    fn test_borrowed_str_return(text: &str) -> &str {
+        text.trim()
+    }
+
+    #[export(substr_return)]
+    fn test_borrowed_cow_return(text: &str) -> std::borrow::Cow<'_, str> {
+        match text.strip_prefix("copy:") {
+            Some(rest) => rest.to_uppercase().into(),
+            None => text.trim().into(),
+        }
+    }
+
+    #[export]
+    fn test_is_true(#[raw] value: Value) -> bool {
+        value.is_true()
+    }
+
+    #[export]
+    fn test_sort(list: &perlmod::Array) -> Vec<Value> {
+        use perlmod::value::SortableValue;
+
+        let mut sorted: Vec<SortableValue> = list.iter().map(SortableValue).collect();
+        sorted.sort();
+        sorted.into_iter().map(SortableValue::into_inner).collect()
+    }
+
+    #[export]
+    fn test_str_cmp(#[raw] a: Value, #[raw] b: Value) -> Result<i8, perlmod::Error> {
+        Ok(a.str_cmp(&b)? as i8)
+    }
+
+    #[export]
+    fn test_eval(code: &str, context: &str) -> Result<Vec<String>, perlmod::Error> {
+        let context = match context {
+            "void" => perlmod::ffi::Gimme::Void,
+            "scalar" => perlmod::ffi::Gimme::Scalar,
+            _ => perlmod::ffi::Gimme::List,
+        };
+        Ok(perlmod::eval(code, context)?
+            .iter()
+            .map(|value| value.pv_string_utf8().to_string())
+            .collect())
+    }
+
+    #[export(option_as_list)]
+    fn test_option_as_list(value: Option<String>) -> Option<String> {
+        value
+    }
+
+    #[export(raw_return)]
+    fn test_bool_values(b: bool) -> Result<(Value, Value), Error> {
+        Ok((perlmod::to_value(&b)?, Value::bool_value(b)))
+    }
+
+    #[export]
+    fn test_register_manual() -> (&'static str, Option<&'static str>) {
+        crate::manual::register();
+        (
+            crate::manual::manual_add_one_meta::XS_NAME,
+            crate::manual::manual_add_one_meta::PROTOTYPE,
+        )
+    }
+
+    #[export]
+    fn test_clear(#[raw] value: Value) -> Result<usize, Error> {
+        match value.dereference() {
+            Some(Value::Hash(hash)) => {
+                hash.clear();
+                Ok(hash.len())
+            }
+            Some(Value::Array(array)) => {
+                array.clear();
+                Ok(array.len())
+            }
+            _ => bail!("expected a hash or array reference"),
+        }
+    }
+
+    #[export]
+    fn test_retain(list: &perlmod::Array, keep_all: bool) {
+        if keep_all {
+            list.retain(|_| true);
+        } else {
+            list.retain(|value| value.dereference().is_some() || value.iv() % 2 == 0);
+        }
+    }
+
+    #[export]
+    fn test_dedup(list: &perlmod::Array) {
+        list.dedup_by(|a, b| a.pv_bytes() == b.pv_bytes());
+    }
+
+    #[export]
+    fn test_integer(value: i32) -> i32 {
+        value
+    }
+
+    #[export(return_ok_true)]
+    fn test_return_ok_true(fail: bool) -> Result<(), Error> {
+        if fail {
+            bail!("failed as requested");
+        }
+        Ok(())
+    }
+
+    #[export]
+    fn test_warn(message: &str) -> Result<(), Error> {
+        perlmod::warn(message)?;
+        Ok(())
+    }
+
+    #[export]
+    fn test_carp(message: &str) -> Result<(), Error> {
+        perlmod::carp(message)?;
+        Ok(())
+    }
+
+    #[export(raw_return)]
+    fn test_utf8_flag(#[raw] value: Value) -> Result<Value, Error> {
+        let fixed = Value::new_bytes(value.pv_bytes());
+        fixed.set_utf8(true)?;
+        Ok(fixed)
+    }
+
+    #[export]
+    fn test_callback(#[coderef] callback: perlmod::Callback) -> Result<usize, Error> {
+        let mut accepted = 0;
+        for (index, name) in (0..).zip(["first", "second", "third"]) {
+            let item = BlessedData {
+                name: name.to_string(),
+                count: index,
+            };
+            if callback.call_with(&item)?.len() == 1 {
+                accepted += 1;
+            }
+        }
+        Ok(accepted)
+    }
+
     #[derive(serde::Serialize)]
     struct MyError {
         a: String,
@@ -114,6 +1270,26 @@ mod export {
         }
     }
 
+    #[derive(Debug)]
+    struct ErrnoError(i32);
+
+    impl std::fmt::Display for ErrnoError {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "failed with errno {}", self.0)
+        }
+    }
+
+    impl perlmod::error::HasErrno for ErrnoError {
+        fn errno(&self) -> Option<i32> {
+            Some(self.0)
+        }
+    }
+
+    #[export(errno)]
+    fn test_errno_from_error(errno: i32) -> Result<(), ErrnoError> {
+        Err(ErrnoError(errno))
+    }
+
     #[export]
     fn use_safe_putenv(on: bool) {
         perlmod::ffi::use_safe_putenv(on);
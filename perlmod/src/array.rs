@@ -6,7 +6,7 @@ use crate::error::CastError;
 use crate::ffi::{self, AV, SV};
 use crate::raw_value;
 use crate::scalar::{Scalar, ScalarRef};
-use crate::Value;
+use crate::{Error, Value};
 
 /// An owned reference to a perl array value (AV).
 ///
@@ -28,6 +28,14 @@ impl Array {
         self.0
     }
 
+    /// Turn this into a reference to the array, as commonly passed to or returned to perl.
+    ///
+    /// Contrary to [`Value::from`], which keeps the array itself, this produces the equivalent of
+    /// [`Value::new_ref_owned(array)`](Value::new_ref_owned()).
+    pub fn into_ref(self) -> Value {
+        Value::new_ref_owned(self)
+    }
+
     /// Get the internal perl value as a low-level [`AV`] pointer.
     pub fn av(&self) -> *mut AV {
         self.0.sv() as *mut AV
@@ -70,13 +78,15 @@ impl Array {
         Self(self.0.clone_ref())
     }
 
-    /// Get the length of the array.
+    /// Get the length of the array. For tied arrays this calls `FETCHSIZE`.
     pub fn len(&self) -> usize {
         // perl returns the highest index, not the length!
         unsafe { ffi::RSPL_av_len(self.av()).wrapping_add(1) }
     }
 
     /// Check if this is an empty array.
+    ///
+    /// Like [`len`](Array::len()), this calls `FETCHSIZE` on tied arrays.
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
@@ -92,6 +102,29 @@ impl Array {
         }
     }
 
+    /// Get the element at `index` for modification, creating it as `undef` if it does not exist
+    /// yet, like using `$array[$index]` as an lvalue in perl.
+    ///
+    /// The returned value aliases the element stored in the array, so modifying it in place, for
+    /// instance via [`ScalarRef::set_int`](crate::ScalarRef::set_int()), modifies the array.
+    ///
+    /// ```
+    /// # fn code(counters: &perlmod::Array) {
+    /// if let Some(counter) = counters.element(3) {
+    ///     counter.set_int(counter.iv() + 1);
+    /// }
+    /// # }
+    /// ```
+    pub fn element(&self, index: usize) -> Option<Value> {
+        let index = libc::ssize_t::try_from(index).ok()?;
+        let sv: *mut *mut SV = unsafe { ffi::RSPL_av_fetch(self.av(), index, 1) };
+        if sv.is_null() {
+            None
+        } else {
+            Some(unsafe { Value::from_raw_ref(*sv) })
+        }
+    }
+
     /// Create an iterator over this array's values.
     pub fn iter(&self) -> Iter {
         Iter {
@@ -119,6 +152,148 @@ impl Array {
         }
     }
 
+    /// Push copies of all `items` onto the array, reserving the required space up front.
+    pub fn extend_from_slice<T>(&self, items: &[T])
+    where
+        T: Into<Value> + Clone,
+    {
+        self.reserve(items.len());
+        for item in items {
+            self.push(item.clone().into());
+        }
+    }
+
+    /// Push all elements of `other` onto the array, reserving the required space up front.
+    ///
+    /// The elements are not copied, both arrays refer to the same values afterwards, so modifying
+    /// an element through one array is visible through the other. Nonexistent elements are
+    /// appended as new `undef` values. Appending an array to itself appends each element once.
+    pub fn append(&self, other: &Array) {
+        // take the length first, `other` may be `self`
+        let count = other.len();
+        self.reserve(count);
+        for index in 0..count {
+            self.push(other.get(index).unwrap_or_else(new_element));
+        }
+    }
+
+    /// Insert `value` at `index`, shifting all elements after it one position up, like
+    /// [`Vec::insert`](std::vec::Vec::insert()).
+    ///
+    /// The shifted elements are moved rather than copied, so references to them stay valid, except
+    /// in tied arrays, where they are copied. Nonexistent elements after `index` are filled in with
+    /// new `undef` values. Fails if `index` is greater than the array's length.
+    pub fn insert(&self, index: usize, value: Value) -> Result<(), Error> {
+        let len = self.len();
+        if index > len {
+            return Err(Error::new_owned(format!(
+                "insertion index {index} out of bounds for array of length {len}"
+            )));
+        }
+
+        let tail: Vec<Value> = (index..len).map(|at| self.take_element(at)).collect();
+        for _ in index..len {
+            self.pop();
+        }
+
+        self.reserve(tail.len() + 1);
+        self.push(value);
+        for value in tail {
+            self.push(value);
+        }
+        Ok(())
+    }
+
+    /// Remove the element at `index` and return it, replacing it with the last element, like
+    /// [`Vec::swap_remove`](std::vec::Vec::swap_remove()).
+    ///
+    /// This does not preserve the order of the elements, but does not need to shift any of them.
+    /// Nonexistent elements are returned as, or moved into place as, new `undef` values. Returns
+    /// `None` if `index` is out of bounds.
+    pub fn swap_remove(&self, index: usize) -> Option<Value> {
+        let len = self.len();
+        if index >= len {
+            return None;
+        }
+
+        let removed = self.take_element(index);
+        if index + 1 < len {
+            let last = self.take_element(len - 1);
+            unsafe { ffi::RSPL_av_store(self.av(), index as libc::ssize_t, last.into_raw()) };
+        }
+        self.pop();
+        Some(removed)
+    }
+
+    /// Get the element at `index` to move it elsewhere.
+    ///
+    /// The elements of tied arrays are only proxies fetching the value when accessed, so these
+    /// get copied instead.
+    fn take_element(&self, index: usize) -> Value {
+        let value = self.get(index).unwrap_or_else(new_element);
+        if unsafe { ffi::RSPL_SvMAGICAL(self.sv()) } {
+            Value::Scalar(unsafe { Scalar::from_raw_move(ffi::RSPL_newSVsv(value.sv())) })
+        } else {
+            value
+        }
+    }
+
+    /// Remove all elements from the array in place. (perlxs `av_clear`).
+    ///
+    /// Other references to this array will see it empty as well.
+    pub fn clear(&self) {
+        unsafe { ffi::RSPL_av_clear(self.av()) }
+    }
+
+    /// Keep only the elements for which `keep` returns true, preserving their order.
+    ///
+    /// Nonexistent elements are passed as `undef`. If all elements are kept, the array is left
+    /// untouched.
+    pub fn retain<F>(&self, mut keep: F)
+    where
+        F: FnMut(&Value) -> bool,
+    {
+        self.retain_with_last(|value, _| keep(value))
+    }
+
+    /// Remove consecutive elements for which `same` returns true, like
+    /// [`Vec::dedup_by`](std::vec::Vec::dedup_by()), except that the elements are not mutable.
+    ///
+    /// `same` is called with the current element and the last element which was kept.
+    pub fn dedup_by<F>(&self, mut same: F)
+    where
+        F: FnMut(&Value, &Value) -> bool,
+    {
+        self.retain_with_last(|value, last| !matches!(last, Some(last) if same(value, last)))
+    }
+
+    /// Rebuild the array from the elements for which `keep` returns true. `keep` also receives
+    /// the last element kept so far.
+    fn retain_with_last<F>(&self, mut keep: F)
+    where
+        F: FnMut(&Value, Option<&Value>) -> bool,
+    {
+        let len = self.len();
+        let mut kept: Vec<Value> = Vec::with_capacity(len);
+        for index in 0..len {
+            let value = self.take_element(index);
+            if keep(&value, kept.last()) {
+                kept.push(value);
+            }
+        }
+
+        if kept.len() == len {
+            return;
+        }
+
+        // The kept values hold their own reference which gets moved back into the array.
+        unsafe { ffi::RSPL_av_clear(self.av()) };
+        self.reserve(kept.len());
+        for value in kept {
+            self.push(value);
+        }
+    }
+
     /// Pop a value off of the array's end.
     pub fn pop(&self) -> Option<Value> {
         if self.is_empty() {
@@ -127,6 +302,23 @@ impl Array {
             Some(unsafe { Value::from_raw_move(ffi::RSPL_av_pop(self.av())) })
         }
     }
+
+    /// Deserialize each element of the array into a `Vec`, see [`from_value`](crate::from_value).
+    ///
+    /// Nonexistent elements are deserialized from `undef`.
+    pub fn into_vec<T>(&self) -> Result<Vec<T>, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        (0..self.len())
+            .map(|index| crate::from_value(self.get(index).unwrap_or_else(Value::new_undef)))
+            .collect()
+    }
+}
+
+/// A new `undef` value to put into an array in place of a nonexistent element.
+fn new_element() -> Value {
+    Value::Scalar(unsafe { Scalar::from_raw_move(ffi::RSPL_newSVsv(ffi::RSPL_get_undef())) })
 }
 
 impl core::ops::Deref for Array {
@@ -155,20 +347,14 @@ impl TryFrom<Scalar> for Array {
     }
 }
 
+/// This prints the array's contents recursively up to a depth of
+/// [`DEBUG_DEPTH`](crate::value::DEBUG_DEPTH), see [`Value::debug_nested`].
 impl std::fmt::Debug for Array {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "[")?;
-        let mut comma = false;
-        for i in self {
-            if comma {
-                write!(f, ", {i:?}")?;
-            } else {
-                comma = true;
-                write!(f, "{i:?}")?;
-            }
-        }
-        write!(f, "]")?;
-        Ok(())
+        std::fmt::Debug::fmt(
+            &Value::Array(self.clone_ref()).debug_nested(crate::value::DEBUG_DEPTH),
+            f,
+        )
     }
 }
 
@@ -0,0 +1,163 @@
+//! A perl code reference which can be called from rust.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::hash::Hash;
+
+use serde::Serialize;
+
+use crate::{Error, Value};
+
+/// A code reference passed from perl, typically via a `#[coderef]` parameter of an
+/// [`#[export]`](macro@crate::export) function.
+///
+/// This allows streaming items back to perl one at a time instead of collecting them into a
+/// single return value. Errors (`die`) in the perl callback are propagated as [`Error`]s, so they
+/// can simply be passed on with `?`.
+///
+/// ```
+/// #[perlmod::package(name = "RSPM::Doc::Callback", file = "/dev/null")]
+/// mod export {
+///     use perlmod::{Callback, Error};
+///
+///     #[export]
+///     fn for_each_item(#[coderef] callback: Callback) -> Result<(), Error> {
+///         for item in ["one", "two", "three"] {
+///             callback.call_with(&item)?;
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Callback(Value);
+
+impl Callback {
+    /// Wrap a value, which must be a code reference.
+    pub fn new(value: Value) -> Result<Self, Error> {
+        match value.dereference() {
+            Some(code) if code.reftype(false) == "CODE" => Ok(Self(value)),
+            _ => Err(Error::new("expected a code reference")),
+        }
+    }
+
+    /// Call the code reference with raw perl values as arguments.
+    ///
+    /// The values returned from the callback are returned as a list.
+    pub fn call(&self, args: &[Value]) -> Result<Vec<Value>, Error> {
+        self.0.call(args)
+    }
+
+    /// Serialize `item` and call the code reference with it as its only argument.
+    pub fn call_with<T: Serialize>(&self, item: &T) -> Result<Vec<Value>, Error> {
+        self.0.call(&[crate::to_value(item)?])
+    }
+
+    /// Get the code reference as a [`Value`].
+    pub fn into_inner(self) -> Value {
+        self.0
+    }
+
+    /// Turn this into a [`CachedCall`] remembering the results for each argument.
+    pub fn cached<K>(self) -> CachedCall<K>
+    where
+        K: Hash + Eq + Serialize,
+    {
+        CachedCall::new(self)
+    }
+}
+
+impl TryFrom<Value> for Callback {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Error> {
+        Self::new(value)
+    }
+}
+
+impl TryFrom<Value> for Option<Callback> {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Error> {
+        if value.is_undef() {
+            Ok(None)
+        } else {
+            Callback::new(value).map(Some)
+        }
+    }
+}
+
+/// A [`Callback`] which remembers its results, so that repeated calls with the same argument only
+/// invoke the perl code once.
+///
+/// This is meant for callbacks without side effects, such as configuration lookups. The results
+/// are kept per argument until [`clear`](CachedCall::clear()) is called, while errors are not
+/// cached. Note that cached results are returned as new references to the same perl values, so
+/// modifications to them are visible to later calls.
+///
+/// ```
+/// #[perlmod::package(name = "RSPM::Doc::CachedCall", file = "/dev/null")]
+/// mod export {
+///     use perlmod::{Callback, Error};
+///
+///     #[export]
+///     fn lookup_all(
+///         #[coderef] lookup: Callback,
+///         keys: Vec<String>,
+///     ) -> Result<Vec<String>, Error> {
+///         let lookup = lookup.cached();
+///         let mut values = Vec::new();
+///         for key in keys {
+///             for value in lookup.call_with(&key)? {
+///                 values.push(value.pv_string_utf8().to_string());
+///             }
+///         }
+///         Ok(values)
+///     }
+/// }
+/// ```
+pub struct CachedCall<K> {
+    callback: Callback,
+    cache: RefCell<HashMap<K, Vec<Value>>>,
+}
+
+impl<K> CachedCall<K>
+where
+    K: Hash + Eq + Serialize,
+{
+    /// Wrap a callback with an empty cache.
+    pub fn new(callback: Callback) -> Self {
+        Self {
+            callback,
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Get the results of calling the code reference with the serialized `arg` as its only
+    /// argument, calling it only if there are no cached results for `arg` yet.
+    pub fn call_with(&self, arg: &K) -> Result<Vec<Value>, Error>
+    where
+        K: Clone,
+    {
+        if let Some(values) = self.cache.borrow().get(arg) {
+            return Ok(values.iter().map(Value::clone_ref).collect());
+        }
+
+        // the callback may recursively use this cache, so don't hold a borrow during the call
+        let values = self.callback.call_with(arg)?;
+        let result = values.iter().map(Value::clone_ref).collect();
+        self.cache.borrow_mut().insert(arg.clone(), values);
+        Ok(result)
+    }
+
+    /// Drop all cached results.
+    pub fn clear(&self) {
+        self.cache.borrow_mut().clear();
+    }
+
+    /// Get the wrapped callback back, dropping the cached results.
+    pub fn into_inner(self) -> Callback {
+        self.callback
+    }
+}
@@ -1,15 +1,18 @@
 //! Serde deserializer for perl values.
 
+use std::cell::RefCell;
+use std::fmt;
 use std::marker::PhantomData;
+use std::rc::Rc;
 
-use serde::de::value::BorrowedStrDeserializer;
+use serde::de::value::{BorrowedStrDeserializer, StrDeserializer};
 use serde::de::{
     self, Deserialize, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor,
 };
 
 use crate::error::Error;
 use crate::raw_value;
-use crate::scalar::Type;
+use crate::scalar::{ScalarRef, Type};
 use crate::Value;
 use crate::{array, ffi, hash};
 
@@ -17,13 +20,104 @@ use crate::{array, ffi, hash};
 struct Deserializer<'de> {
     input: Value,
     option_allowed: bool,
+    borrow: Borrow,
+    options: Rc<Options>,
     _lifetime: PhantomData<&'de Value>,
 }
 
+/// Settings of a deserialization, shared by the deserializers of all the nested values.
+#[derive(Default)]
+struct Options {
+    /// Only accept perl's canonical boolean values for `bool`s, see [`from_value_strict_bool`].
+    strict_bool: bool,
+    /// The coercions made so far if deserializing via [`from_value_lenient`].
+    warnings: Option<RefCell<Vec<Warning>>>,
+}
+
+impl Options {
+    /// Check whether we're deserializing via [`from_value_lenient`].
+    fn lenient(&self) -> bool {
+        self.warnings.is_some()
+    }
+
+    /// Record a coercion if we're deserializing via [`from_value_lenient`].
+    fn coerced(&self, message: impl FnOnce() -> String) {
+        if let Some(warnings) = &self.warnings {
+            warnings.borrow_mut().push(Warning(message()));
+        }
+    }
+}
+
+/// What strings and byte slices passed to visitors may borrow from.
+#[derive(Clone, Copy, PartialEq)]
+enum Borrow {
+    /// Always pass copies.
+    Copy,
+    /// Borrow from any value, including temporary ones, see [`from_ref_value`].
+    Any,
+    /// Borrow only from values owned by the input, see [`from_borrowed_value`].
+    Owned,
+}
+
+impl Borrow {
+    /// Restrict borrowing for the contents of `sv`: magical values, such as tied ones, only hold
+    /// the data they fetched temporarily, so with [`Borrow::Owned`] their contents are copied.
+    fn within(self, sv: *mut ffi::SV) -> Self {
+        match self {
+            Borrow::Owned if unsafe { ffi::RSPL_SvMAGICAL(sv) } => Borrow::Copy,
+            other => other,
+        }
+    }
+
+    /// Restrict borrowing for temporary values created during deserialization, such as the key
+    /// `SV`s of hash entries.
+    fn temporary(self) -> Self {
+        match self {
+            Borrow::Owned => Borrow::Copy,
+            other => other,
+        }
+    }
+}
+
+/// A coercion which happened while deserializing a value with [`from_value_lenient`].
+#[derive(Clone, Debug)]
+pub struct Warning(String);
+
+impl Warning {
+    /// Get the warning's message.
+    pub fn message(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 /// Deserialize a perl [`Value`](crate::Value).
 ///
 /// Note that this causes all the underlying data to be copied recursively, except for other
 /// [`Value`](crate::Value) variables, which will be references.
+///
+/// Perl hashes have no defined order, so ordered data is usually passed as an array of
+/// key-value pairs such as `[[a => 1], [b => 2]]`, which deserializes into a `Vec<(K, V)>`
+/// keeping the order, as well as into map types. Hashes can be deserialized into ordered maps
+/// such as a `BTreeMap`, which then orders the keys itself.
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// # fn code(value: perlmod::Value) -> Result<(), perlmod::Error> {
+/// // `[[b => 2], [a => 1]]` as well as `{ b => 2, a => 1 }` yield `a` first:
+/// let sorted: BTreeMap<String, u32> = perlmod::from_value(value.clone())?;
+/// // only `[[b => 2], [a => 1]]` is accepted, and yields `b` first:
+/// let ordered: Vec<(String, u32)> = perlmod::from_value(value)?;
+/// # let _ = (sorted, ordered);
+/// # Ok(())
+/// # }
+/// ```
 pub fn from_value<T>(input: Value) -> Result<T, Error>
 where
     T: serde::de::DeserializeOwned,
@@ -34,6 +128,100 @@ where
     Ok(out)
 }
 
+/// Deserialize a perl [`Value`](crate::Value) like [`from_value`], but only accept perl's
+/// canonical boolean values for `bool`s.
+///
+/// These are `1` and `0`, the strings `"1"`, `"0"` and `""`, and perl's native booleans such as
+/// `!!0`. Anything else, including `undef`, `2` or `"yes"`, results in an error instead of being
+/// interpreted by perl's truthiness rules.
+pub fn from_value_strict_bool<T>(input: Value) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let _guard = raw_value::guarded(true);
+    let options = Rc::new(Options {
+        strict_bool: true,
+        ..Options::default()
+    });
+    let mut deserializer = Deserializer::<'static>::with_borrow(input, Borrow::Any, options);
+    let out = T::deserialize(&mut deserializer)?;
+    Ok(out)
+}
+
+/// Deserialize a perl [`Value`](crate::Value) like [`from_value`], but coerce scalars of the
+/// wrong kind instead of failing, and return a [`Warning`] for each such coercion.
+///
+/// Strings parsing as integers are accepted for integers, strings perl considers numeric are
+/// accepted for floats, numbers are accepted for strings, and strings are accepted for `bool`s by
+/// perl's truthiness rules.
+///
+/// ```
+/// # fn code(value: perlmod::Value) -> Result<(), perlmod::Error> {
+/// let (count, warnings): (u32, _) = perlmod::from_value_lenient(value)?;
+/// for warning in warnings {
+///     perlmod::warn(&format!("count: {warning}\n"))?;
+/// }
+/// # let _ = count;
+/// # Ok(())
+/// # }
+/// ```
+pub fn from_value_lenient<T>(input: Value) -> Result<(T, Vec<Warning>), Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let _guard = raw_value::guarded(true);
+    let options = Rc::new(Options {
+        warnings: Some(RefCell::default()),
+        ..Options::default()
+    });
+    let mut deserializer =
+        Deserializer::<'static>::with_borrow(input, Borrow::Any, Rc::clone(&options));
+    let out = T::deserialize(&mut deserializer)?;
+    let warnings = options.warnings.as_ref().map(RefCell::take);
+    Ok((out, warnings.unwrap_or_default()))
+}
+
+/// Deserialize a blessed object into an externally tagged enum, using the package the object is
+/// blessed into as the variant and the object's contents as the variant's data.
+///
+/// Since package names contain `::`, variants usually need a `#[serde(rename = "...")]`, which also
+/// allows mapping classes to arbitrary variant names. Objects blessed into classes without a
+/// variant fail to deserialize.
+///
+/// ```
+/// #[derive(serde::Deserialize)]
+/// enum Shape {
+///     #[serde(rename = "My::Circle")]
+///     Circle { radius: f64 },
+///     #[serde(rename = "My::Polygon")]
+///     Polygon(Vec<(f64, f64)>),
+/// }
+///
+/// // `bless({ radius => 1 }, 'My::Circle')` or `bless([[0, 0], [1, 1], [0, 1]], 'My::Polygon')`
+/// fn shape(object: perlmod::Value) -> Result<Shape, perlmod::Error> {
+///     perlmod::from_blessed_value(object)
+/// }
+/// ```
+pub fn from_blessed_value<T>(input: Value) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let _guard = raw_value::guarded(true);
+
+    if !matches!(input, Value::Reference(_)) {
+        return Error::fail("expected a blessed reference");
+    }
+    let class = input.class_name()?;
+    let value = input
+        .dereference()
+        .ok_or_else(|| Error::new("failed to dereference a reference while deserializing"))?;
+
+    T::deserialize(BlessedDeserializer {
+        class,
+        value: Some(value),
+    })
+}
+
 /// Deserialize a reference to a perl [`Value`](crate::Value).
 ///
 /// Note that this causes all the underlying data to be copied recursively, except for other
@@ -49,17 +237,129 @@ where
     Ok(out)
 }
 
+/// Deserialize a reference to a perl [`Value`](crate::Value), borrowing strings and byte slices
+/// from the value and the data it contains.
+///
+/// Contrary to [`from_ref_value`], borrowed `&'de str` and `&'de [u8]` outputs never point into
+/// temporary values created during deserialization: strings, byte strings and hash keys are only
+/// borrowed if they are owned by the value itself or the arrays, hashes and references nested
+/// within it. Everything else, such as the contents of tied or otherwise magical values, is
+/// copied, so borrowing types such as `Cow<'de, str>` with `#[serde(borrow)]` will be owned there,
+/// and plain `&'de str` members will fail to deserialize.
+///
+/// # Safety
+///
+/// The `&'de Value` only keeps the value itself alive. The data nested within it is owned by the
+/// containing arrays, hashes and references, which can be modified through a shared reference,
+/// for instance with [`Hash::insert`](crate::Hash::insert) or
+/// [`ScalarRef::set_string`](crate::ScalarRef::set_string), as well as by perl code. The caller
+/// must make sure that neither the value nor anything nested within it is modified or freed while
+/// the deserialized data is in use. This includes perl code run during deserialization, such as
+/// that of tied values.
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use perlmod::Value;
+///
+/// #[derive(serde::Deserialize)]
+/// struct Entry<'a> {
+///     name: &'a str,
+///     size: u64,
+/// }
+///
+/// // Safety: the values are not modified while the result is in use.
+/// fn name(value: &Value) -> Result<&str, perlmod::Error> {
+///     unsafe { perlmod::from_borrowed_value(value) }
+/// }
+///
+/// fn entries(value: &Value) -> Result<HashMap<&str, Entry>, perlmod::Error> {
+///     unsafe { perlmod::from_borrowed_value(value) }
+/// }
+/// ```
+pub unsafe fn from_borrowed_value<'de, T>(input: &'de Value) -> Result<T, Error>
+where
+    T: Deserialize<'de>,
+{
+    let _guard = raw_value::guarded(true);
+    let mut deserializer =
+        Deserializer::<'de>::with_borrow(input.clone_ref(), Borrow::Owned, Rc::default());
+    T::deserialize(&mut deserializer)
+}
+
+/// Additional checks for deserialized parameters of [`#[export]`](macro@crate::export) functions
+/// marked with `#[validate]`, for requirements `Deserialize` does not cover, such as value ranges
+/// or non-empty strings.
+///
+/// If validation fails, the function is not called and perl code gets the error's `Display`
+/// output as the `die` message instead.
+///
+/// ```
+/// #[perlmod::package(name = "RSPM::Doc::Validate", file = "/dev/null")]
+/// mod export {
+///     #[derive(serde::Deserialize)]
+///     struct Limits {
+///         min: u32,
+///         max: u32,
+///     }
+///
+///     impl perlmod::Validate for Limits {
+///         type Error = String;
+///
+///         fn validate(&self) -> Result<(), String> {
+///             if self.min > self.max {
+///                 return Err(format!("min ({}) exceeds max ({})", self.min, self.max));
+///             }
+///             Ok(())
+///         }
+///     }
+///
+///     #[export]
+///     fn range(#[validate] limits: Limits) -> u32 {
+///         limits.max - limits.min
+///     }
+/// }
+/// ```
+pub trait Validate {
+    /// The error type, whose `Display` output is passed on to perl.
+    type Error: std::fmt::Display;
+
+    /// Check the deserialized value.
+    fn validate(&self) -> Result<(), Self::Error>;
+}
+
+/// Optional parameters are only validated if they were passed.
+impl<T: Validate> Validate for Option<T> {
+    type Error = T::Error;
+
+    fn validate(&self) -> Result<(), T::Error> {
+        match self {
+            Some(value) => value.validate(),
+            None => Ok(()),
+        }
+    }
+}
+
 impl<'deserializer> Deserializer<'deserializer> {
     pub fn from_value(input: Value) -> Self {
+        Self::with_borrow(input, Borrow::Any, Rc::default())
+    }
+
+    /// With [`Borrow::Copy`], strings and byte slices are always passed to the visitor as copies,
+    /// including those of nested values and hash keys.
+    fn with_borrow(input: Value, borrow: Borrow, options: Rc<Options>) -> Self {
         Deserializer {
             input,
             option_allowed: true,
+            borrow,
+            options,
             _lifetime: PhantomData,
         }
     }
 
     fn deref_current(&mut self) -> Result<(), Error> {
         while let Value::Reference(_) = &self.input {
+            self.borrow = self.borrow.within(self.input.sv());
             self.input = self.input.dereference().ok_or_else(|| {
                 Error::new("failed to dereference a reference while deserializing")
             })?;
@@ -71,8 +371,9 @@ impl<'deserializer> Deserializer<'deserializer> {
         if let Value::Scalar(value) = &self.input {
             match value.ty() {
                 Type::Scalar(_) => Ok(()),
-                Type::Other(other) => Err(Error(format!(
-                    "cannot deserialize weird magic perl values ({other})"
+                Type::Other(_) => Err(Error(format!(
+                    "cannot deserialize weird magic perl values ({})",
+                    value.svtype_name()
                 ))),
                 // These are impossible as they are all handled by different Value enum types:
                 Type::Reference => Error::fail("Value::Scalar: containing a reference"),
@@ -95,14 +396,15 @@ impl<'deserializer> Deserializer<'deserializer> {
     where
         V: Visitor<'de>,
     {
+        let borrow = self.borrow;
+        let options = self.options.clone();
         match self.get()? {
             Value::Scalar(value) => match value.ty() {
                 Type::Scalar(flags) => {
                     use crate::scalar::Flags;
 
                     if flags.contains(Flags::STRING) {
-                        let s = unsafe { str_set_wrong_lifetime(value.pv_string_utf8()) };
-                        visitor.visit_borrowed_str(s)
+                        visit_str(value, borrow, visitor)
                     } else if flags.contains(Flags::DOUBLE) {
                         visitor.visit_f64(value.nv())
                     } else if flags.contains(Flags::INTEGER) {
@@ -115,8 +417,12 @@ impl<'deserializer> Deserializer<'deserializer> {
                 }
                 _ => unreachable!(),
             },
-            Value::Hash(value) => visitor.visit_map(HashAccess::new(value)),
-            Value::Array(value) => visitor.visit_seq(ArrayAccess::new(value)),
+            Value::Hash(value) => {
+                visitor.visit_map(HashAccess::with_borrow(value, borrow, options))
+            }
+            Value::Array(value) => {
+                visitor.visit_seq(ArrayAccess::with_borrow(value, borrow, options))
+            }
             Value::Reference(_) => unreachable!(),
         }
     }
@@ -126,35 +432,111 @@ impl<'deserializer> Deserializer<'deserializer> {
     where
         V: Visitor<'de>,
     {
+        let borrow = self.borrow;
+        let options = self.options.clone();
         match self.get()? {
             Value::Scalar(value) => match value.ty() {
                 Type::Scalar(flags) => {
                     use crate::scalar::Flags;
 
                     if flags.contains(Flags::INTEGER) {
-                        visitor.visit_i64(value.iv() as i64)
+                        visit_integer(value, visitor)
                     } else if flags.contains(Flags::DOUBLE) {
                         visitor.visit_f64(value.nv())
                     } else if flags.contains(Flags::STRING) {
-                        let s = unsafe { str_set_wrong_lifetime(value.pv_string_utf8()) };
-                        visitor.visit_borrowed_str(s)
+                        if options.lenient() {
+                            let text = value.pv_string_utf8();
+                            if let Ok(v) = text.parse::<i64>() {
+                                options
+                                    .coerced(|| format!("coerced string '{text}' to an integer"));
+                                return visitor.visit_i64(v);
+                            } else if let Ok(v) = text.parse::<u64>() {
+                                options
+                                    .coerced(|| format!("coerced string '{text}' to an integer"));
+                                return visitor.visit_u64(v);
+                            }
+                        }
+                        visit_str(value, borrow, visitor)
                     } else {
                         visitor.visit_unit()
                     }
                 }
                 _ => unreachable!(),
             },
-            Value::Hash(value) => visitor.visit_map(HashAccess::new(value)),
-            Value::Array(value) => visitor.visit_seq(ArrayAccess::new(value)),
+            Value::Hash(value) => {
+                visitor.visit_map(HashAccess::with_borrow(value, borrow, options))
+            }
+            Value::Array(value) => {
+                visitor.visit_seq(ArrayAccess::with_borrow(value, borrow, options))
+            }
             Value::Reference(_) => unreachable!(),
         }
     }
 
+    /// deserialize_any_nv for `f32` and `f64`. With [`from_value_lenient`] this also accepts
+    /// strings perl considers numeric, such as `"1.5"`, `"NaN"` or `"-Inf"`, by using perl's own
+    /// numeric conversion.
+    ///
+    /// `f32` values are narrowed from the `f64` by serde, which keeps NaN and the infinities.
+    fn deserialize_float<'de, V>(&mut self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        use crate::scalar::Flags;
+
+        let options = self.options.clone();
+        if !options.lenient() {
+            return self.deserialize_any_nv(visitor);
+        }
+
+        if let Value::Scalar(value) = self.get()? {
+            if value.ty() == Type::Scalar(Flags::STRING) && value.looks_like_number() {
+                options
+                    .coerced(|| format!("coerced string '{}' to a number", value.pv_string_utf8()));
+                return visitor.visit_f64(value.nv());
+            }
+        }
+
+        self.deserialize_any_nv(visitor)
+    }
+
+    /// deserialize_any_iv for 64 and 128 bit integers, which also accepts strings of decimal
+    /// integers out of perl's integer range, as produced by the serializer for such values.
+    fn deserialize_wide_iv<'de, V>(&mut self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        use crate::scalar::Flags;
+
+        if let Value::Scalar(value) = self.get()? {
+            if value.ty() == Type::Scalar(Flags::STRING) {
+                let text = value.pv_string_utf8();
+                if let Ok(v) = text.parse::<i128>() {
+                    if isize::try_from(v).is_err() && usize::try_from(v).is_err() {
+                        return match i64::try_from(v) {
+                            Ok(v) => visitor.visit_i64(v),
+                            Err(_) => visitor.visit_i128(v),
+                        };
+                    }
+                } else if let Ok(v) = text.parse::<u128>() {
+                    return match u64::try_from(v) {
+                        Ok(v) => visitor.visit_u64(v),
+                        Err(_) => visitor.visit_u128(v),
+                    };
+                }
+            }
+        }
+
+        self.deserialize_any_iv(visitor)
+    }
+
     /// deserialize_any, preferring a float value
     fn deserialize_any_nv<'de, V>(&mut self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
+        let borrow = self.borrow;
+        let options = self.options.clone();
         match self.get()? {
             Value::Scalar(value) => match value.ty() {
                 Type::Scalar(flags) => {
@@ -165,21 +547,109 @@ impl<'deserializer> Deserializer<'deserializer> {
                     } else if flags.contains(Flags::INTEGER) {
                         visitor.visit_i64(value.iv() as i64)
                     } else if flags.contains(Flags::STRING) {
-                        let s = unsafe { str_set_wrong_lifetime(value.pv_string_utf8()) };
-                        visitor.visit_borrowed_str(s)
+                        visit_str(value, borrow, visitor)
                     } else {
                         visitor.visit_unit()
                     }
                 }
                 _ => unreachable!(),
             },
-            Value::Hash(value) => visitor.visit_map(HashAccess::new(value)),
-            Value::Array(value) => visitor.visit_seq(ArrayAccess::new(value)),
+            Value::Hash(value) => {
+                visitor.visit_map(HashAccess::with_borrow(value, borrow, options))
+            }
+            Value::Array(value) => {
+                visitor.visit_seq(ArrayAccess::with_borrow(value, borrow, options))
+            }
             Value::Reference(_) => unreachable!(),
         }
     }
 }
 
+/// Visit a string scalar, borrowing it from the value if `borrow` allows it.
+fn visit_str<'de, V>(value: &ScalarRef, borrow: Borrow, visitor: V) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    if borrow.within(value.sv()) != Borrow::Copy {
+        visitor.visit_borrowed_str(unsafe { str_set_wrong_lifetime(value.pv_string_utf8()) })
+    } else {
+        visitor.visit_str(value.pv_string_utf8())
+    }
+}
+
+/// Visit a scalar as a bool for [`from_value_strict_bool`], accepting only perl's canonical boolean
+/// values.
+fn visit_strict_bool<'de, V>(
+    value: &ScalarRef,
+    flags: crate::scalar::Flags,
+    visitor: V,
+) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    use crate::scalar::Flags;
+
+    if flags.intersects(Flags::INTEGER | Flags::DOUBLE) {
+        let nv = value.nv();
+        if nv == 0.0 || nv == 1.0 {
+            return visitor.visit_bool(nv == 1.0);
+        }
+    } else if flags.contains(Flags::STRING) {
+        match value.pv_bytes() {
+            b"" | b"0" => return visitor.visit_bool(false),
+            b"1" => return visitor.visit_bool(true),
+            _ => (),
+        }
+    } else {
+        return Error::fail("expected bool value, got undef");
+    }
+
+    Err(Error(format!(
+        "expected bool value, got '{}'",
+        value.pv_string_utf8()
+    )))
+}
+
+/// Visit a numeric scalar as an integer.
+///
+/// Floating point values are only accepted if they have no fractional part and fit into 64 bits,
+/// as `SvIV` would otherwise silently truncate them. Range checks for smaller types are left to
+/// the visitor.
+fn visit_integer<'de, V>(value: &ScalarRef, visitor: V) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    let sv = value.sv();
+
+    if unsafe { ffi::RSPL_SvIOK(sv) } {
+        if unsafe { ffi::RSPL_SvIsUV(sv) } {
+            return visitor.visit_u64(unsafe { ffi::RSPL_SvUV(sv) } as u64);
+        }
+        return visitor.visit_i64(value.iv() as i64);
+    }
+
+    if unsafe { ffi::RSPL_SvNOK(sv) } {
+        const I64_MIN: f64 = -9223372036854775808.0;
+        const I64_END: f64 = 9223372036854775808.0;
+        const U64_END: f64 = 18446744073709551616.0;
+
+        let nv = value.nv();
+        if nv.fract() != 0.0 {
+            return Err(Error(format!(
+                "cannot deserialize non-integral number {nv} as an integer"
+            )));
+        } else if (I64_MIN..I64_END).contains(&nv) {
+            return visitor.visit_i64(nv as i64);
+        } else if (0.0..U64_END).contains(&nv) {
+            return visitor.visit_u64(nv as u64);
+        } else {
+            return Err(Error(format!("number {nv} is out of range for an integer")));
+        }
+    }
+
+    visitor.visit_i64(value.iv() as i64)
+}
+
 /// We use this only for `Value`s in our deserializer. We know this works because serde says the
 /// lifetime needs to only live as long as the serializer, and we feed our serializer with the data
 /// from a borrowed Value (keeping references to all the contained data within perl), which lives
@@ -202,12 +672,21 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
+        let borrow = self.borrow;
+        let options = self.options.clone();
         match self.get()? {
             Value::Scalar(value) => match value.ty() {
                 Type::Scalar(flags) => {
                     use crate::scalar::Flags;
 
-                    if flags.is_empty() || flags.intersects(Flags::INTEGER | Flags::DOUBLE) {
+                    if options.strict_bool {
+                        visit_strict_bool(value, flags, visitor)
+                    } else if flags.is_empty() || flags.intersects(Flags::INTEGER | Flags::DOUBLE) {
+                        visitor.visit_bool(unsafe { ffi::RSPL_SvTRUE(value.sv()) })
+                    } else if options.lenient() {
+                        options.coerced(|| {
+                            format!("coerced string '{}' to a bool", value.pv_string_utf8())
+                        });
                         visitor.visit_bool(unsafe { ffi::RSPL_SvTRUE(value.sv()) })
                     } else {
                         Error::fail("expected bool value")
@@ -215,8 +694,12 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
                 }
                 _ => unreachable!(),
             },
-            Value::Hash(value) => visitor.visit_map(HashAccess::new(value)),
-            Value::Array(value) => visitor.visit_seq(ArrayAccess::new(value)),
+            Value::Hash(value) => {
+                visitor.visit_map(HashAccess::with_borrow(value, borrow, options))
+            }
+            Value::Array(value) => {
+                visitor.visit_seq(ArrayAccess::with_borrow(value, borrow, options))
+            }
             Value::Reference(_) => unreachable!(),
         }
     }
@@ -267,34 +750,50 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        self.deserialize_any_iv(visitor)
+        self.deserialize_wide_iv(visitor)
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_any_iv(visitor)
+        self.deserialize_wide_iv(visitor)
+    }
+
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_wide_iv(visitor)
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_wide_iv(visitor)
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_any_nv(visitor)
+        self.deserialize_float(visitor)
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_any_nv(visitor)
+        self.deserialize_float(visitor)
     }
 
     fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
+        let borrow = self.borrow;
+        let options = self.options.clone();
         match self.get()? {
             Value::Scalar(value) => match value.ty() {
                 Type::Scalar(flags) => {
@@ -314,10 +813,7 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
                         let mut chars = s.chars();
                         match chars.next() {
                             Some(ch) if chars.next().is_none() => visitor.visit_char(ch),
-                            _ => {
-                                let s = unsafe { str_set_wrong_lifetime(value.pv_string_utf8()) };
-                                visitor.visit_borrowed_str(s)
-                            }
+                            _ => visit_str(value, borrow, visitor),
                         }
                     } else {
                         visitor.visit_unit()
@@ -325,8 +821,12 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
                 }
                 _ => unreachable!(),
             },
-            Value::Hash(value) => visitor.visit_map(HashAccess::new(value)),
-            Value::Array(value) => visitor.visit_seq(ArrayAccess::new(value)),
+            Value::Hash(value) => {
+                visitor.visit_map(HashAccess::with_borrow(value, borrow, options))
+            }
+            Value::Array(value) => {
+                visitor.visit_seq(ArrayAccess::with_borrow(value, borrow, options))
+            }
             Value::Reference(_) => unreachable!(),
         }
     }
@@ -335,13 +835,30 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        self.deserialize_any(visitor)
+        self.deserialize_string(visitor)
     }
 
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
+        let options = self.options.clone();
+        if options.lenient() {
+            use crate::scalar::Flags;
+
+            if let Value::Scalar(value) = self.get()? {
+                if let Type::Scalar(flags) = value.ty() {
+                    if !flags.contains(Flags::STRING)
+                        && flags.intersects(Flags::INTEGER | Flags::DOUBLE)
+                    {
+                        let text = value.pv_string_utf8().to_string();
+                        options.coerced(|| format!("coerced number {text} to a string"));
+                        return visitor.visit_string(text);
+                    }
+                }
+            }
+        }
+
         self.deserialize_any(visitor)
     }
 
@@ -349,16 +866,22 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
+        let borrow = self.borrow;
+        let options = self.options.clone();
         match self.get()? {
             Value::Scalar(value) => match value.ty() {
                 Type::Scalar(flags) => {
                     use crate::scalar::Flags;
 
                     if flags.contains(Flags::STRING) {
-                        let bytes = value.pv_bytes();
-                        let bytes: &'de [u8] =
-                            unsafe { std::slice::from_raw_parts(bytes.as_ptr(), bytes.len()) };
-                        visitor.visit_borrowed_bytes(bytes)
+                        if borrow.within(value.sv()) != Borrow::Copy {
+                            let bytes = value.pv_bytes();
+                            let bytes: &'de [u8] =
+                                unsafe { std::slice::from_raw_parts(bytes.as_ptr(), bytes.len()) };
+                            visitor.visit_borrowed_bytes(bytes)
+                        } else {
+                            visitor.visit_bytes(value.pv_bytes())
+                        }
                     } else if flags.contains(Flags::DOUBLE) {
                         visitor.visit_f64(value.nv())
                     } else if flags.contains(Flags::INTEGER) {
@@ -369,8 +892,12 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
                 }
                 _ => unreachable!(),
             },
-            Value::Hash(value) => visitor.visit_map(HashAccess::new(value)),
-            Value::Array(value) => visitor.visit_seq(ArrayAccess::new(value)),
+            Value::Hash(value) => {
+                visitor.visit_map(HashAccess::with_borrow(value, borrow, options))
+            }
+            Value::Array(value) => {
+                visitor.visit_seq(ArrayAccess::with_borrow(value, borrow, options))
+            }
             Value::Reference(_) => unreachable!(),
         }
     }
@@ -419,13 +946,23 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
 
     fn deserialize_newtype_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_newtype_struct(self)
+        if name == raw_value::NAME {
+            if !raw_value::is_enabled() {
+                return Error::fail("attempted raw value deserialization while disabled");
+            }
+
+            visitor.visit_map(RawDeserializer {
+                value: Some(&self.input),
+            })
+        } else {
+            visitor.visit_newtype_struct(self)
+        }
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Error>
@@ -458,7 +995,14 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        self.deserialize_any(visitor)
+        let borrow = self.borrow;
+        let options = self.options.clone();
+        match self.get()? {
+            Value::Array(value) => {
+                visitor.visit_map(PairAccess::with_borrow(value, borrow, options))
+            }
+            _ => self.deserialize_any(visitor),
+        }
     }
 
     fn deserialize_struct<V>(
@@ -479,7 +1023,8 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
                 value: Some(&self.input),
             })
         } else {
-            self.deserialize_map(visitor)
+            // structs may also be deserialized from arrays, which are not lists of pairs here
+            self.deserialize_any(visitor)
         }
     }
 
@@ -495,6 +1040,8 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         let mut iter;
         // This is called for externally tagged enums only, so either a Hash with a single key, or
         // a simple string variant:
+        let borrow = self.borrow;
+        let options = self.options.clone();
         match self.get()? {
             Value::Scalar(value) => match value.ty() {
                 Type::Scalar(flags) => {
@@ -505,6 +1052,8 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
                         visitor.visit_enum(EnumDeserializer {
                             variant,
                             value: None,
+                            borrow,
+                            options,
                         })
                     } else {
                         Error::fail("expected an enum value")
@@ -527,11 +1076,15 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
                         visitor.visit_enum(EnumDeserializer {
                             variant,
                             value: Some(value),
+                            borrow: borrow.within(hash.sv()),
+                            options,
                         })
                     }
                     Err(_) => visitor.visit_enum(EnumDeserializerByteVariant {
                         variant: key,
                         value: Some(value),
+                        borrow: borrow.within(hash.sv()),
+                        options,
                     }),
                 }
             }
@@ -557,6 +1110,8 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
 struct EnumDeserializer<'a> {
     variant: &'a str,
     value: Option<Value>,
+    borrow: Borrow,
+    options: Rc<Options>,
 }
 
 impl<'a, 'de> de::EnumAccess<'de> for EnumDeserializer<'a> {
@@ -568,7 +1123,11 @@ impl<'a, 'de> de::EnumAccess<'de> for EnumDeserializer<'a> {
         V: de::DeserializeSeed<'de>,
     {
         let variant = self.variant.into_deserializer();
-        let visitor = VariantDeserializer { value: self.value };
+        let visitor = VariantDeserializer {
+            value: self.value,
+            borrow: self.borrow,
+            options: self.options.clone(),
+        };
         seed.deserialize(variant).map(|v| (v, visitor))
     }
 }
@@ -576,6 +1135,8 @@ impl<'a, 'de> de::EnumAccess<'de> for EnumDeserializer<'a> {
 struct EnumDeserializerByteVariant<'a> {
     variant: &'a [u8],
     value: Option<Value>,
+    borrow: Borrow,
+    options: Rc<Options>,
 }
 
 impl<'a, 'de> de::EnumAccess<'de> for EnumDeserializerByteVariant<'a> {
@@ -588,13 +1149,19 @@ impl<'a, 'de> de::EnumAccess<'de> for EnumDeserializerByteVariant<'a> {
     {
         // FIXME: With serde 1.0.122 the `.to_vec()` can be dropped!
         let variant = self.variant.to_vec().into_deserializer();
-        let visitor = VariantDeserializer { value: self.value };
+        let visitor = VariantDeserializer {
+            value: self.value,
+            borrow: self.borrow,
+            options: self.options.clone(),
+        };
         seed.deserialize(variant).map(|v| (v, visitor))
     }
 }
 
 struct VariantDeserializer {
     value: Option<Value>,
+    borrow: Borrow,
+    options: Rc<Options>,
 }
 
 impl<'de> de::VariantAccess<'de> for VariantDeserializer {
@@ -602,9 +1169,11 @@ impl<'de> de::VariantAccess<'de> for VariantDeserializer {
 
     fn unit_variant(self) -> Result<(), Error> {
         match self.value {
-            Some(value) => {
-                de::Deserialize::deserialize(&mut Deserializer::<'de>::from_value(value))
-            }
+            Some(value) => de::Deserialize::deserialize(&mut Deserializer::<'de>::with_borrow(
+                value,
+                self.borrow,
+                self.options,
+            )),
             None => Ok(()),
         }
     }
@@ -614,7 +1183,11 @@ impl<'de> de::VariantAccess<'de> for VariantDeserializer {
         T: de::DeserializeSeed<'de>,
     {
         match self.value {
-            Some(value) => seed.deserialize(&mut Deserializer::<'de>::from_value(value)),
+            Some(value) => seed.deserialize(&mut Deserializer::<'de>::with_borrow(
+                value,
+                self.borrow,
+                self.options.clone(),
+            )),
             None => Error::fail("expected newtype variant, found unit variant"),
         }
     }
@@ -628,7 +1201,11 @@ impl<'de> de::VariantAccess<'de> for VariantDeserializer {
                 if v.is_empty() {
                     visitor.visit_unit()
                 } else {
-                    visitor.visit_seq(ArrayAccess::new(&v))
+                    visitor.visit_seq(ArrayAccess::with_borrow(
+                        &v,
+                        self.borrow,
+                        self.options.clone(),
+                    ))
                 }
             }
             Some(_) => Error::fail("expected tuple variant"),
@@ -645,29 +1222,229 @@ impl<'de> de::VariantAccess<'de> for VariantDeserializer {
         V: de::Visitor<'de>,
     {
         match self.value {
-            Some(Value::Hash(v)) => visitor.visit_map(HashAccess::new(&v)),
+            Some(Value::Hash(v)) => visitor.visit_map(HashAccess::with_borrow(
+                &v,
+                self.borrow,
+                self.options.clone(),
+            )),
             _ => Error::fail("expected struct variant"),
         }
     }
 }
 
+/// Get the key of a hash entry as a `str` borrowed directly from the hash, if it is valid utf-8.
+///
+/// Keys without the utf-8 flag are only used if they are plain ASCII, since otherwise they're
+/// latin-1 and would need to be converted.
+///
+/// # Safety
+///
+/// Like with [`str_set_wrong_lifetime`], the hash entry must outlive the returned string.
+unsafe fn entry_str_key<'a>(entry: *mut ffi::HE) -> Option<&'a str> {
+    let mut len = 0;
+    let mut utf8 = false;
+    let ptr = unsafe { ffi::RSPL_HeKEY(entry, &mut len, &mut utf8) };
+    if ptr.is_null() {
+        return None;
+    }
+
+    let bytes = unsafe { std::slice::from_raw_parts(ptr as *const u8, len) };
+    if !utf8 && !bytes.is_ascii() {
+        return None;
+    }
+    std::str::from_utf8(bytes).ok()
+}
+
+/// Deserializer for hash keys.
+///
+/// String-like requests are served straight from the hash entry via
+/// [`BorrowedStrDeserializer`], so borrowing types like `&str` don't need a copy, and only
+/// types requiring an owned `String` will make one. Everything else goes through the regular
+/// [`Deserializer`] with a key `SV` like any other value.
+///
+/// With [`Borrow::Copy`], the key is passed to the visitor as a transient string instead.
+struct KeyDeserializer<'de> {
+    entry: *mut ffi::HE,
+    key: &'de str,
+    borrow: Borrow,
+    options: Rc<Options>,
+}
+
+impl<'de> KeyDeserializer<'de> {
+    fn key_sv_deserializer(&self) -> Deserializer<'de> {
+        let key = unsafe { Value::from_raw_ref(ffi::RSPL_hv_iterkeysv(self.entry)) };
+        Deserializer::with_borrow(key, self.borrow.temporary(), self.options.clone())
+    }
+}
+
+macro_rules! key_as_str {
+    ($($method:ident)*) => {$(
+        fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            if self.borrow != Borrow::Copy {
+                BorrowedStrDeserializer::new(self.key).$method(visitor)
+            } else {
+                StrDeserializer::new(self.key).$method(visitor)
+            }
+        }
+    )*};
+}
+
+macro_rules! key_as_sv {
+    ($($method:ident ($($arg:ident: $ty:ty),*))*) => {$(
+        fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            (&mut self.key_sv_deserializer()).$method($($arg,)* visitor)
+        }
+    )*};
+}
+
+impl<'de> de::Deserializer<'de> for KeyDeserializer<'de> {
+    type Error = Error;
+
+    key_as_str! {
+        deserialize_any deserialize_str deserialize_string deserialize_identifier
+        deserialize_ignored_any
+    }
+
+    key_as_sv! {
+        deserialize_bool() deserialize_i8() deserialize_i16() deserialize_i32() deserialize_i64()
+        deserialize_u8() deserialize_u16() deserialize_u32() deserialize_u64()
+        deserialize_f32() deserialize_f64() deserialize_char() deserialize_bytes()
+        deserialize_byte_buf() deserialize_option() deserialize_unit() deserialize_seq()
+        deserialize_map()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_newtype_struct(name: &'static str)
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+    }
+}
+
+/// Deserializer for [`from_blessed_value`], presenting an object as a single entry map from its
+/// class to its contents.
+struct BlessedDeserializer<'a> {
+    class: &'a str,
+    value: Option<Value>,
+}
+
+impl<'a, 'de> de::Deserializer<'de> for BlessedDeserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(EnumDeserializer {
+            variant: self.class,
+            value: self.value,
+            borrow: Borrow::Copy,
+            options: Rc::default(),
+        })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
+
+impl<'a, 'de> MapAccess<'de> for BlessedDeserializer<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.value.is_some() {
+            seed.deserialize(StrDeserializer::<Error>::new(self.class))
+                .map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match self.value.take() {
+            Some(value) => seed.deserialize(&mut Deserializer::with_borrow(
+                value,
+                Borrow::Copy,
+                Rc::default(),
+            )),
+            None => Error::fail("map access value requested after end"),
+        }
+    }
+}
+
 /// Serde `MapAccess` intermediate type.
+///
+/// This uses the hash's own iterator, and keeps a pointer to the current entry between
+/// deserializing its key and its value. Since deserializing a key or value may run perl code (for
+/// instance via magic), which could modify the hash and free that entry, the hash's key count and
+/// iterator position are checked before using the iterator again, and modifications fail the
+/// deserialization instead.
 pub struct HashAccess<'a> {
     hash: &'a hash::Hash,
     entry: *mut ffi::HE,
     finished: bool,
     at_value: bool,
+    borrow: Borrow,
+    options: Rc<Options>,
+    /// The key count when the iteration started.
+    keys: usize,
+    /// The iterator's last entry, only compared to, never dereferenced, as it may be gone.
+    last_entry: *mut ffi::HE,
 }
 
 impl<'a> HashAccess<'a> {
     pub fn new(value: &'a hash::Hash) -> Self {
+        Self::with_borrow(value, Borrow::Any, Rc::default())
+    }
+
+    fn with_borrow(value: &'a hash::Hash, borrow: Borrow, options: Rc<Options>) -> Self {
         let _ = value.shared_iter(); // reset iterator
         Self {
             hash: value,
             entry: std::ptr::null_mut(),
             finished: false,
             at_value: false,
+            borrow: borrow.within(value.sv()),
+            options,
+            keys: value.len(),
+            last_entry: std::ptr::null_mut(),
+        }
+    }
+
+    /// Make sure the hash was not modified since the iterator was last used.
+    fn check_unmodified(&self) -> Result<(), Error> {
+        let hv = self.hash.hv();
+        if unsafe {
+            ffi::RSPL_HvTOTALKEYS(hv) != self.keys || ffi::RSPL_HvEITER(hv) != self.last_entry
+        } {
+            return Error::fail("hash modified during deserialization");
         }
+        Ok(())
     }
 }
 
@@ -683,7 +1460,9 @@ impl<'de, 'a> MapAccess<'de> for HashAccess<'a> {
         }
 
         if self.entry.is_null() {
+            self.check_unmodified()?;
             self.entry = unsafe { ffi::RSPL_hv_iternext(self.hash.hv()) };
+            self.last_entry = self.entry;
             if self.entry.is_null() {
                 self.finished = true;
                 return Ok(None);
@@ -694,9 +1473,24 @@ impl<'de, 'a> MapAccess<'de> for HashAccess<'a> {
 
         self.at_value = true;
 
+        if let Some(key) = unsafe { entry_str_key(self.entry) } {
+            return seed
+                .deserialize(KeyDeserializer {
+                    entry: self.entry,
+                    key,
+                    borrow: self.borrow,
+                    options: self.options.clone(),
+                })
+                .map(Some);
+        }
+
         let key = unsafe { Value::from_raw_ref(ffi::RSPL_hv_iterkeysv(self.entry)) };
-        seed.deserialize(&mut Deserializer::from_value(key))
-            .map(Some)
+        seed.deserialize(&mut Deserializer::with_borrow(
+            key,
+            self.borrow.temporary(),
+            self.options.clone(),
+        ))
+        .map(Some)
     }
 
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
@@ -711,24 +1505,39 @@ impl<'de, 'a> MapAccess<'de> for HashAccess<'a> {
             return Error::fail("map access key skipped");
         }
 
+        self.check_unmodified()?;
         self.at_value = false;
 
         let value =
             unsafe { Value::from_raw_ref(ffi::RSPL_hv_iterval(self.hash.hv(), self.entry)) };
         self.entry = std::ptr::null_mut();
 
-        seed.deserialize(&mut Deserializer::from_value(value))
+        seed.deserialize(&mut Deserializer::with_borrow(
+            value,
+            self.borrow,
+            self.options.clone(),
+        ))
     }
 }
 
 /// Serde `SeqAccess` intermediate type.
 pub struct ArrayAccess<'a> {
     iter: array::Iter<'a>,
+    borrow: Borrow,
+    options: Rc<Options>,
 }
 
 impl<'a> ArrayAccess<'a> {
     pub fn new(value: &'a array::Array) -> Self {
-        Self { iter: value.iter() }
+        Self::with_borrow(value, Borrow::Any, Rc::default())
+    }
+
+    fn with_borrow(value: &'a array::Array, borrow: Borrow, options: Rc<Options>) -> Self {
+        Self {
+            iter: value.iter(),
+            borrow: borrow.within(value.sv()),
+            options,
+        }
     }
 }
 
@@ -741,11 +1550,81 @@ impl<'de, 'a> SeqAccess<'de> for ArrayAccess<'a> {
     {
         self.iter
             .next()
-            .map(move |value| seed.deserialize(&mut Deserializer::from_value(value)))
+            .map(|value| {
+                seed.deserialize(&mut Deserializer::with_borrow(
+                    value,
+                    self.borrow,
+                    self.options.clone(),
+                ))
+            })
             .transpose()
     }
 }
 
+/// Map access over a perl array of 2-element array references, which is how ordered maps are
+/// usually represented in perl.
+struct PairAccess<'a> {
+    iter: array::Iter<'a>,
+    borrow: Borrow,
+    options: Rc<Options>,
+    value: Option<(Value, Borrow)>,
+}
+
+impl<'a> PairAccess<'a> {
+    fn with_borrow(value: &'a array::Array, borrow: Borrow, options: Rc<Options>) -> Self {
+        Self {
+            iter: value.iter(),
+            borrow: borrow.within(value.sv()),
+            options,
+            value: None,
+        }
+    }
+}
+
+impl<'de, 'a> MapAccess<'de> for PairAccess<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let pair = match self.iter.next() {
+            Some(pair) => pair,
+            None => return Ok(None),
+        };
+
+        let (key, value, borrow) = match pair.dereference() {
+            Some(Value::Array(pair)) if pair.len() == 2 => {
+                (pair.get(0), pair.get(1), self.borrow.within(pair.sv()))
+            }
+            _ => return Error::fail("expected a hash or an array of key-value pairs"),
+        };
+        self.value = Some((value.unwrap_or_else(Value::new_undef), borrow));
+
+        let key = key.unwrap_or_else(Value::new_undef);
+        seed.deserialize(&mut Deserializer::with_borrow(
+            key,
+            borrow,
+            self.options.clone(),
+        ))
+        .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match self.value.take() {
+            Some((value, borrow)) => seed.deserialize(&mut Deserializer::with_borrow(
+                value,
+                borrow,
+                self.options.clone(),
+            )),
+            None => Error::fail("map access value requested before key"),
+        }
+    }
+}
+
 struct RawDeserializer<'a> {
     value: Option<&'a Value>,
 }
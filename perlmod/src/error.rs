@@ -105,6 +105,55 @@ pub fn get_errno() -> c_int {
     ERRNO.with(|v| v.get())
 }
 
+/// Error types which carry an `errno`-like value.
+///
+/// When an `#[export(errno)]` function returns an error implementing this trait, the value
+/// returned by [`errno`](HasErrno::errno()) is stored via [`set_errno`] before the error is raised
+/// in perl, so that perl code can inspect it via `$!` after catching the error. If `None` is
+/// returned, any value previously stored with [`set_errno`] is used instead.
+///
+/// Error types not implementing this trait behave as if `None` was returned.
+pub trait HasErrno {
+    fn errno(&self) -> Option<c_int>;
+}
+
+impl HasErrno for std::io::Error {
+    fn errno(&self) -> Option<c_int> {
+        self.raw_os_error()
+    }
+}
+
+/// This is part of the proc-macro API and is of little use to users of this crate directly.
+///
+/// Used to call [`HasErrno::errno`] on error types implementing it, and fall back to `None` for
+/// all other types.
+#[doc(hidden)]
+pub struct ErrnoProbe<'a, T>(pub &'a T);
+
+#[doc(hidden)]
+pub trait ErrnoProbeHasErrno {
+    fn perlmod_errno(&self) -> Option<c_int>;
+}
+
+impl<T: HasErrno> ErrnoProbeHasErrno for ErrnoProbe<'_, T> {
+    #[inline]
+    fn perlmod_errno(&self) -> Option<c_int> {
+        self.0.errno()
+    }
+}
+
+#[doc(hidden)]
+pub trait ErrnoProbeFallback {
+    fn perlmod_errno(&self) -> Option<c_int>;
+}
+
+impl<T> ErrnoProbeFallback for &ErrnoProbe<'_, T> {
+    #[inline]
+    fn perlmod_errno(&self) -> Option<c_int> {
+        None
+    }
+}
+
 /// This is part of the proc-macro API and is of little use to users of this crate directly.
 /// When manually implementing "xsubs" this can be used before returning as a shortcut to copying
 /// the perlmod errno value to libc.
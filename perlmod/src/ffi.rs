@@ -65,6 +65,23 @@ pub struct Interpreter {
     _ffi: usize,
 }
 
+/// Whether perlmod was built for a perl with `MULTIPLICITY`, in which case the perl API passes
+/// the interpreter as first parameter (the `pTHX` macro), see [`perl_fn!`](crate::perl_fn!) and
+/// [`vtbl_types`].
+///
+/// Code declaring its own xsubs or magic callbacks without [`perl_fn!`](crate::perl_fn!) can
+/// check this against its own configuration, ideally at compile time.
+pub const MULTIPLICITY: bool = cfg!(perlmod = "multiplicity");
+
+/// Check whether perlmod's C glue was compiled against perl headers with `MULTIPLICITY`.
+///
+/// Contrary to [`MULTIPLICITY`], this reflects the headers the glue code was actually built with,
+/// so a mismatch between the two means the crate was built with an inconsistent perl
+/// configuration, and any xsub or magic callback would crash due to the ABI mismatch.
+pub fn has_multiplicity() -> bool {
+    unsafe { RSPL_has_multiplicity() }
+}
+
 /// Build perl-compatible functions and fn types (`pTHX` macro equivalent).
 ///
 /// Takes an `extern "C" fn` (with or without body) and potentially inserts the a
@@ -302,6 +319,9 @@ extern "C" {
     pub fn RSPL_stack_get(offset: usize) -> *mut SV;
 
     pub fn RSPL_croak_sv(sv: *mut SV) -> !;
+    /// Emit a warning, catching errors from a `$SIG{__WARN__}` handler like
+    /// [`RSPL_call_sv_list`].
+    pub fn RSPL_warn_sv(sv: *mut SV, carp: bool, error: *mut *mut SV) -> *mut AV;
 
     pub fn RSPL_newXS_flags(
         name: *const i8,
@@ -315,7 +335,13 @@ extern "C" {
     pub fn RSPL_SvIV(sv: *mut SV) -> isize;
     pub fn RSPL_SvPVutf8(sv: *mut SV, len: *mut libc::size_t) -> *const libc::c_char;
     pub fn RSPL_SvPV(sv: *mut SV, len: *mut libc::size_t) -> *const libc::c_char;
+    /// `SvPV_force` for plain, non-UTF-8 scalars with a reference count of 1, otherwise `NULL`.
+    pub fn RSPL_SvPV_force_unique(sv: *mut SV, len: *mut libc::size_t) -> *mut libc::c_char;
+    /// `PERL_HASH` of the string form, downgrading UTF-8 strings where possible like hash keys.
+    pub fn RSPL_perl_hash(sv: *mut SV) -> u32;
     pub fn RSPL_SvUTF8(sv: *mut SV) -> bool;
+    pub fn RSPL_SvUTF8_on(sv: *mut SV);
+    pub fn RSPL_SvUTF8_off(sv: *mut SV);
     /// This calls `sv_utf8_downgrade` first to avoid croaking, instead returns `NULL` on error.
     pub fn RSPL_SvPVbyte(sv: *mut SV, len: *mut libc::size_t) -> *const libc::c_char;
     pub fn RSPL_sv_2mortal(sv: *mut SV) -> *mut SV;
@@ -327,6 +353,7 @@ extern "C" {
     pub fn RSPL_stack_shrink_to(count: usize);
     pub fn RSPL_stack_sp() -> *mut *mut SV;
     pub fn RSPL_newRV_inc(sv: *mut SV) -> *mut SV;
+    pub fn RSPL_newRV_noinc(sv: *mut SV) -> *mut SV;
     pub fn RSPL_newSViv(v: isize) -> *mut SV;
     pub fn RSPL_newSVuv(v: usize) -> *mut SV;
     pub fn RSPL_newSVnv(v: f64) -> *mut SV;
@@ -334,6 +361,7 @@ extern "C" {
     pub fn RSPL_newSVpvn_utf8(v: *const libc::c_char, len: libc::size_t) -> *mut SV;
     pub fn RSPL_SvREFCNT_inc(sv: *mut SV) -> *mut SV;
     pub fn RSPL_SvREFCNT_dec(sv: *mut SV);
+    pub fn RSPL_SvREFCNT(sv: *mut SV) -> u32;
     pub fn RSPL_is_reference(sv: *mut SV) -> bool;
     pub fn RSPL_dereference(sv: *mut SV) -> *mut SV;
     pub fn RSPL_is_array(sv: *mut SV) -> bool;
@@ -341,8 +369,40 @@ extern "C" {
     pub fn RSPL_type_flags(sv: *mut SV) -> u32;
     pub fn RSPL_svtype(sv: *mut SV) -> u32;
     pub fn RSPL_SvOK(sv: *mut SV) -> bool;
+    pub fn RSPL_SvIOK(sv: *mut SV) -> bool;
+    pub fn RSPL_SvNOK(sv: *mut SV) -> bool;
+    pub fn RSPL_SvIsUV(sv: *mut SV) -> bool;
+    pub fn RSPL_SvUV(sv: *mut SV) -> usize;
     pub fn RSPL_SvANY(sv: *mut SV) -> bool;
     pub fn RSPL_SvTRUE(sv: *mut SV) -> bool;
+    pub fn RSPL_SvOBJECT(sv: *mut SV) -> bool;
+    pub fn RSPL_sv_isobject(sv: *mut SV) -> bool;
+    pub fn RSPL_SvMAGICAL(sv: *mut SV) -> bool;
+    /// Returns a new plain copy of the scalar `sv`, after processing get-magic.
+    pub fn RSPL_newSVsv(sv: *mut SV) -> *mut SV;
+    pub fn RSPL_looks_like_number(sv: *mut SV) -> bool;
+    pub fn RSPL_has_multiplicity() -> bool;
+    /// A new scalar containing only the numeric form of `sv`.
+    pub fn RSPL_sv_to_number(sv: *mut SV) -> *mut SV;
+    /// A new scalar containing only the string form of `sv`.
+    pub fn RSPL_sv_to_string(sv: *mut SV) -> *mut SV;
+    /// Compare two scalars as strings like perl's `cmp` operator.
+    pub fn RSPL_sv_cmp(a: *mut SV, b: *mut SV) -> libc::c_int;
+    /// Compare two scalars as numbers like perl's `==` operator, without overloading.
+    pub fn RSPL_sv_num_eq(a: *mut SV, b: *mut SV) -> bool;
+    /// Compare two scalars as strings like perl's `eq` operator, without overloading.
+    pub fn RSPL_sv_str_eq(a: *mut SV, b: *mut SV) -> bool;
+    pub fn RSPL_SvAMAGIC(sv: *mut SV) -> bool;
+    /// Check whether `sv` is a perl file handle, or a reference to one.
+    pub fn RSPL_sv_is_handle(sv: *mut SV) -> bool;
+    /// Reads up to `len` bytes from a perl file handle via `PerlIO_read`. Returns the number of
+    /// bytes read, -1 if `sv` is not a handle open for reading, or -2 on read errors.
+    pub fn RSPL_sv_read(sv: *mut SV, buffer: *mut libc::c_char, len: usize) -> libc::ssize_t;
+    /// Returns the file descriptor of a perl file handle or -1.
+    pub fn RSPL_sv_fileno(sv: *mut SV) -> libc::c_int;
+    /// Writes all bytes to a perl file handle via `PerlIO_write`. Returns 0 on success, -1 if
+    /// `sv` is not a handle open for writing, or -2 on write errors.
+    pub fn RSPL_sv_write_all(sv: *mut SV, data: *const libc::c_char, len: usize) -> libc::c_int;
 
     pub fn RSPL_is_defined(sv: *mut SV) -> bool;
 
@@ -350,11 +410,27 @@ extern "C" {
     pub fn RSPL_av_extend(av: *mut AV, len: libc::ssize_t);
     pub fn RSPL_av_push(av: *mut AV, sv: *mut SV);
     pub fn RSPL_av_pop(av: *mut AV) -> *mut SV;
+    pub fn RSPL_av_clear(av: *mut AV);
     pub fn RSPL_av_len(av: *mut AV) -> usize;
     pub fn RSPL_av_fetch(av: *mut AV, index: libc::ssize_t, lval: i32) -> *mut *mut SV;
+    /// Store `sv` at `index`, always taking over the reference to it.
+    pub fn RSPL_av_store(av: *mut AV, index: libc::ssize_t, sv: *mut SV);
 
     pub fn RSPL_newHV() -> *mut HV;
+    /// A new hash with enough buckets for `capacity` keys. (perlxs `hv_ksplit`)
+    pub fn RSPL_newHV_capacity(capacity: usize) -> *mut HV;
     pub fn RSPL_HvTOTALKEYS(hv: *mut HV) -> usize;
+    /// The entry a hash's iterator is currently at.
+    pub fn RSPL_HvEITER(hv: *mut HV) -> *mut HE;
+    pub fn RSPL_hv_iter_save(
+        hv: *mut HV,
+        eiter: *mut *mut HE,
+        riter: *mut i32,
+        lazydel: *mut bool,
+    ) -> bool;
+    pub fn RSPL_hv_iter_restore(hv: *mut HV, eiter: *mut HE, riter: i32, lazydel: bool);
+    pub fn RSPL_hv_is_empty(hv: *mut HV) -> bool;
+    pub fn RSPL_hv_clear(hv: *mut HV);
     pub fn RSPL_hv_fetch(
         hv: *mut HV,
         key: *const libc::c_char,
@@ -363,7 +439,22 @@ extern "C" {
     ) -> *mut *mut SV;
     /// Always consumes ownership of `value`.
     pub fn RSPL_hv_store(hv: *mut HV, key: *const libc::c_char, klen: i32, value: *mut SV) -> bool;
+    /// Always consumes ownership of `value`, returns whether it was stored.
+    pub fn RSPL_hv_store_if_absent(
+        hv: *mut HV,
+        key: *const libc::c_char,
+        klen: i32,
+        value: *mut SV,
+    ) -> bool;
     pub fn RSPL_hv_store_ent(hv: *mut HV, key: *mut SV, value: *mut SV) -> bool;
+    /// Move a value to a different key, returns whether the source key existed.
+    pub fn RSPL_hv_rename_key(
+        hv: *mut HV,
+        from: *const libc::c_char,
+        flen: i32,
+        to: *const libc::c_char,
+        tlen: i32,
+    ) -> bool;
     pub fn RSPL_hv_iterinit(hv: *mut HV);
     pub fn RSPL_hv_iternextsv(
         hv: *mut HV,
@@ -372,15 +463,46 @@ extern "C" {
     ) -> *mut SV;
     pub fn RSPL_hv_iternext(hv: *mut HV) -> *mut HE;
     pub fn RSPL_hv_iterkeysv(he: *mut HE) -> *mut SV;
+    pub fn RSPL_HeKEY(he: *mut HE, out_len: *mut usize, out_utf8: *mut bool)
+        -> *const libc::c_char;
     pub fn RSPL_hv_iterval(hv: *mut HV, he: *mut HE) -> *mut SV;
 
     pub fn RSPL_gv_stashsv(name: *const SV, flags: i32) -> *mut HV;
+    pub fn RSPL_GV_ADD() -> i32;
     pub fn RSPL_sv_bless(sv: *mut SV, stash: *mut HV) -> *mut SV;
 
     pub fn RSPL_ENTER();
     pub fn RSPL_SAVETMPS();
     pub fn RSPL_FREETMPS();
     pub fn RSPL_LEAVE();
+    pub fn RSPL_scopestack_ix() -> i32;
+    /// Clear `*flag` once perl leaves the current scope.
+    pub fn RSPL_SAVE_clear_flag(flag: *mut bool);
+
+    pub fn RSPL_save_scalar(name: *const libc::c_char, len: libc::size_t) -> *mut SV;
+    pub fn RSPL_sv_setsv_mg(dst: *mut SV, src: *mut SV);
+    /// Invoke an overloaded operator via `amagic_call`, see [`ScalarRef::overload_op`].
+    ///
+    /// `op` is a [`scalar::Overload`](crate::scalar::Overload) and `right` may be null. Returns
+    /// the results (empty if the operator is not overloaded) like [`RSPL_call_sv_list`], or null
+    /// with the error stored in `error` if the overload method died.
+    ///
+    /// [`ScalarRef::overload_op`]: crate::ScalarRef::overload_op()
+    pub fn RSPL_amagic_call(
+        left: *mut SV,
+        right: *mut SV,
+        op: libc::c_int,
+        error: *mut *mut SV,
+    ) -> *mut AV;
+    /// Check whether `sv` `DOES` the (UTF-8) role `role`, treating errors as false.
+    pub fn RSPL_sv_does(sv: *mut SV, role: *const libc::c_char, len: usize) -> bool;
+    pub fn RSPL_sv_setiv_mg(sv: *mut SV, v: isize);
+    pub fn RSPL_sv_setuv_mg(sv: *mut SV, v: usize);
+    pub fn RSPL_sv_setnv_mg(sv: *mut SV, v: f64);
+    /// Set a scalar to a string, optionally marking it as UTF-8, and trigger its set magic.
+    pub fn RSPL_sv_setpvn_mg(sv: *mut SV, v: *const libc::c_char, len: libc::size_t, utf8: bool);
+    /// Set a scalar to `undef` in place, keeping the SV itself.
+    pub fn RSPL_sv_set_undef(sv: *mut SV);
 
     pub fn RSPL_sv_reftype(sv: *const SV, ob: libc::c_int) -> *const libc::c_char;
 
@@ -390,7 +512,8 @@ extern "C" {
     pub fn RSPL_vivify_defelem(sv: *mut SV);
 
     //pub fn RSPL_SvFLAGS(sv: *mut SV) -> u32;
-    pub fn RSPL_SvGETMAGIC(sv: *mut SV) -> bool;
+    pub fn RSPL_SvGETMAGIC(sv: *mut SV);
+    pub fn RSPL_SvSETMAGIC(sv: *mut SV);
 
     pub fn RSPL_sv_magicext(
         sv: *mut SV,
@@ -411,6 +534,20 @@ extern "C" {
     pub fn RSPL_vtbl_substr() -> *const MGVTBL;
     pub fn RSPL_substr(orig: *mut SV, off: usize, len: usize) -> *mut SV;
 
+    /// Calls `sub` in list context with `G_EVAL`. Returns `NULL` on error and stores an owned
+    /// copy of `$@` in `error`.
+    pub fn RSPL_call_sv_list(
+        sub: *mut SV,
+        args: *const *mut SV,
+        nargs: usize,
+        error: *mut *mut SV,
+    ) -> *mut AV;
+
+    pub fn RSPL_gimme() -> u32;
+    /// Evaluates `code` with `G_EVAL` in the context passed as in [`RSPL_gimme`]. Returns `NULL`
+    /// on error and stores an owned copy of `$@` in `error`.
+    pub fn RSPL_eval_sv(code: *mut SV, gimme: u32, error: *mut *mut SV) -> *mut AV;
+
     pub fn RSPL_defstash() -> *mut HV;
 
     pub fn RSPL_set_use_safe_putenv(on: libc::c_int);
@@ -492,6 +629,42 @@ pub unsafe fn stack_push_raw(value: *mut SV) {
     }
 }
 
+/// Push the key/value pairs of a hash reference to the stack as a flat list, like perl does when
+/// using a hash in list context. Anything else is pushed as is.
+///
+/// # Safety
+///
+/// Same as [`stack_push_raw`].
+pub unsafe fn stack_push_flattened(value: *mut SV) {
+    let hash = match unsafe { crate::Value::from_raw_ref(value) }.dereference() {
+        Some(crate::Value::Hash(hash)) => hash,
+        _ => return unsafe { stack_push_raw(value) },
+    };
+
+    for (key, value) in hash.iter_sv() {
+        stack_push(key.into_mortal());
+        stack_push(value.into_mortal());
+    }
+}
+
+/// Push the elements of an array (not an array reference) to the stack. This is used for the
+/// return values of exported functions, where tuple structs are returned as a list. Anything else
+/// is pushed as is.
+///
+/// # Safety
+///
+/// Same as [`stack_push_raw`].
+pub unsafe fn stack_push_list(value: *mut SV) {
+    match unsafe { crate::Value::from_raw_ref(value) } {
+        crate::Value::Array(array) => {
+            for value in array.iter() {
+                stack_push(value.into_mortal());
+            }
+        }
+        _ => unsafe { stack_push_raw(value) },
+    }
+}
+
 pub fn stack_push(value: crate::Mortal) {
     unsafe {
         stack_push_raw(value.into_raw());
@@ -559,25 +732,370 @@ pub unsafe fn croak(sv: *mut SV) -> ! {
     }
 }
 
+/// Emit a perl warning. (perlxs `warn_sv`).
+///
+/// Unless the message ends with a newline, perl appends the location of the currently executing
+/// statement, which for an exported function is the line calling it.
+///
+/// The warning is emitted with `G_EVAL` in effect, so if a `$SIG{__WARN__}` handler dies, its
+/// error is returned instead of unwinding through the calling rust code.
+pub fn warn(message: &str) -> Result<(), crate::Error> {
+    warn_sv(message, false)
+}
+
+/// Emit a perl warning reporting the location of the caller of the current perl sub, similar to
+/// `Carp::carp`.
+///
+/// When called directly from the top level perl code this behaves like [`warn`], including the
+/// handling of a dying `$SIG{__WARN__}` handler.
+pub fn carp(message: &str) -> Result<(), crate::Error> {
+    warn_sv(message, true)
+}
+
+fn warn_sv(message: &str, carp: bool) -> Result<(), crate::Error> {
+    let message = crate::Value::new_string(message);
+    let mut error: *mut SV = std::ptr::null_mut();
+    let result = unsafe { RSPL_warn_sv(message.sv(), carp, &mut error) };
+    crate::value::call_result(result, error).map(drop)
+}
+
+/// The context a perl sub is called in. (perlxs `G_VOID`, `G_SCALAR`, `G_LIST`).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Gimme {
+    Void,
+    Scalar,
+    List,
+}
+
+impl Gimme {
+    /// Run `scalar` or `list` depending on the context, treating void context like scalar
+    /// context.
+    ///
+    /// This can be used anywhere in an exported function, typically with the context from
+    /// [`gimme`], to avoid computing results perl is not going to use. The closures may return a
+    /// `Result` as well.
+    ///
+    /// ```
+    /// #[perlmod::package(name = "RSPM::Doc::Gimme", file = "/dev/null")]
+    /// mod export {
+    ///     use perlmod::ffi::gimme;
+    ///
+    ///     /// Return a reference to the names in list context, or only their count otherwise.
+    ///     #[export(raw_return)]
+    ///     fn names() -> Result<perlmod::Value, perlmod::Error> {
+    ///         gimme().map(
+    ///             || Ok(perlmod::Value::new_uint(2)),
+    ///             || perlmod::to_value(&["a", "b"]),
+    ///         )
+    ///     }
+    /// }
+    /// ```
+    pub fn map<T, S, L>(self, scalar: S, list: L) -> T
+    where
+        S: FnOnce() -> T,
+        L: FnOnce() -> T,
+    {
+        match self {
+            Gimme::Void | Gimme::Scalar => scalar(),
+            Gimme::List => list(),
+        }
+    }
+
+    /// Like [`map`](Gimme::map()), but run `void` in void context, where perl discards whatever
+    /// is returned.
+    pub fn void_map<T, S, L, V>(self, scalar: S, list: L, void: V) -> T
+    where
+        S: FnOnce() -> T,
+        L: FnOnce() -> T,
+        V: FnOnce() -> T,
+    {
+        match self {
+            Gimme::Void => void(),
+            Gimme::Scalar => scalar(),
+            Gimme::List => list(),
+        }
+    }
+}
+
+/// The context an exported function was called in.
+///
+/// An [`#[export]`](macro@crate::export) function receives this via a parameter marked with
+/// `#[context]`, which is filled in when the function is entered and does not take a value from
+/// perl:
+///
+/// ```
+/// #[perlmod::package(name = "RSPM::Doc::Context", file = "/dev/null")]
+/// mod export {
+///     use perlmod::Context;
+///
+///     /// Look up a name, unless the result would be discarded anyway.
+///     #[export]
+///     fn lookup(#[context] context: Context, name: &str) -> Option<String> {
+///         if context.want_void() {
+///             return None;
+///         }
+///         Some(name.to_uppercase())
+///     }
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Context {
+    gimme: Gimme,
+}
+
+impl Context {
+    /// Get the context the currently running sub was called in, see [`gimme`].
+    pub fn current() -> Self {
+        Self { gimme: gimme() }
+    }
+
+    /// The context as a [`Gimme`] value, for instance to use [`Gimme::map`].
+    pub fn gimme(self) -> Gimme {
+        self.gimme
+    }
+
+    /// Check whether the caller expects a list, like perl's `wantarray` being true.
+    pub fn want_list(self) -> bool {
+        self.gimme == Gimme::List
+    }
+
+    /// Check whether the caller expects a single value, like perl's `wantarray` being false.
+    pub fn want_scalar(self) -> bool {
+        self.gimme == Gimme::Scalar
+    }
+
+    /// Check whether the return value is discarded, like perl's `wantarray` being `undef`.
+    pub fn want_void(self) -> bool {
+        self.gimme == Gimme::Void
+    }
+}
+
+impl From<Context> for Gimme {
+    fn from(context: Context) -> Gimme {
+        context.gimme
+    }
+}
+
+/// Evaluate a string of perl code, like perl's `eval EXPR`, in the given context. (perlxs
+/// `eval_sv`).
+///
+/// Compilation errors and `die`s are caught and returned as an [`Error`](crate::Error) containing
+/// the stringified `$@`. In scalar context the result contains a single value, in void context
+/// it is empty.
+///
+/// ```no_run
+/// use perlmod::ffi::Gimme;
+///
+/// # fn code() -> Result<(), perlmod::Error> {
+/// let sum = perlmod::eval("1 + 2", Gimme::Scalar)?;
+/// assert_eq!(sum[0].iv(), 3);
+/// # Ok(())
+/// # }
+/// ```
+pub fn eval(code: &str, ctx: Gimme) -> Result<Vec<crate::Value>, crate::Error> {
+    let gimme = match ctx {
+        Gimme::Void => 0,
+        Gimme::Scalar => 1,
+        Gimme::List => 2,
+    };
+    let code = crate::Scalar::new_string(code);
+    let mut error: *mut SV = std::ptr::null_mut();
+    let result = unsafe { RSPL_eval_sv(code.sv(), gimme, &mut error) };
+    crate::value::call_result(result, error)
+}
+
+/// Get the context the currently running sub was called in. (perlxs `GIMME_V`).
+pub fn gimme() -> Gimme {
+    match unsafe { RSPL_gimme() } {
+        0 => Gimme::Void,
+        1 => Gimme::Scalar,
+        _ => Gimme::List,
+    }
+}
+
 /// Create a pseudo-block for mortals & temps to be freed after it.
 /// This calls `ENTER; SAVETMPS;` before and `FREETMPS; LEAVE;` after the provided closure.
+///
+/// Perl only frees mortal values at the end of the statement calling an exported function. A
+/// long-running function which creates many temporaries (eg. by calling back into perl, or via
+/// [`Value::into_mortal`](crate::Value::into_mortal())) can therefore keep a lot of memory alive
+/// until it returns. Wrapping the body of an inner loop with this makes sure each iteration's
+/// temporaries are released, and any `DESTROY` methods run, right away.
+///
+/// Note that the returned value must not be a mortal created within `func`, as it will already
+/// have been freed.
+///
+/// See [`enter_scope`] for a guard based version.
+///
+/// ```
+/// #[perlmod::package(name = "RSPM::Doc::PseudoBlock", file = "/dev/null")]
+/// mod export {
+///     use perlmod::{Callback, Error};
+///
+///     #[export]
+///     fn notify_all(#[coderef] callback: Callback, count: usize) -> Result<(), Error> {
+///         for i in 0..count {
+///             perlmod::ffi::pseudo_block(|| callback.call_with(&i))?;
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
 pub fn pseudo_block<F, R>(func: F) -> R
 where
     F: FnOnce() -> R,
 {
-    unsafe {
-        RSPL_ENTER();
-        RSPL_SAVETMPS();
+    let _scope = enter_scope();
+    func()
+}
+
+/// A scope entered via `ENTER`, which remembers its depth on perl's scope stack and notices when
+/// perl leaves it on its own, so guards only ever leave their own scope.
+///
+/// This holds raw pointers and is therefore neither `Send` nor `Sync`, the guards using it must
+/// stay on the thread of the interpreter they were created for.
+pub(crate) struct Scope {
+    depth: i32,
+    // Cleared by perl when leaving the scope. This is only freed once the scope is known to be
+    // left, and leaked otherwise.
+    active: *mut std::cell::Cell<bool>,
+}
+
+impl Scope {
+    /// `ENTER` a new scope.
+    pub(crate) fn enter() -> Self {
+        let active = Box::into_raw(Box::new(std::cell::Cell::new(true)));
+        unsafe {
+            RSPL_ENTER();
+            RSPL_SAVE_clear_flag((*active).as_ptr());
+            Self {
+                depth: RSPL_scopestack_ix(),
+                active,
+            }
+        }
     }
 
-    let res = func();
+    /// Leave the scope by calling `leave`, which must end with `LEAVE`, unless perl already left
+    /// it, eg. because the guard outlived the xsub it was created in.
+    ///
+    /// If the scope is still active but not the innermost one, it cannot be left without also
+    /// leaving scopes which belong to someone else. This is called from `Drop` implementations
+    /// and must not unwind into perl, so instead of panicking this emits a perl warning and leaves
+    /// the scope to perl, which ends it along with the enclosing scope, at the latest when the
+    /// exported function returns.
+    pub(crate) fn leave_with(&self, leave: impl FnOnce()) {
+        if unsafe { (*self.active).get() } {
+            if unsafe { RSPL_scopestack_ix() } != self.depth {
+                // perl still clears the flag once it leaves the scope, so it must stay allocated
+                let _ = warn("perl scope guard dropped out of order, leaving the scope to perl");
+                return;
+            }
+            leave();
+        }
+        drop(unsafe { Box::from_raw(self.active) });
+    }
+}
+
+/// Guard for a pseudo-block created via [`enter_scope`], ending it when dropped.
+///
+/// Dropping a guard while a scope entered after it is still active emits a perl warning and
+/// leaves the scope open, so perl ends it along with the enclosing scope instead. If perl
+/// has already left the scope, eg. because the guard was kept around after returning to perl,
+/// dropping it does nothing.
+#[must_use = "the scope ends as soon as the guard is dropped"]
+pub struct ScopeGuard {
+    scope: Scope,
+}
+
+/// Create a pseudo-block for mortals & temps, which lasts until the returned guard is dropped.
+/// This calls `ENTER; SAVETMPS;` right away and `FREETMPS; LEAVE;` when the guard is dropped.
+///
+/// This is the same as [`pseudo_block`], but can bracket code which does not fit into a closure
+/// well, for instance because it returns early via `?`.
+///
+/// Mortal values created while the guard is alive are freed when it is dropped, while mortals
+/// created before are left alone. A [`Value`](crate::Value) holds its own reference, so values
+/// which are still needed after the scope ends simply must not be turned into mortals within it.
+/// Guards of nested scopes must be dropped in the reverse order of their creation, which is what
+/// happens to guards kept in local variables anyway. Use [`pseudo_block`] to have this ordering
+/// enforced by the closure's lifetime instead.
+///
+/// ```
+/// # fn code(callback: &perlmod::Callback) -> Result<(), perlmod::Error> {
+/// for i in 0..100 {
+///     let _scope = perlmod::enter_scope();
+///     // the results of each call are freed at the end of the iteration
+///     callback.call_with(&i)?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn enter_scope() -> ScopeGuard {
+    let scope = Scope::enter();
+    unsafe { RSPL_SAVETMPS() };
+    ScopeGuard { scope }
+}
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        self.scope.leave_with(|| unsafe {
+            RSPL_FREETMPS();
+            RSPL_LEAVE();
+        });
+    }
+}
 
+/// Make sure a package (stash) with the given name exists, creating it if necessary. (perlxs
+/// `gv_stashsv` with `GV_ADD`).
+///
+/// Without this, [`Value::bless`](crate::Value::bless()) fails for packages which have not been
+/// declared on the perl side, such as classes implemented purely in rust.
+pub fn ensure_package(name: &str) {
+    let name = crate::Scalar::new_string(name);
     unsafe {
-        RSPL_FREETMPS();
-        RSPL_LEAVE();
+        RSPL_gv_stashsv(name.sv(), RSPL_GV_ADD());
     }
+}
+
+/// Install a raw XSUB as `package::name` at runtime and return a code reference to it. (perlxs
+/// `newXS_flags`).
+///
+/// The package is created if it does not exist yet, and an existing sub of the same name is
+/// replaced. To install a rust closure instead, see [`Value::new_closure`](crate::Value::new_closure()).
+///
+/// # Panics
+///
+/// Panics if the package name, sub name or prototype contain a null byte.
+///
+/// # Safety
+///
+/// As with [`Value::new_xsub`](crate::Value::new_xsub()), it is up to the user to ensure that
+/// `xsub` is a valid perl XSUB, as perl WILL execute it and expect it to behave accordingly.
+pub unsafe fn install_xsub(
+    package: &str,
+    name: &str,
+    xsub: perl_fn!(extern "C" fn(*mut CV)),
+    prototype: Option<&str>,
+) -> crate::Value {
+    let sub_name = std::ffi::CString::new(format!("{package}::{name}"))
+        .expect("sub name must not contain null bytes");
+    let prototype = prototype
+        .map(|proto| std::ffi::CString::new(proto).expect("prototype must not contain null bytes"));
+
+    let cv = unsafe {
+        RSPL_newXS_flags(
+            sub_name.as_ptr(),
+            xsub as _,
+            concat!(file!(), "\0").as_ptr() as *const i8,
+            prototype
+                .as_ref()
+                .map(|proto| proto.as_ptr())
+                .unwrap_or(std::ptr::null()),
+            0,
+        )
+    };
 
-    res
+    unsafe { crate::Value::new_ref(&crate::Value::from_raw_ref(cv as *mut SV)) }
 }
 
 /// Tell perl to use a "safe" `putenv` call instead of manually manipulating the `environ`
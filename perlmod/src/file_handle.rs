@@ -0,0 +1,129 @@
+//! A perl file handle which can be used from rust.
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ffi, Error, RawValue, Value};
+
+const NOT_A_HANDLE: &str = "expected a file handle";
+
+fn is_handle(value: &Value) -> bool {
+    unsafe { ffi::RSPL_sv_is_handle(value.sv()) }
+}
+
+/// A perl file handle, such as a glob reference from `open`, a glob like `*STDIN`, or an IO
+/// handle like `*STDIN{IO}`.
+///
+/// This can be taken via a `#[try_from_ref]` parameter of an [`#[export]`](macro@crate::export)
+/// function, or deserialized as part of a larger structure, where it is kept as an opaque handle
+/// to the original perl value. Reading and writing goes through perl's `PerlIO` layers, so it
+/// sees the same buffers and layers perl code using the handle does.
+///
+/// ```
+/// #[perlmod::package(name = "RSPM::Doc::FileHandle", file = "/dev/null")]
+/// mod export {
+///     use std::io::Read;
+///
+///     use perlmod::{Error, FileHandle};
+///
+///     #[export]
+///     fn slurp(#[try_from_ref] fh: FileHandle) -> Result<String, Error> {
+///         let mut data = String::new();
+///         (&fh).read_to_string(&mut data)
+///             .map_err(|err| Error::new_owned(err.to_string()))?;
+///         Ok(data)
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct FileHandle(Value);
+
+impl FileHandle {
+    /// Wrap a value, which must be a file handle or a reference to one.
+    pub fn new(value: Value) -> Result<Self, Error> {
+        if is_handle(&value) {
+            Ok(Self(value))
+        } else {
+            Err(Error::new(NOT_A_HANDLE))
+        }
+    }
+
+    /// Get the file descriptor of the handle, or `None` if it is not open.
+    ///
+    /// See [`Value::as_raw_fd`] for the caveats of using the descriptor directly.
+    pub fn as_raw_fd(&self) -> Option<std::os::unix::io::RawFd> {
+        self.0.as_raw_fd()
+    }
+
+    /// Read up to `buffer.len()` bytes from the handle, like perl's `read`. Returns the number of
+    /// bytes read, which is 0 at the end of the file.
+    pub fn read(&self, buffer: &mut [u8]) -> Result<usize, Error> {
+        match unsafe { ffi::RSPL_sv_read(self.0.sv(), buffer.as_mut_ptr().cast(), buffer.len()) } {
+            -1 => Err(Error::new("not a file handle open for reading")),
+            got if got < 0 => Err(Error::new("failed to read from file handle")),
+            got => Ok(got as usize),
+        }
+    }
+
+    /// Write all of `data` to the handle, see [`Value::write_all`].
+    pub fn write_all(&self, data: &[u8]) -> Result<(), Error> {
+        self.0.write_all(data)
+    }
+
+    /// Get the handle as a [`Value`].
+    pub fn into_inner(self) -> Value {
+        self.0
+    }
+}
+
+impl std::io::Read for &FileHandle {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        FileHandle::read(self, buffer).map_err(std::io::Error::other)
+    }
+}
+
+impl TryFrom<Value> for FileHandle {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Error> {
+        Self::new(value)
+    }
+}
+
+impl TryFrom<&Value> for FileHandle {
+    type Error = Error;
+
+    fn try_from(value: &Value) -> Result<Self, Error> {
+        Self::new(value.clone())
+    }
+}
+
+impl fmt::Debug for FileHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("FileHandle").field(&self.0).finish()
+    }
+}
+
+impl<'de> Deserialize<'de> for FileHandle {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = RawValue::deserialize(deserializer)?.into_inner();
+        if !is_handle(&value) {
+            return Err(serde::de::Error::custom(NOT_A_HANDLE));
+        }
+        Ok(Self(value))
+    }
+}
+
+impl Serialize for FileHandle {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
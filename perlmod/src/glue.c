@@ -65,10 +65,58 @@ extern const char* RSPL_SvPV(SV *sv, size_t *out_len) {
     return out;
 }
 
+/// `SvPV_force` for plain scalars which are not referenced elsewhere, otherwise returns `NULL`.
+extern char* RSPL_SvPV_force_unique(SV *sv, size_t *out_len) {
+    size_t length;
+    char *out;
+
+    if (SvREFCNT(sv) != 1
+        || SvREADONLY(sv)
+        || SvMAGICAL(sv)
+        || SvROK(sv)
+        || SvUTF8(sv)
+        || SvTYPE(sv) > SVt_PVMG)
+    {
+        return NULL;
+    }
+
+    out = SvPV_force(sv, length);
+    *out_len = length;
+    return out;
+}
+
+extern U32 RSPL_perl_hash(SV *sv) {
+    STRLEN length;
+    const char *str = SvPV(sv, length);
+    U32 hash;
+
+    // like hash keys, use the downgraded form of UTF-8 strings where possible
+    if (SvUTF8(sv)) {
+        bool is_utf8 = TRUE;
+        const char *bytes = (const char*)bytes_from_utf8((const U8*)str, &length, &is_utf8);
+        PERL_HASH(hash, bytes, length);
+        if (bytes != str) {
+            Safefree(bytes);
+        }
+        return hash;
+    }
+
+    PERL_HASH(hash, str, length);
+    return hash;
+}
+
 extern bool RSPL_SvUTF8(SV *sv) {
     return SvUTF8(sv) != 0;
 }
 
+extern void RSPL_SvUTF8_on(SV *sv) {
+    SvUTF8_on(sv);
+}
+
+extern void RSPL_SvUTF8_off(SV *sv) {
+    SvUTF8_off(sv);
+}
+
 /// SvPVbyte with a downgrade check to avoid croaking!
 extern const char* RSPL_SvPVbyte(SV *sv, size_t *out_len) {
     size_t length;
@@ -125,6 +173,10 @@ extern SV* RSPL_newRV_inc(SV *rv) {
     return newRV_inc(rv);
 }
 
+extern SV* RSPL_newRV_noinc(SV *rv) {
+    return newRV_noinc(rv);
+}
+
 extern SV* RSPL_newSViv(isize v) {
     return newSViv(v);
 }
@@ -153,6 +205,10 @@ extern void RSPL_SvREFCNT_dec(SV *sv) {
     return SvREFCNT_dec(sv);
 }
 
+extern uint32_t RSPL_SvREFCNT(SV *sv) {
+    return SvREFCNT(sv);
+}
+
 extern bool RSPL_is_scalar(SV *sv) {
     return SvTYPE(sv) < SVt_PVAV;
 }
@@ -161,6 +217,22 @@ extern bool RSPL_SvOK(SV *sv) {
     return SvOK(sv);
 }
 
+extern bool RSPL_SvIOK(SV *sv) {
+    return SvIOK(sv);
+}
+
+extern bool RSPL_SvNOK(SV *sv) {
+    return SvNOK(sv);
+}
+
+extern bool RSPL_SvIsUV(SV *sv) {
+    return SvIsUV(sv);
+}
+
+extern usize RSPL_SvUV(SV *sv) {
+    return SvUV(sv);
+}
+
 extern bool RSPL_SvANY(SV *sv) {
     return SvANY(sv);
 }
@@ -169,6 +241,165 @@ extern bool RSPL_SvTRUE(SV *sv) {
     return SvTRUE(sv);
 }
 
+extern bool RSPL_SvOBJECT(SV *sv) {
+    return SvOBJECT(sv);
+}
+
+extern bool RSPL_sv_isobject(SV *sv) {
+    return sv_isobject(sv);
+}
+
+extern bool RSPL_SvMAGICAL(SV *sv) {
+    return SvMAGICAL(sv);
+}
+
+extern SV* RSPL_newSVsv(SV *sv) {
+    return newSVsv(sv);
+}
+
+extern bool RSPL_has_multiplicity() {
+#ifdef MULTIPLICITY
+    return true;
+#else
+    return false;
+#endif
+}
+
+extern bool RSPL_looks_like_number(SV *sv) {
+    return looks_like_number(sv);
+}
+
+/// A new value holding only the numeric form of `sv`, like `0 + $sv`.
+extern SV* RSPL_sv_to_number(SV *sv) {
+    SvGETMAGIC(sv);
+    // this is how perl's arithmetic decides whether a value can be used as an integer
+    if (SvIV_please_nomg(sv)) {
+        return SvIsUV(sv) ? newSVuv(SvUV_nomg(sv)) : newSViv(SvIV_nomg(sv));
+    }
+    return newSVnv(SvNV_nomg(sv));
+}
+
+/// A new value holding only the string form of `sv`, like `"$sv"`.
+extern SV* RSPL_sv_to_string(SV *sv) {
+    STRLEN length;
+    const char *str = SvPV(sv, length);
+    return newSVpvn_flags(str, length, SvUTF8(sv) ? SVf_UTF8 : 0);
+}
+
+extern int RSPL_sv_cmp(SV *a, SV *b) {
+    return sv_cmp(a, b);
+}
+
+/// Compare two scalars as numbers like perl's `==` operator, ignoring `==` overloading.
+extern bool RSPL_sv_num_eq(SV *a, SV *b) {
+    SvGETMAGIC(a);
+    SvGETMAGIC(b);
+
+    if (SvROK(a) && SvROK(b)) {
+        return SvRV(a) == SvRV(b);
+    }
+
+    // like perl's arithmetic, prefer integers and only fall back to doubles if required
+    if (SvIV_please_nomg(a) && SvIV_please_nomg(b)) {
+        if (SvIsUV(a) == SvIsUV(b)) {
+            return SvUVX(a) == SvUVX(b);
+        }
+        // a negative signed value never equals an unsigned one
+        return (SvIsUV(a) ? SvIVX(b) : SvIVX(a)) >= 0 && SvUVX(a) == SvUVX(b);
+    }
+
+    return SvNV_nomg(a) == SvNV_nomg(b);
+}
+
+/// Compare two scalars as strings like perl's `eq` operator, ignoring `eq` overloading.
+extern bool RSPL_sv_str_eq(SV *a, SV *b) {
+    return sv_eq(a, b);
+}
+
+extern bool RSPL_SvAMAGIC(SV *sv) {
+    return SvAMAGIC(sv);
+}
+
+/// Like `sv_2io(sv)`, but returns NULL instead of croaking if `sv` is not a glob or IO handle (or
+/// a reference to one).
+static IO* RSPL_sv_io(SV *sv) {
+    if (SvROK(sv)) {
+        sv = SvRV(sv);
+    }
+
+    if (isGV_with_GP(sv)) {
+        return GvIO((GV*)sv);
+    } else if (SvTYPE(sv) == SVt_PVIO) {
+        return (IO*)sv;
+    }
+
+    return NULL;
+}
+
+/// Like `PerlIO_fileno(IoIFP(sv_2io(sv)))`, but returns -1 instead of croaking if `sv` is not a
+/// glob or IO handle (or a reference to one), or is not open.
+extern int RSPL_sv_fileno(SV *sv) {
+    IO *io = RSPL_sv_io(sv);
+    PerlIO *fp;
+
+    if (!io || !(fp = IoIFP(io))) {
+        return -1;
+    }
+
+    return PerlIO_fileno(fp);
+}
+
+extern bool RSPL_sv_is_handle(SV *sv) {
+    return RSPL_sv_io(sv) != NULL;
+}
+
+/// Read up to `len` bytes from a perl file handle via `PerlIO_read`, which, like `read`, goes
+/// through the handle's layers and buffers.
+///
+/// Returns the number of bytes read (0 at the end of the file), -1 if `sv` is not a handle open
+/// for reading, or -2 on read errors.
+extern ssize_t RSPL_sv_read(SV *sv, char *buffer, usize len) {
+    IO *io = RSPL_sv_io(sv);
+    PerlIO *fp;
+    SSize_t got;
+
+    if (!io || !(fp = IoIFP(io))) {
+        return -1;
+    }
+
+    got = PerlIO_read(fp, buffer, len);
+    if (got < 0 || PerlIO_error(fp)) {
+        return -2;
+    }
+
+    return got;
+}
+
+/// Write all of `data` to the output stream of the perl file handle `sv` via `PerlIO_write`, so it
+/// goes through the handle's layers and buffers.
+///
+/// Returns 0 on success, -1 if `sv` is not a handle open for writing, or -2 on write errors.
+extern int RSPL_sv_write_all(SV *sv, const char *data, usize len) {
+    IO *io = RSPL_sv_io(sv);
+    PerlIO *fp;
+    SSize_t written;
+
+    if (!io || !(fp = IoOFP(io))) {
+        return -1;
+    }
+
+    while (len) {
+        written = PerlIO_write(fp, data, len);
+        if (written <= 0 || PerlIO_error(fp)) {
+            return -2;
+        }
+        data += written;
+        len -= (usize)written;
+    }
+
+    return 0;
+}
+
 // This must be the same as in rust!
 #define TYPE_FLAG_INT     1
 #define TYPE_FLAG_DOUBLE  2
@@ -264,6 +495,10 @@ extern void RSPL_av_push(AV *av, SV *sv) {
     av_push(av, sv);
 }
 
+extern void RSPL_av_clear(AV *av) {
+    av_clear(av);
+}
+
 extern SV* RSPL_av_pop(AV *av) {
     return av_pop(av);
 }
@@ -272,14 +507,77 @@ extern SV** RSPL_av_fetch(AV *av, ssize_t index, int32_t lval) {
     return av_fetch(av, index, lval);
 }
 
+/// Store `value` at `index`, replacing the previous element.
+/// ALWAYS takes ownership of 'value'.
+extern void RSPL_av_store(AV *av, ssize_t index, SV *value) {
+    SV **svp = av_store(av, index, value);
+    // for ties the value only gets element magic attached, set magic triggers the tie's STORE
+    if (SvRMAGICAL(av)) {
+        SvSETMAGIC(value);
+    }
+    if (!svp) {
+        SvREFCNT_dec(value);
+    }
+}
+
 extern HV* RSPL_newHV() {
     return newHV();
 }
 
+/// A new hash with enough buckets for `capacity` keys.
+extern HV* RSPL_newHV_capacity(usize capacity) {
+    HV *hv = newHV();
+    if (capacity > 0) {
+        hv_ksplit(hv, (IV)capacity);
+    }
+    return hv;
+}
+
 extern usize RSPL_HvTOTALKEYS(HV *hv) {
     return HvTOTALKEYS(hv);
 }
 
+extern HE* RSPL_HvEITER(HV *hv) {
+    return HvEITER(hv);
+}
+
+/// Save the state of the hash's internal iterator, to be put back via `RSPL_hv_iter_restore`
+/// after iterating over the hash in between.
+///
+/// Returns false for tied hashes, whose iteration state lives in the tie and cannot be restored.
+extern bool RSPL_hv_iter_save(HV *hv, HE **eiter, I32 *riter, bool *lazydel) {
+    if (SvRMAGICAL(hv) && mg_find((SV*)hv, PERL_MAGIC_tied)) {
+        return false;
+    }
+    *eiter = HvEITER(hv);
+    *riter = HvRITER(hv);
+    // The current entry may have been deleted during the iteration, in which case
+    // `hv_iterinit` would free it.
+    *lazydel = HvLAZYDEL(hv) != 0;
+    HvLAZYDEL_off(hv);
+    return true;
+}
+
+extern void RSPL_hv_iter_restore(HV *hv, HE *eiter, I32 riter, bool lazydel) {
+    HvEITER_set(hv, eiter);
+    HvRITER_set(hv, riter);
+    if (lazydel) {
+        HvLAZYDEL_on(hv);
+    }
+}
+
+/// Tied hashes don't have a meaningful key count, so ask the tie via `SCALAR` (or `FIRSTKEY`).
+extern bool RSPL_hv_is_empty(HV *hv) {
+    if (SvRMAGICAL(hv) && mg_find((SV*)hv, PERL_MAGIC_tied)) {
+        return !SvTRUE(hv_scalar(hv));
+    }
+    return HvTOTALKEYS(hv) == 0;
+}
+
+extern void RSPL_hv_clear(HV *hv) {
+    hv_clear(hv);
+}
+
 extern SV** RSPL_hv_fetch(HV *hv, const char *key, int32_t klen, int32_t lval) {
     return hv_fetch(hv, key, klen, lval);
 }
@@ -294,6 +592,49 @@ extern bool RSPL_hv_store(HV *hv, const char *key, int32_t klen, SV *value) {
     }
 }
 
+/// Store `value` under `key` unless the key exists, returns whether it was stored.
+/// ALWAYS takes ownership of 'value'.
+extern bool RSPL_hv_store_if_absent(HV *hv, const char *key, int32_t klen, SV *value) {
+    HE *he;
+    SV **svp;
+
+    // magic such as ties needs the regular accessors, with set magic to trigger a tie's STORE
+    if (SvRMAGICAL(hv)) {
+        if (hv_exists(hv, key, klen)) {
+            SvREFCNT_dec(value);
+            return false;
+        }
+        // for ties the value only gets element magic attached and is not stored in the hash
+        svp = hv_store(hv, key, klen, value, 0);
+        SvSETMAGIC(value);
+        if (!svp) {
+            SvREFCNT_dec(value);
+        }
+        return true;
+    }
+
+    // a single lookup creating the entry if missing, with an empty value we can fill in
+    he = (HE*)hv_common_key_len(hv, key, klen, HV_FETCH_LVALUE | HV_FETCH_EMPTY_HE, NULL, 0);
+    if (HeVAL(he)) {
+        SvREFCNT_dec(value);
+        return false;
+    }
+    HeVAL(he) = value;
+    return true;
+}
+
+/// Move the value stored under `from` to the key `to`, taking over the hash's reference to it.
+/// Returns whether `from` existed.
+extern bool RSPL_hv_rename_key(HV *hv, const char *from, int32_t flen, const char *to, int32_t tlen) {
+    // `hv_delete` hands over the hash's reference as a mortal, so keep it alive for the store
+    SV *value = hv_delete(hv, from, flen, 0);
+    if (!value) {
+        return false;
+    }
+    RSPL_hv_store(hv, to, tlen, SvREFCNT_inc(value));
+    return true;
+}
+
 extern bool RSPL_hv_store_ent(HV *hv, SV *key, SV *value) {
     if (hv_store_ent(hv, key, value, 0) == NULL) {
         SvREFCNT_dec(value);
@@ -319,6 +660,17 @@ extern SV* RSPL_hv_iterkeysv(HE *he) {
     return hv_iterkeysv(he);
 }
 
+/// Get the key of a hash entry without creating an `SV`. Returns `NULL` for entries which store
+/// their key as an `SV` (eg. from tied hashes).
+extern const char* RSPL_HeKEY(HE *he, usize *out_len, bool *out_utf8) {
+    if (HeKLEN(he) == HEf_SVKEY) {
+        return NULL;
+    }
+    *out_len = (usize)HeKLEN(he);
+    *out_utf8 = HeKUTF8(he) != 0;
+    return HeKEY(he);
+}
+
 extern SV* RSPL_hv_iterval(HV *hv, HE *he) {
     return hv_iterval(hv, he);
 }
@@ -327,6 +679,10 @@ extern HV* RSPL_gv_stashsv(SV *name, int32_t flags) {
     return gv_stashsv(name, flags);
 }
 
+extern int32_t RSPL_GV_ADD() {
+    return GV_ADD;
+}
+
 extern SV* RSPL_sv_bless(SV *sv, HV *stash) {
     return sv_bless(sv, stash);
 }
@@ -347,6 +703,57 @@ extern void RSPL_LEAVE() {
     LEAVE;
 }
 
+extern I32 RSPL_scopestack_ix() {
+    return PL_scopestack_ix;
+}
+
+static void RSPL_clear_flag(pTHX_ void *flag) {
+    *(bool*)flag = false;
+}
+
+/// Clear `*flag` once perl leaves the current scope, however that happens.
+extern void RSPL_SAVE_clear_flag(bool *flag) {
+    SAVEDESTRUCTOR_X(RSPL_clear_flag, flag);
+}
+
+/// Localize the package scalar `name` like perl's `local $name`, creating it if necessary, and
+/// return the new (undefined) value. The old value is restored on the next `LEAVE`.
+extern SV* RSPL_save_scalar(const char *name, size_t len) {
+    GV *gv = gv_fetchpvn_flags(name, len, GV_ADD | SVf_UTF8, SVt_PV);
+    return save_scalar(gv);
+}
+
+extern void RSPL_sv_setsv_mg(SV *dst, SV *src) {
+    sv_setsv_mg(dst, src);
+}
+
+extern void RSPL_sv_setiv_mg(SV *sv, isize v) {
+    sv_setiv_mg(sv, v);
+}
+
+extern void RSPL_sv_setuv_mg(SV *sv, usize v) {
+    sv_setuv_mg(sv, v);
+}
+
+extern void RSPL_sv_setnv_mg(SV *sv, double v) {
+    sv_setnv_mg(sv, v);
+}
+
+extern void RSPL_sv_setpvn_mg(SV *sv, const char *v, size_t len, bool utf8) {
+    sv_setpvn(sv, v, len);
+    if (utf8) {
+        SvUTF8_on(sv);
+    } else {
+        SvUTF8_off(sv);
+    }
+    SvSETMAGIC(sv);
+}
+
+extern void RSPL_sv_set_undef(SV *sv) {
+    sv_set_undef(sv);
+    SvSETMAGIC(sv);
+}
+
 extern const char* RSPL_sv_reftype(const SV *const sv, const int ob) {
     return sv_reftype(sv, ob);
 }
@@ -385,12 +792,12 @@ extern SV* RSPL_substr(SV *orig, usize off, usize len) {
 //    return SvFLAGS(sv);
 //}
 
-//extern bool RSPL_SvMAGICAL(SV *sv) {
-//    return SvMAGICAL(sv);
-//}
-
 extern void RSPL_SvGETMAGIC(SV *sv) {
-    return SvGETMAGIC(sv);
+    SvGETMAGIC(sv);
+}
+
+extern void RSPL_SvSETMAGIC(SV *sv) {
+    SvSETMAGIC(sv);
 }
 
 extern const MGVTBL* RSPL_vtbl_substr() {
@@ -450,6 +857,246 @@ extern int RSPL_PERL_MAGIC_ext() {
     return PERL_MAGIC_ext;
 }
 
+#ifndef G_LIST
+#define G_LIST G_ARRAY
+#endif
+
+/// The context the current sub was called in: 0 for void, 1 for scalar, 2 for list context.
+extern uint32_t RSPL_gimme() {
+    switch (GIMME_V) {
+    case G_VOID:
+        return 0;
+    case G_SCALAR:
+        return 1;
+    default:
+        return 2;
+    }
+}
+
+/// After a `call_sv` or `eval_sv` with `count` results on the stack ending at `sp`, return a new
+/// array containing copies of the results, or `NULL` with a copy of `$@` stored in `*error`.
+static AV* RSPL_collect_results(SV **sp, I32 count, SV **error) {
+    AV *result;
+    I32 i;
+
+    if (SvTRUE(ERRSV)) {
+        *error = newSVsv(ERRSV);
+        return NULL;
+    }
+
+    result = newAV();
+    if (count > 0) {
+        av_extend(result, count - 1);
+    }
+    for (i = 0; i != count; ++i) {
+        av_store(result, i, newSVsv(*(sp - count + 1 + i)));
+    }
+    return result;
+}
+
+/// Call `sub` in list context with borrowed `args`, catching errors via `G_EVAL`.
+/// On success returns a new array containing copies of the returned values, otherwise returns
+/// `NULL` and stores a copy of `$@` in `*error`. The caller's `$@` is localized and left untouched.
+extern AV* RSPL_call_sv_list(SV *sub, SV **args, usize nargs, SV **error) {
+    dSP;
+    AV *result;
+    I32 count;
+
+    ENTER;
+    SAVETMPS;
+    save_scalar(PL_errgv);
+
+    PUSHMARK(SP);
+    EXTEND(SP, (SSize_t)nargs);
+    for (usize a = 0; a != nargs; ++a) {
+        PUSHs(args[a]);
+    }
+    PUTBACK;
+
+    count = call_sv(sub, G_LIST | G_EVAL);
+
+    SPAGAIN;
+    result = RSPL_collect_results(SP, count, error);
+    SP -= count;
+    PUTBACK;
+
+    FREETMPS;
+    LEAVE;
+
+    return result;
+}
+
+/// Get the anonymous xsub for `func` of the current interpreter, creating it on first use. It is
+/// kept in `PL_modglobal` under `key`, so each interpreter gets its own.
+static SV* RSPL_interp_xsub(const char *key, XSUBADDR_t func) {
+    SV **xsub = hv_fetch(PL_modglobal, key, (I32)strlen(key), 1);
+    if (!SvROK(*xsub)) {
+        SV *rv = newRV_noinc((SV*)newXS(NULL, func, __FILE__));
+        sv_setsv(*xsub, rv);
+        SvREFCNT_dec(rv);
+    }
+    return SvRV(*xsub);
+}
+
+/// xsub emitting its argument as a warning via `warn_sv`.
+static XSPROTO(RSPL_warn_xsub) {
+    dXSARGS;
+
+    if (items != 1) {
+        croak_xs_usage(cv, "message");
+    }
+
+    warn_sv(ST(0));
+    XSRETURN_EMPTY;
+}
+
+/// Emit `sv` as a warning. If `carp` is set, it reports the location the currently running perl
+/// sub was called from, similar to `Carp::carp`.
+///
+/// The warning is emitted from an xsub called with `G_EVAL`, with the same result handling as
+/// `RSPL_call_sv_list`, so a dying `$SIG{__WARN__}` handler produces an error instead of
+/// unwinding through the caller.
+extern AV* RSPL_warn_sv(SV *sv, bool carp, SV **error) {
+    const PERL_CONTEXT *caller = carp ? caller_cx(0, NULL) : NULL;
+    SV *xsub = RSPL_interp_xsub("perlmod::warn_xsub", RSPL_warn_xsub);
+    AV *result;
+
+    ENTER;
+    if (caller) {
+        SAVEVPTR(PL_curcop);
+        PL_curcop = caller->blk_oldcop;
+    }
+    result = RSPL_call_sv_list(xsub, &sv, 1, error);
+    LEAVE;
+
+    return result;
+}
+
+/// Evaluate a string of perl code in the context given as in `RSPL_gimme`, with the same result
+/// handling as `RSPL_call_sv_list`.
+extern AV* RSPL_eval_sv(SV *code, uint32_t gimme, SV **error) {
+    dSP;
+    AV *result;
+    I32 count;
+    I32 flags = gimme == 0 ? G_VOID : gimme == 1 ? G_SCALAR : G_LIST;
+
+    ENTER;
+    SAVETMPS;
+
+    count = eval_sv(code, flags | G_EVAL);
+
+    SPAGAIN;
+    result = RSPL_collect_results(SP, count, error);
+    SP -= count;
+    PUTBACK;
+
+    FREETMPS;
+    LEAVE;
+
+    return result;
+}
+
+/// xsub calling `amagic_call` with its arguments `(left, right, method, flags)`, returning nothing
+/// if no overloaded method applies.
+static XSPROTO(RSPL_amagic_xsub) {
+    dXSARGS;
+    SV *result;
+
+    if (items != 4) {
+        croak_xs_usage(cv, "left, right, method, flags");
+    }
+
+    result = amagic_call(ST(0), ST(1), (int)SvIV(ST(2)), (int)SvIV(ST(3)));
+    if (!result) {
+        XSRETURN_EMPTY;
+    }
+    ST(0) = result;
+    XSRETURN(1);
+}
+
+/// Invoke the overloaded operator `op` (the index of `perlmod::scalar::Overload`) on `left` and
+/// the optional `right` operand.
+///
+/// The overload method is called via an xsub to catch errors via `G_EVAL`, with the same result
+/// handling as `RSPL_call_sv_list`. The resulting array is empty if no overloaded method exists.
+extern AV* RSPL_amagic_call(SV *left, SV *right, int op, SV **error) {
+    SV *xsub;
+    SV *args[4];
+    AV *result;
+    int method;
+    int flags = 0;
+
+    switch (op) {
+    case 0: method = add_amg; break;
+    case 1: method = subtr_amg; break;
+    case 2: method = mult_amg; break;
+    case 3: method = div_amg; break;
+    case 4: method = modulo_amg; break;
+    case 5: method = pow_amg; break;
+    case 6: method = concat_amg; break;
+    case 7: method = ncmp_amg; break;
+    case 8: method = scmp_amg; break;
+    case 9: method = eq_amg; break;
+    case 10: method = seq_amg; break;
+    case 11: method = string_amg; flags = AMGf_unary | AMGf_noright; break;
+    case 12: method = numer_amg; flags = AMGf_unary | AMGf_noright; break;
+    case 13: method = bool__amg; flags = AMGf_unary | AMGf_noright; break;
+    case 14: method = neg_amg; flags = AMGf_unary | AMGf_noright; break;
+    case 15: method = abs_amg; flags = AMGf_unary | AMGf_noright; break;
+    default:
+        *error = newSVpvs("invalid overload operator");
+        return NULL;
+    }
+
+    args[0] = left;
+    args[1] = right ? right : &PL_sv_undef;
+    args[2] = newSViv(method);
+    args[3] = newSViv(flags);
+
+    xsub = RSPL_interp_xsub("perlmod::amagic_xsub", RSPL_amagic_xsub);
+    result = RSPL_call_sv_list(xsub, args, 4, error);
+
+    SvREFCNT_dec(args[2]);
+    SvREFCNT_dec(args[3]);
+
+    return result;
+}
+
+/// Check whether `sv` (an object or a class name) `DOES` the role `role`, which includes `@ISA`
+/// inheritance as well as roles composed via an overridden `DOES` method.
+///
+/// The method is called with `G_EVAL`, so a missing or dying `DOES` method (eg. for unblessed
+/// references) is treated as returning false. The error is discarded, `$@` is localized so the
+/// caller's value is kept.
+extern bool RSPL_sv_does(SV *sv, const char *role, usize len) {
+    dSP;
+    I32 count;
+    bool does = false;
+
+    ENTER;
+    SAVETMPS;
+    save_scalar(PL_errgv);
+
+    PUSHMARK(SP);
+    XPUSHs(sv);
+    mXPUSHs(newSVpvn_flags(role, len, SVf_UTF8));
+    PUTBACK;
+
+    count = call_method("DOES", G_SCALAR | G_EVAL);
+
+    SPAGAIN;
+    if (count == 1 && !SvTRUE(ERRSV)) {
+        does = SvTRUE(TOPs);
+    }
+    SP -= count;
+    PUTBACK;
+
+    FREETMPS;
+    LEAVE;
+
+    return does;
+}
+
 extern HV* RSPL_defstash() {
     return PL_defstash;
 }
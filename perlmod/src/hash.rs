@@ -1,10 +1,12 @@
 //! Module dealing with perl [`Hash`](crate::Hash)es. ([`HV`](crate::ffi::HV) pointers).
 
+use std::collections::HashMap;
+
 use crate::error::CastError;
-use crate::ffi::{self, HV, SV};
+use crate::ffi::{self, HE, HV, SV};
 use crate::raw_value;
 use crate::scalar::{Scalar, ScalarRef};
-use crate::Value;
+use crate::{Error, Value};
 
 /// An owned reference to a perl hash value (HV).
 ///
@@ -20,12 +22,26 @@ impl Hash {
         unsafe { Self::from_raw_move(ffi::RSPL_newHV()) }
     }
 
+    /// Create a new hash with enough buckets for `capacity` keys, so inserting them does not need
+    /// to grow the hash repeatedly.
+    pub fn with_capacity(capacity: usize) -> Self {
+        unsafe { Self::from_raw_move(ffi::RSPL_newHV_capacity(capacity)) }
+    }
+
     /// Turn this into a `Scalar`. The underlying perl value does not change, this is a pure type
     /// cast down to a less specific "pointer" type.
     pub fn into_scalar(self) -> Scalar {
         self.0
     }
 
+    /// Turn this into a reference to the hash, as commonly passed to or returned to perl.
+    ///
+    /// Contrary to [`Value::from`], which keeps the hash itself, this produces the equivalent of
+    /// [`Value::new_ref_owned(hash)`](Value::new_ref_owned()).
+    pub fn into_ref(self) -> Value {
+        Value::new_ref_owned(self)
+    }
+
     /// Get the internal perl value as a low-level `HV` pointer.
     pub fn hv(&self) -> *mut HV {
         self.0.sv() as *mut HV
@@ -69,13 +85,18 @@ impl Hash {
     }
 
     /// Get the number of keys in this hash.
+    ///
+    /// Note that this does not call into tied hashes. Use [`is_empty`](Hash::is_empty()) to check
+    /// whether a tied hash contains any entries.
     pub fn len(&self) -> usize {
         unsafe { ffi::RSPL_HvTOTALKEYS(self.hv()) }
     }
 
     /// Check if this is an empty hash.
+    ///
+    /// For tied hashes this calls the tie's `SCALAR` method, or `FIRSTKEY` if there is none.
     pub fn is_empty(&self) -> bool {
-        self.len() == 0
+        unsafe { ffi::RSPL_hv_is_empty(self.hv()) }
     }
 
     /// Get a value from the hash. Note that this only uses utf8 strings. For a more generic method
@@ -101,6 +122,13 @@ impl Hash {
         }
     }
 
+    /// Remove all entries from the hash in place. (perlxs `hv_clear`).
+    ///
+    /// Other references to this hash will see it empty as well.
+    pub fn clear(&self) {
+        unsafe { ffi::RSPL_hv_clear(self.hv()) }
+    }
+
     /// Insert a value into the hash.
     pub fn insert(&self, key: &str, value: Value) {
         self.insert_by_bytes(key.as_bytes(), value);
@@ -118,6 +146,40 @@ impl Hash {
         }
     }
 
+    /// Insert a value into the hash only if there is no entry for the key yet, for instance to
+    /// merge defaults into a hash without replacing values which are already set, including
+    /// `undef` ones.
+    ///
+    /// Returns whether the value was inserted, otherwise it is dropped. For hashes without magic
+    /// this only looks up the key once.
+    pub fn insert_if_absent(&self, key: &str, value: Value) -> bool {
+        unsafe {
+            ffi::RSPL_hv_store_if_absent(
+                self.hv(),
+                key.as_ptr() as *const libc::c_char,
+                key.len() as i32,
+                value.into_raw(),
+            )
+        }
+    }
+
+    /// Move the value stored under the key `from` to the key `to` in place, replacing any value
+    /// previously stored under `to`. The value itself is moved, not copied, so references to it
+    /// stay valid.
+    ///
+    /// Returns whether `from` existed. Like [`get`](Hash::get()), this uses the keys' utf8 bytes.
+    pub fn rename_key(&self, from: &str, to: &str) -> bool {
+        unsafe {
+            ffi::RSPL_hv_rename_key(
+                self.hv(),
+                from.as_ptr() as *const libc::c_char,
+                from.len() as i32,
+                to.as_ptr() as *const libc::c_char,
+                to.len() as i32,
+            )
+        }
+    }
+
     /// Insert a value using an existin value as a key.
     pub fn insert_by_value(&self, key: &Value, value: Value) {
         unsafe {
@@ -137,6 +199,55 @@ impl Hash {
         Iter { hash: self }
     }
 
+    /// Save the state of the hash's internal iterator, which is put back when the returned guard
+    /// is dropped, so the hash can be iterated over in between without affecting an ongoing
+    /// iteration.
+    ///
+    /// Returns `None` for tied hashes, whose iteration state cannot be restored.
+    pub(crate) fn save_iter(&self) -> Option<SavedIter<'_>> {
+        let mut eiter = std::ptr::null_mut();
+        let mut riter = 0;
+        let mut lazydel = false;
+        unsafe { ffi::RSPL_hv_iter_save(self.hv(), &mut eiter, &mut riter, &mut lazydel) }.then(
+            || SavedIter {
+                hash: self,
+                eiter,
+                riter,
+                lazydel,
+            },
+        )
+    }
+
+    /// Get the *shared* iterator over this hash's elements, yielding the keys as full [`Scalar`]
+    /// values.
+    ///
+    /// Contrary to [`shared_iter`](Hash::shared_iter()), this preserves the key's UTF-8 flag, so
+    /// the caller can decide how to interpret keys containing arbitrary binary data.
+    ///
+    /// Note that this uses the hash's internal iterator, see [`shared_iter`](Hash::shared_iter())
+    /// for details.
+    pub fn iter_sv(&self) -> IterSv<'_> {
+        unsafe {
+            ffi::RSPL_hv_iterinit(self.hv());
+        }
+        IterSv { hash: self }
+    }
+
+    /// Deserialize each value of the hash into a `HashMap`, see [`from_value`](crate::from_value).
+    ///
+    /// Note that this uses the hash's internal iterator, see [`shared_iter`](Hash::shared_iter())
+    /// for details.
+    pub fn into_hashmap<T>(&self) -> Result<HashMap<String, T>, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let mut map = HashMap::with_capacity(self.len());
+        for (key, value) in self.iter_sv() {
+            map.insert(key.pv_string_utf8().to_owned(), crate::from_value(value)?);
+        }
+        Ok(map)
+    }
+
     /// Get the global `PL_defstash`.
     ///
     /// # Safety
@@ -173,9 +284,33 @@ impl TryFrom<Scalar> for Hash {
     }
 }
 
+/// This prints the hash's contents recursively up to a depth of
+/// [`DEBUG_DEPTH`](crate::value::DEBUG_DEPTH), see [`Value::debug_nested`].
+///
+/// This uses the hash's internal iterator, but puts back its previous state afterwards, except
+/// for tied hashes, see [`shared_iter`](Hash::shared_iter()).
 impl std::fmt::Debug for Hash {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{{HASH}}")
+        std::fmt::Debug::fmt(
+            &Value::Hash(self.clone_ref()).debug_nested(crate::value::DEBUG_DEPTH),
+            f,
+        )
+    }
+}
+
+/// The state of a hash's internal iterator, see [`Hash::save_iter`].
+pub(crate) struct SavedIter<'a> {
+    hash: &'a Hash,
+    eiter: *mut HE,
+    riter: i32,
+    lazydel: bool,
+}
+
+impl Drop for SavedIter<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::RSPL_hv_iter_restore(self.hash.hv(), self.eiter, self.riter, self.lazydel);
+        }
     }
 }
 
@@ -208,6 +343,31 @@ impl<'a> Iterator for Iter<'a> {
     }
 }
 
+/// An iterator over a perl hash yielding the keys as [`Scalar`] values.
+///
+/// Like [`Iter`], this uses the hash's integrated iterator.
+pub struct IterSv<'a> {
+    hash: &'a Hash,
+}
+
+impl<'a> Iterator for IterSv<'a> {
+    type Item = (Scalar, Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            let entry = ffi::RSPL_hv_iternext(self.hash.hv());
+            if entry.is_null() {
+                return None;
+            }
+
+            Some((
+                Scalar::from_raw_ref(ffi::RSPL_hv_iterkeysv(entry)),
+                Value::from_raw_ref(ffi::RSPL_hv_iterval(self.hash.hv(), entry)),
+            ))
+        }
+    }
+}
+
 impl serde::Serialize for Hash {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -219,8 +379,9 @@ impl serde::Serialize for Hash {
             return raw_value::serialize_raw(self, serializer);
         }
 
+        // serialize the keys as full scalars so they end up as strings with the correct encoding
         let mut map = serializer.serialize_map(Some(self.len()))?;
-        for (k, v) in self.shared_iter() {
+        for (k, v) in self.iter_sv() {
             map.serialize_key(&k)?;
             map.serialize_value(&v)?;
         }
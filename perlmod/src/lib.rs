@@ -31,14 +31,21 @@ mod macros;
 
 #[macro_use]
 pub mod ffi;
+#[doc(inline)]
+pub use ffi::{carp, enter_scope, eval, warn, Context, ScopeGuard};
 
 pub mod de;
 pub mod ser;
 
 #[doc(inline)]
-pub use de::{from_ref_value, from_value};
+pub use de::{
+    from_blessed_value, from_borrowed_value, from_ref_value, from_value, from_value_lenient,
+    from_value_strict_bool, Validate, Warning,
+};
 #[doc(inline)]
-pub use ser::to_value;
+pub use ser::{
+    return_list, to_blessed_value, to_value, to_value_empty_as_undef, LazyList, ReturnList,
+};
 
 pub mod scalar;
 #[doc(inline)]
@@ -63,6 +70,22 @@ pub mod magic;
 #[doc(inline)]
 pub use magic::{MagicSpec, MagicTag, MagicValue};
 
+pub mod callback;
+#[doc(inline)]
+pub use callback::{CachedCall, Callback};
+
+pub mod file_handle;
+#[doc(inline)]
+pub use file_handle::FileHandle;
+
+pub mod local;
+#[doc(inline)]
+pub use local::{save_scalar, LocalGuard};
+
+pub mod queue;
+#[doc(inline)]
+pub use queue::{PerlCallbackQueue, SendValue};
+
 #[cfg(feature = "exporter")]
 #[doc(inline)]
 pub use perlmod_macro::package;
@@ -72,21 +95,55 @@ pub use perlmod_macro::package;
 /// Attribute to export a function so that it can be installed as an `xsub` in perl. See the
 /// [`package!`](macro@package) macro for a usage example.
 ///
+/// Functions returning a tuple return its members as a list to perl. The same applies to tuple
+/// structs such as `struct Pair(u32, u32)`, including ones wrapped in newtype structs, to a
+/// [`ReturnList`] built at runtime, and to a [`LazyList`] in list context, while other values are
+/// returned as a single scalar.
+///
 /// This macro has the following optional arguments:
 ///
 /// * `raw_return`: specifies that the return type, which must be a [`Value`], will be returned as
 ///   is, and not go through serialization. As of perlmod
 ///   0.6, serialization of a [`Value`] will not produce a clone, so this is mostly an
-///   optimization.
+///   optimization. An `Option<Value>` can be returned as well, with `None` producing `undef`.
 /// * `prototype`: The perl prototype for the function. By default, this will be guessed from the
 ///   parameters as a chain of '$', with trailing `Option<>` parameters behind a `;`. So for
 ///   example, an `fn(i32, Option<i32>, i32, Option<i32>)` has the prototype `$$$;$`.
+///   Parameters of type `&perlmod::Array` and `&perlmod::Hash` use `\@` and `\%` respectively,
+///   and individual parameters can override theirs via `#[proto = "..."]` (see below).
 /// * `xs_name`: override the name of the exported xsub, this is not recommended and only makes
 ///   sense when *not* using the `#[package]` macro, as with the `#[package]` macro, these aren't
 ///   publicly visible.
 /// * `name`: the name the function should be using in perl. This only makes sense with the
 ///   `#[package]` macro, as otherwise the user is responsible for loading the function via perl's
-///   `DynaLoader` on their own.
+///   `DynaLoader` on their own. It can be repeated to install the function under additional
+///   names as well, for instance to keep a legacy alias: `#[export(name = "foo", name = "old")]`.
+/// * `errno`: copy the value stored via [`set_errno`](error::set_errno()) to libc's `errno` right
+///   before returning to perl, so perl code can access it via `$!`. If the function returns an
+///   error implementing [`HasErrno`](error::HasErrno), its value takes precedence.
+/// * `option_as_list`: for functions returning an `Option<T>` (or `Result<Option<T>, E>`), return
+///   an empty list instead of `undef` for `None` when called in list context, like a bare `return`
+///   in perl. In scalar context `None` still returns `undef`.
+/// * `map_as_list`: for functions returning a map (or anything else serializing to a hash), return
+///   the key/value pairs as a flat list when called in list context, so perl code can assign the
+///   result to a hash directly. In scalar context a hash reference is returned as usual.
+///   Functions returning a `Vec<(K, V)>` of pairs are treated the same way, with the list keeping
+///   the pairs' order, while in scalar context they produce a hash reference.
+/// * `return_ok_true`: for functions returning `Result<(), E>`, return a true value on success
+///   instead of nothing, so perl code can use them in conditions such as `if (f()) { ... }`.
+/// * `empty_as_undef`: serialize empty sequences, such as an empty `Vec`, within the return value
+///   as `undef` instead of an empty array reference, see [`to_value_empty_as_undef`].
+/// * `substr_return`: for functions returning a `&str` or `Cow<str>` borrowed from one of their
+///   `&str` parameters, return a substring of the original perl value instead of a copy, as with
+///   [`ScalarRef::merge_str_slice`]. Like perl's 4-argument `substr`, the returned value aliases
+///   the parameter: assigning to it (eg. as the loop variable of `for (f($text))`) modifies the
+///   parameter, and references taken to it see later changes to the parameter. Strings which do
+///   not borrow from a parameter are still copied.
+/// * `accessor`: install the method under the given name as a combined getter and setter, like
+///   the accessors generated by `Moo` or `Moose`. The function must take exactly two parameters,
+///   the invocant and an `Option` of the value. The value is `None` when called as a getter
+///   (`$obj->name`), and `Some` when called as a setter (`$obj->name($value)`), even if the
+///   value is `undef`, which can be accepted via `Option<Option<T>>`. This replaces `name`.
 ///
 /// Additionally, function parameters can also use the following attributes:
 ///
@@ -99,6 +156,25 @@ pub use perlmod_macro::package;
 ///   Implementing the `TryFrom` trait accordingly can make using blessed references more
 ///   convenient, but at the cost of hiding underlying `unsafe` code.
 ///
+/// * `#[coderef]` with a parameter of type [`Callback`] (or `Option<Callback>`): The parameter
+///   must be a code reference, which can then be called from rust, for instance to stream
+///   results back to perl one item at a time.
+///
+/// * `#[class]` with a parameter of type `&str`: The class name of a method's invocant, which
+///   can be either a class name as in `Foo->new()`, or an object as in `$obj->new()`, in which
+///   case the package it is blessed into is passed. See [`Value::class_name`].
+///
+/// * `#[validate]`: After deserialization, call [`Validate::validate`] on the parameter and `die`
+///   with its error instead of calling the function if it fails. This can be used for checks
+///   beyond what `Deserialize` does, such as value ranges.
+///
+/// * `#[proto = "..."]`: Override the generated prototype for this parameter, for instance
+///   `#[proto = "\\[@%]"]`. This is checked to be a valid prototype for a single parameter.
+///
+/// * `#[context]` with a parameter of type [`Context`]: The context the function was called in,
+///   which can be used to decide early whether to produce a list, a single value or nothing at
+///   all. Like `#[cv]`, this is not passed from perl and does not take a parameter slot.
+///
 /// * `#[cv]`: This can be used on a single parameter of type [`&CV`](perlmod::ffi::CV) to get
 ///   access to the `xsub` value used to call the function.
 ///
@@ -106,5 +182,40 @@ pub use perlmod_macro::package;
 ///   closures with an xsub as an entry point to retrieving the closure via
 ///   [`magic`](ScalarRef::add_magic).
 ///
+/// Parameters of type [`&perlmod::Array`](Array) or [`&perlmod::Hash`](Hash) expect a reference
+/// to an array or hash respectively. Their prototype lets perl code pass `@array` or `%hash`
+/// directly, which will be passed as a reference automatically. The types must be spelled with
+/// their `perlmod::` path (optionally with a leading `::`), as the macro can only see their
+/// names.
+///
+/// Other than that, only `&str` and `&[u8]` parameters can borrow from the perl values passed to
+/// the function. In particular, `&mut` parameters are rejected, as they could only ever refer to
+/// a temporary deserialized copy:
+///
+/// ```compile_fail
+/// #[perlmod::package(name = "RSPM::Doc::MutRef", file = "/dev/null")]
+/// mod export {
+///     #[export]
+///     fn append(list: &mut Vec<String>) {
+///         list.push("appended".to_string());
+///     }
+/// }
+/// ```
+///
+/// The generated xsubs call the function directly, without a `catch_unwind` wrapper, so there is
+/// no unwinding overhead to opt out of. Since the xsubs are `extern "C"` functions, a panic
+/// escaping an exported function aborts the process. Functions which may panic should use
+/// [`std::panic::catch_unwind`] themselves and turn the panic into an error.
+///
+/// When used outside of a [`package`](macro@package), a module named `<function>_meta` is
+/// generated alongside the function, containing the `XS_NAME`, `PERL_NAME`, `ALIASES`,
+/// `PROTOTYPE` and the `XSUB` itself as constants, so it can be registered manually via
+/// [`RSPL_newXS_flags`](ffi::RSPL_newXS_flags), or collected into a package with
+/// [`make_package!`](macro@make_package).
+///
 /// For an example on making blessed objects, see [`Value::bless_box`](Value::bless_box()).
 pub use perlmod_macro::export;
+
+#[cfg(feature = "exporter")]
+#[doc(inline)]
+pub use perlmod_macro::make_package;
@@ -0,0 +1,65 @@
+//! Localizing perl package variables, like perl's `local`.
+
+use crate::ffi::{self, Scope, SV};
+use crate::scalar::ScalarRef;
+
+/// Localize a package scalar such as `$/` or `$Some::Package::var`, like `local $name;` in perl.
+///
+/// The variable is created if it does not exist yet and starts out as `undef`, its previous value
+/// is restored once the returned guard is dropped. The name is given without the `$` sigil.
+///
+/// ```no_run
+/// use perlmod::{Callback, Error, Value};
+///
+/// fn read_all(read: &Callback) -> Result<Vec<Value>, Error> {
+///     // like `local $/ = undef;`
+///     let _slurp = perlmod::save_scalar("/");
+///     read.call(&[])
+/// }
+/// ```
+pub fn save_scalar(name: &str) -> LocalGuard {
+    let scope = Scope::enter();
+    let sv =
+        unsafe { ffi::RSPL_SvREFCNT_inc(ffi::RSPL_save_scalar(name.as_ptr() as _, name.len())) };
+    LocalGuard { sv, scope }
+}
+
+/// A localized package scalar returned by [`save_scalar`]. This dereferences to the new value of
+/// the variable.
+///
+/// Dropping this restores the previous value. Since this uses perl's save stack, guards must be
+/// dropped in the reverse order of their creation, and before returning to perl.
+///
+/// Dropping a guard while a scope entered after it (eg. by a later `save_scalar` call) is still
+/// active emits a perl warning and keeps the variable localized until perl leaves the enclosing
+/// scope, at the latest when the exported function returns. If perl has already left the scope, eg. because the guard was kept around after
+/// returning to perl, the previous value has already been restored and dropping the guard does
+/// nothing else.
+#[must_use = "the previous value is restored as soon as the guard is dropped"]
+pub struct LocalGuard {
+    // we hold a reference, so this stays valid even after perl restored the old value
+    sv: *mut SV,
+    scope: Scope,
+}
+
+impl LocalGuard {
+    /// Assign a copy of `value` to the localized variable, like `local $name = $value;`.
+    pub fn set(&self, value: &ScalarRef) {
+        unsafe { ffi::RSPL_sv_setsv_mg(self.sv, value.sv()) }
+    }
+}
+
+impl core::ops::Deref for LocalGuard {
+    type Target = ScalarRef;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*(self.sv as *const ScalarRef) }
+    }
+}
+
+impl Drop for LocalGuard {
+    fn drop(&mut self) {
+        self.scope.leave_with(|| unsafe { ffi::RSPL_LEAVE() });
+        unsafe { ffi::RSPL_SvREFCNT_dec(self.sv) }
+    }
+}
@@ -242,3 +242,49 @@ macro_rules! instantiate_magic {
         $crate::instantiate_magic_result!($class, $magic => $value)?
     }};
 }
+
+/// Create a single bootstrap function registering the exports of multiple
+/// [`#[package]`](macro@crate::package) modules at once.
+///
+/// Every package module provides its own `boot_<Package__Name>` symbol, so by default each of
+/// them needs its own `.pm` file to load it. With this macro, a single host `.pm` file can load
+/// all of them through one symbol instead. The packages are listed explicitly by their module
+/// paths, and the function name has to be the `boot_` symbol perl looks up for the host package.
+///
+/// ```
+/// #[perlmod::package(name = "RSPM::Doc::First", file = "/dev/null")]
+/// mod first {
+///     #[export]
+///     fn name() -> &'static str {
+///         "first"
+///     }
+/// }
+///
+/// #[perlmod::package(name = "RSPM::Doc::Second", file = "/dev/null")]
+/// mod second {
+///     #[export]
+///     fn name() -> &'static str {
+///         "second"
+///     }
+/// }
+///
+/// // This is what `RSPM::Doc::Combined->bootstrap` will call:
+/// perlmod::combined_bootstrap! {
+///     pub fn boot_RSPM__Doc__Combined { first, second }
+/// }
+/// ```
+#[macro_export]
+macro_rules! combined_bootstrap {
+    ($vis:vis fn $name:ident { $($($package:ident)::+),* $(,)? }) => {
+        #[no_mangle]
+        #[allow(non_snake_case)]
+        $vis extern "C" fn $name(_cv: Option<&$crate::ffi::CV>) {
+            unsafe {
+                let argmark = $crate::ffi::pop_arg_mark();
+                argmark.set_stack();
+            }
+
+            $( $($package)::+::perlmod_bootstrap(); )*
+        }
+    };
+}
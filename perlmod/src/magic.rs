@@ -163,7 +163,10 @@ impl<T: Leakable> MagicTag<T> {
                 Some(ptr) => {
                     let _drop = unsafe { T::reclaim(ptr) };
                 }
-                None => eprintln!("Default magic drop handler called but pointer was NULL"),
+                None => {
+                    // there is no one to report a dying warning handler to
+                    let _ = crate::warn("default magic drop handler called but pointer was NULL");
+                }
             }
             0
         }
@@ -0,0 +1,189 @@
+//! Deferring work from other threads to the perl interpreter's thread.
+//!
+//! Perl is not thread safe, so perl values must never be touched from threads other than the one
+//! running the interpreter. When rust code spawns threads (or runs an async runtime) and needs to
+//! hand results back to perl, a [`PerlCallbackQueue`] can be used to queue closures from any
+//! thread, which are then executed on the interpreter's thread by calling
+//! [`drain_on_interpreter_thread`](PerlCallbackQueue::drain_on_interpreter_thread()) from an
+//! xsub.
+//!
+//! Perl values can travel along with such work as [`SendValue`]s, which requires them to be
+//! exclusively owned by rust.
+//!
+//! ```
+//! #[perlmod::package(name = "RSPM::Doc::Queue", file = "/dev/null")]
+//! mod export {
+//!     use std::sync::OnceLock;
+//!
+//!     use perlmod::queue::{PerlCallbackQueue, SendValue};
+//!     use perlmod::{Callback, Error, Value};
+//!
+//!     static QUEUE: OnceLock<PerlCallbackQueue> = OnceLock::new();
+//!
+//!     /// Start a background computation, `callback` gets called with the result once `poll`
+//!     /// gets called after it finished.
+//!     #[export]
+//!     fn start(#[coderef] callback: Callback) -> Result<(), Error> {
+//!         let queue = QUEUE.get_or_init(PerlCallbackQueue::new).clone();
+//!         // perl still holds the reference passed to us, so create our own one
+//!         let code = callback.into_inner().dereference().unwrap();
+//!         let callback = SendValue::new(Value::new_ref(&code))
+//!             .map_err(|_| Error::new("reference to callback is shared"))?;
+//!         std::thread::spawn(move || {
+//!             let result = 6 * 7;
+//!             queue.push(move || {
+//!                 callback.into_value()?.call(&[Value::new_int(result)])?;
+//!                 Ok(())
+//!             });
+//!         });
+//!         Ok(())
+//!     }
+//!
+//!     /// Run the callbacks of all finished computations.
+//!     #[export]
+//!     fn poll() -> Result<usize, Error> {
+//!         match QUEUE.get() {
+//!             Some(queue) => queue.drain_on_interpreter_thread(),
+//!             None => Ok(0),
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+
+use crate::{Error, Value};
+
+type Job = Box<dyn FnOnce() -> Result<(), Error> + Send>;
+
+struct Inner {
+    thread: ThreadId,
+    jobs: Mutex<VecDeque<Job>>,
+}
+
+/// A queue of closures to be run on the perl interpreter's thread.
+///
+/// The queue is bound to the thread it was created on, which must be the interpreter's thread.
+/// Cloning the queue produces another handle to the same queue, which can be sent to other threads
+/// to [`push`](PerlCallbackQueue::push()) work onto it.
+#[derive(Clone)]
+pub struct PerlCallbackQueue(Arc<Inner>);
+
+impl PerlCallbackQueue {
+    /// Create a new queue bound to the current thread, which must be running the perl
+    /// interpreter, so this is typically called from an xsub.
+    pub fn new() -> Self {
+        Self(Arc::new(Inner {
+            thread: thread::current().id(),
+            jobs: Mutex::new(VecDeque::new()),
+        }))
+    }
+
+    /// Queue a closure to be executed on the interpreter's thread. This can be called from any
+    /// thread.
+    pub fn push<F>(&self, func: F)
+    where
+        F: FnOnce() -> Result<(), Error> + Send + 'static,
+    {
+        self.lock().push_back(Box::new(func));
+    }
+
+    /// Get the number of currently queued closures.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Check whether there are no queued closures.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Execute all queued closures in order, including ones queued while draining, and return
+    /// how many were executed.
+    ///
+    /// This must be called on the thread the queue was created on, otherwise an error is
+    /// returned without running anything. If a closure fails, its error is returned and the
+    /// remaining closures stay queued for the next call.
+    pub fn drain_on_interpreter_thread(&self) -> Result<usize, Error> {
+        if thread::current().id() != self.0.thread {
+            return Err(Error::new(
+                "callback queue drained outside of the perl interpreter's thread",
+            ));
+        }
+
+        let mut count = 0;
+        // don't hold the lock while running a job, it may queue more work
+        while let Some(job) = self.lock().pop_front() {
+            count += 1;
+            job()?;
+        }
+        Ok(count)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<Job>> {
+        // a job panicking does not leave the queue in an inconsistent state
+        self.0.jobs.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Default for PerlCallbackQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A perl value which can be sent to other threads, for instance to be passed back to the
+/// interpreter's thread via a [`PerlCallbackQueue`].
+///
+/// A value is only accepted if it has a reference count of 1, meaning rust holds the only
+/// reference to it, so no perl code can observe it while it is in flight. The value itself can
+/// only be accessed again on the thread it was created on. Should a `SendValue` be dropped on
+/// any other thread, the value is leaked instead of touching perl's reference counts.
+pub struct SendValue {
+    value: Option<Value>,
+    thread: ThreadId,
+}
+
+unsafe impl Send for SendValue {}
+
+impl SendValue {
+    /// Wrap a value with a reference count of 1, otherwise the value is returned as error.
+    ///
+    /// Note that for a [`Value::Reference`] this is the count of the reference itself, so the
+    /// reference must not be stored anywhere else, while the referenced value may still be shared
+    /// with perl. Such a value is not accessed by `SendValue` though until it is back on the
+    /// interpreter's thread.
+    pub fn new(value: Value) -> Result<Self, Value> {
+        if value.ref_count() != 1 {
+            return Err(value);
+        }
+
+        Ok(Self {
+            value: Some(value),
+            thread: thread::current().id(),
+        })
+    }
+
+    /// Get the value back. This fails if called on a thread other than the one the `SendValue`
+    /// was created on, in which case the value is leaked.
+    pub fn into_value(mut self) -> Result<Value, Error> {
+        if thread::current().id() != self.thread {
+            return Err(Error::new(
+                "perl value accessed outside of the perl interpreter's thread",
+            ));
+        }
+        Ok(self.value.take().unwrap())
+    }
+}
+
+impl Drop for SendValue {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            if thread::current().id() != self.thread {
+                std::mem::forget(value);
+            }
+        }
+    }
+}
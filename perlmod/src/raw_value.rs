@@ -13,6 +13,7 @@ pub(crate) const VALUE: &str = "$__perlmod_private_raw_value";
 
 thread_local!(static SERIALIZE_RAW: RefCell<bool> = RefCell::new(false));
 
+/// Restores the previous state when dropped, including when unwinding from a panic.
 pub(crate) struct RawGuard(bool);
 
 #[inline]
@@ -20,6 +21,12 @@ pub(crate) fn guarded(on: bool) -> RawGuard {
     SERIALIZE_RAW.with(move |raw| RawGuard(raw.replace(on)))
 }
 
+impl Drop for RawGuard {
+    fn drop(&mut self) {
+        SERIALIZE_RAW.with(|raw| *raw.borrow_mut() = self.0);
+    }
+}
+
 #[inline]
 pub(crate) fn is_enabled() -> bool {
     SERIALIZE_RAW.with(|raw| *raw.borrow())
@@ -51,6 +58,27 @@ impl RawValue {
     }
 }
 
+impl RawValue {
+    /// Get the raw value from the map passed by the perlmod deserializer in response to a raw
+    /// value request.
+    pub(crate) fn visit_raw<'de, V>(mut visitor: V) -> Result<Self, V::Error>
+    where
+        V: serde::de::MapAccess<'de>,
+    {
+        use serde::de::Error;
+
+        if visitor.next_key()? != Some(VALUE) {
+            return Err(Error::custom("raw value key not found"));
+        }
+
+        let sv: usize = visitor.next_value()?;
+
+        Ok(RawValue {
+            value: unsafe { Value::from_raw_ref(sv as *mut crate::ffi::SV) },
+        })
+    }
+}
+
 impl From<Value> for RawValue {
     fn from(value: Value) -> Self {
         Self { value }
@@ -76,7 +104,7 @@ impl<'de> Deserialize<'de> for RawValue {
     where
         D: serde::Deserializer<'de>,
     {
-        use serde::de::{Error, Visitor};
+        use serde::de::Visitor;
 
         struct V;
 
@@ -87,19 +115,11 @@ impl<'de> Deserialize<'de> for RawValue {
                 formatter.write_str("a raw perl value")
             }
 
-            fn visit_map<V>(self, mut visitor: V) -> Result<RawValue, V::Error>
+            fn visit_map<V>(self, visitor: V) -> Result<RawValue, V::Error>
             where
                 V: serde::de::MapAccess<'de>,
             {
-                if visitor.next_key()? != Some(VALUE) {
-                    return Err(Error::custom("raw value key not found"));
-                }
-
-                let sv: usize = visitor.next_value()?;
-
-                Ok(RawValue {
-                    value: unsafe { Value::from_raw_ref(sv as *mut crate::ffi::SV) },
-                })
+                RawValue::visit_raw(visitor)
             }
         }
 
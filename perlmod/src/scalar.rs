@@ -9,7 +9,7 @@ use crate::error::MagicError;
 use crate::ffi::{self, SV};
 use crate::magic::{Leakable, MagicSpec, MagicValue};
 use crate::raw_value;
-use crate::{Error, Value};
+use crate::{Array, Error, Hash, Value};
 
 /// An owned reference to a perl value.
 ///
@@ -112,6 +112,16 @@ impl Scalar {
         }
     }
 
+    /// Create a new latin-1 string, one character per byte.
+    ///
+    /// This is the same as [`new_bytes`](Scalar::new_bytes()) and never sets the UTF-8 flag, even
+    /// for bytes `>= 0x80`, but documents that the data is meant as text, for instance when
+    /// passing through latin-1 fields of binary protocols. For `str` data use
+    /// [`new_string`](Scalar::new_string()) instead.
+    pub fn new_latin1(s: &[u8]) -> Self {
+        Self::new_bytes(s)
+    }
+
     /// Convenience method to create a new raw pointer value. Note that pointers are stored as
     /// arbitrary "byte strings" and any such byte string value can be interpreted as a raw pointer.
     pub fn new_pointer<T>(s: *mut T) -> Self {
@@ -166,6 +176,28 @@ impl Scalar {
     }
 }
 
+impl From<Array> for Scalar {
+    fn from(array: Array) -> Self {
+        array.into_scalar()
+    }
+}
+
+impl From<Hash> for Scalar {
+    fn from(hash: Hash) -> Self {
+        hash.into_scalar()
+    }
+}
+
+impl From<Value> for Scalar {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Scalar(v) | Value::Reference(v) => v,
+            Value::Array(v) => v.into_scalar(),
+            Value::Hash(v) => v.into_scalar(),
+        }
+    }
+}
+
 impl Clone for Scalar {
     #[inline]
     fn clone(&self) -> Self {
@@ -249,6 +281,77 @@ pub enum Type {
     Other(u8),
 }
 
+/// Operators which can be invoked via [`ScalarRef::overload_op`].
+///
+/// NOTE: The values must be the same as in our c glue code!
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Overload {
+    /// `+`
+    Add = 0,
+    /// `-`
+    Subtract = 1,
+    /// `*`
+    Multiply = 2,
+    /// `/`
+    Divide = 3,
+    /// `%`
+    Modulo = 4,
+    /// `**`
+    Power = 5,
+    /// `.`
+    Concat = 6,
+    /// `<=>`
+    NumCompare = 7,
+    /// `cmp`
+    StrCompare = 8,
+    /// `==`
+    NumEq = 9,
+    /// `eq`
+    StrEq = 10,
+    /// `""` (unary)
+    String = 11,
+    /// `0+` (unary)
+    Numify = 12,
+    /// `bool` (unary)
+    Bool = 13,
+    /// `neg` (unary)
+    Negate = 14,
+    /// `abs` (unary)
+    Abs = 15,
+}
+
+impl Overload {
+    /// Get the operator's key as used by `use overload`.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Overload::Add => "+",
+            Overload::Subtract => "-",
+            Overload::Multiply => "*",
+            Overload::Divide => "/",
+            Overload::Modulo => "%",
+            Overload::Power => "**",
+            Overload::Concat => ".",
+            Overload::NumCompare => "<=>",
+            Overload::StrCompare => "cmp",
+            Overload::NumEq => "==",
+            Overload::StrEq => "eq",
+            Overload::String => "\"\"",
+            Overload::Numify => "0+",
+            Overload::Bool => "bool",
+            Overload::Negate => "neg",
+            Overload::Abs => "abs",
+        }
+    }
+
+    /// Check whether this operator takes only a single operand.
+    pub fn is_unary(self) -> bool {
+        matches!(
+            self,
+            Overload::String | Overload::Numify | Overload::Bool | Overload::Negate | Overload::Abs
+        )
+    }
+}
+
 impl ScalarRef {
     pub(crate) fn sv(&self) -> *mut SV {
         self as *const ScalarRef as *const SV as *mut SV
@@ -347,6 +450,81 @@ impl ScalarRef {
         }
     }
 
+    /// Get mutable access to the string buffer of a value which is not referenced anywhere else,
+    /// turning it into a plain string first. (perlxs `SvPV_force`)
+    ///
+    /// This allows editing data in place without creating a new value, for instance in a codec
+    /// working on a freshly created buffer. `None` is returned if the value's reference count is
+    /// not 1 (including values still held by a perl variable), or if it is read-only, magical,
+    /// a reference or an aggregate. Since editing UTF-8 data could leave invalid UTF-8 behind,
+    /// strings with the UTF-8 flag are rejected as well, see
+    /// [`pv_utf8_to_bytes`](ScalarRef::pv_utf8_to_bytes()) to downgrade them first.
+    pub fn bytes_mut(&mut self) -> Option<&mut [u8]> {
+        unsafe {
+            let mut len: libc::size_t = 0;
+            let ptr = ffi::RSPL_SvPV_force_unique(self.sv(), &mut len) as *mut u8;
+            if ptr.is_null() {
+                return None;
+            }
+            Some(std::slice::from_raw_parts_mut(ptr, len))
+        }
+    }
+
+    /// Get the hash perl uses for this value's string form as a hash key. (perlxs `PERL_HASH`)
+    ///
+    /// This can be used to distribute keys into the same buckets perl would. Note that the hash
+    /// seed is chosen per interpreter and usually randomized at startup, so the result is neither
+    /// stable across processes nor suitable for persisting.
+    pub fn string_hash(&self) -> u32 {
+        unsafe { ffi::RSPL_perl_hash(self.sv()) }
+    }
+
+    /// Check whether the string's UTF-8 flag is set. (perlxs `SvUTF8`)
+    pub fn is_utf8(&self) -> bool {
+        unsafe { ffi::RSPL_SvUTF8(self.sv()) }
+    }
+
+    /// Set or clear the string's UTF-8 flag without modifying its contents. (perlxs `SvUTF8_on`,
+    /// `SvUTF8_off`)
+    ///
+    /// This is useful for strings which are known to contain UTF-8 data where perl did not set
+    /// the flag. Enabling the flag fails if the contained bytes are not valid UTF-8.
+    pub fn set_utf8(&self, on: bool) -> Result<(), Error> {
+        if !on {
+            unsafe { ffi::RSPL_SvUTF8_off(self.sv()) };
+        } else if !self.is_utf8() {
+            if std::str::from_utf8(self.pv_bytes()).is_err() {
+                return Err(Error::new("string is not valid utf-8"));
+            }
+            unsafe { ffi::RSPL_SvUTF8_on(self.sv()) };
+        }
+        Ok(())
+    }
+
+    /// Get the key bytes perl would use when storing this value as a hash key, along with whether
+    /// they are UTF-8 encoded.
+    ///
+    /// Like `hv_store_ent`, UTF-8 strings which only contain code points below 256 are downgraded
+    /// to latin-1 bytes, so `"\x{e9}"` yields the same key regardless of its UTF-8 flag, while the
+    /// UTF-8 encoded bytes of a string without the flag are used as they are. Contrary to
+    /// [`pv_utf8_to_bytes`](ScalarRef::pv_utf8_to_bytes()), the value itself is not downgraded.
+    pub fn hash_key(&self) -> (Vec<u8>, bool) {
+        let bytes = self.pv_bytes();
+        if !self.is_utf8() {
+            return (bytes.to_vec(), false);
+        }
+
+        let s = match std::str::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(_) => return (bytes.to_vec(), true),
+        };
+
+        match s.chars().map(|c| u8::try_from(u32::from(c))).collect() {
+            Ok(latin1) => (latin1, false),
+            Err(_) => (bytes.to_vec(), true),
+        }
+    }
+
     /// Coerce to a byte-string, downgrading from utf-8. (perlxs `SvPVbyte`)
     ///
     /// May fail if there are values which don't fit into bytes in the contained utf-8 string, in
@@ -397,11 +575,103 @@ impl ScalarRef {
         unsafe { Scalar::from_raw_ref(self.sv()) }
     }
 
+    /// Get the current reference count of this value. (perlxs `SvREFCNT`).
+    ///
+    /// This is mostly useful for debugging. Note that the [`Scalar`] (or other owned type) this is
+    /// accessed through holds a reference itself, and so does every temporary created via
+    /// [`dereference`](ScalarRef::dereference()).
+    ///
+    /// The count is only a snapshot: any call into the perl API (including calling perl code or
+    /// freeing temporaries) may change it, so it must not be used to make ownership decisions.
+    pub fn refcnt(&self) -> usize {
+        unsafe { ffi::RSPL_SvREFCNT(self.sv()) as usize }
+    }
+
     /// Convenience check for `SVt_NULL`
     pub fn is_undef(&self) -> bool {
         0 == unsafe { ffi::RSPL_type_flags(self.sv()) }
     }
 
+    /// Check whether this value is true in perl's boolean context, like `if ($value)` would for
+    /// values which are not references.
+    ///
+    /// Note that unlike [`is_undef`](ScalarRef::is_undef()), this is also false for defined
+    /// values such as `0`, `"0"` or the empty string.
+    ///
+    /// Unlike in perl, references are always true, even for objects overloading `bool` (or `""`
+    /// or `0+`), since such an overload method could die without a way to report the error here.
+    /// Use [`overload_op`](ScalarRef::overload_op()) with [`Overload::Bool`] to honour these.
+    pub fn is_true(&self) -> bool {
+        unsafe { ffi::RSPL_is_reference(self.sv()) || ffi::RSPL_SvTRUE(self.sv()) }
+    }
+
+    /// Trigger the value's get magic, such as the `FETCH` method of a tied scalar, which updates
+    /// the value's contents. (perlxs `SvGETMAGIC`)
+    ///
+    /// The coercion methods such as [`pv_string_utf8`](ScalarRef::pv_string_utf8()) or
+    /// [`iv`](ScalarRef::iv()) already do this, but direct access to the value's contents, for
+    /// instance via [`as_raw`](ScalarRef::as_raw()) and the raw [`ffi`](crate::ffi) functions,
+    /// sees the value from before the last trigger. Note that every call invokes the magic again.
+    pub fn get_magic(&self) {
+        unsafe { ffi::RSPL_SvGETMAGIC(self.sv()) }
+    }
+
+    /// Trigger the value's set magic, such as the `STORE` method of a tied scalar, to pass on
+    /// the value's current contents. (perlxs `SvSETMAGIC`)
+    ///
+    /// This is needed after modifying a value without going through a method that already does
+    /// this, such as [`set_undef`](ScalarRef::set_undef()), for instance after changing its
+    /// contents via the raw [`ffi`](crate::ffi) functions or [`set_utf8`](ScalarRef::set_utf8()).
+    pub fn set_magic(&self) {
+        unsafe { ffi::RSPL_SvSETMAGIC(self.sv()) }
+    }
+
+    /// Set this value to an integer in place, triggering its set magic.
+    ///
+    /// Like all setters, this modifies the SV itself, so values aliasing it, such as array
+    /// elements retrieved via [`Array::element`](crate::Array::element()) or references to it,
+    /// see the change.
+    pub fn set_int(&self, v: isize) {
+        unsafe { ffi::RSPL_sv_setiv_mg(self.sv(), v) }
+    }
+
+    /// Set this value to an unsigned integer in place, triggering its set magic.
+    pub fn set_uint(&self, v: usize) {
+        unsafe { ffi::RSPL_sv_setuv_mg(self.sv(), v) }
+    }
+
+    /// Set this value to a floating point number in place, triggering its set magic.
+    pub fn set_float(&self, v: f64) {
+        unsafe { ffi::RSPL_sv_setnv_mg(self.sv(), v) }
+    }
+
+    /// Set this value to a string in place, triggering its set magic.
+    ///
+    /// As with [`Scalar::new_string`], the UTF-8 flag is only enabled for non-ASCII strings.
+    pub fn set_string(&self, s: &str) {
+        let utf8 = s.as_bytes().iter().any(|&b| b >= 0x80);
+        unsafe { ffi::RSPL_sv_setpvn_mg(self.sv(), s.as_ptr().cast(), s.len(), utf8) }
+    }
+
+    /// Set this value to a byte string in place, triggering its set magic.
+    pub fn set_bytes(&self, s: &[u8]) {
+        unsafe { ffi::RSPL_sv_setpvn_mg(self.sv(), s.as_ptr().cast(), s.len(), false) }
+    }
+
+    /// Copy the contents of another value into this one in place, like an assignment
+    /// `$this = $other` in perl, triggering its set magic.
+    pub fn set(&self, other: &ScalarRef) {
+        unsafe { ffi::RSPL_sv_setsv_mg(self.sv(), other.sv()) }
+    }
+
+    /// Set this value to `undef` in place, like `undef $value` in perl.
+    ///
+    /// Contrary to replacing a [`Value`], this keeps the SV itself, so references to it (such as
+    /// output parameters passed as `\$out`) see the change.
+    pub fn set_undef(&self) {
+        unsafe { ffi::RSPL_sv_set_undef(self.sv()) }
+    }
+
     // FIXME: self consuming on a phantom type... this can probably not be useful
     /// Turn this into a [`Value`].
     pub fn into_value(self) -> Value {
@@ -426,6 +696,43 @@ impl ScalarRef {
         }
     }
 
+    /// Check whether this is a reference to a blessed value. (perlxs `sv_isobject`)
+    pub fn is_blessed(&self) -> bool {
+        unsafe { ffi::RSPL_sv_isobject(self.sv()) }
+    }
+
+    /// Get the package the value this references is blessed into, or `None` if this is not a
+    /// reference to a blessed value. (Similar to `Scalar::Util::blessed` in perl).
+    ///
+    /// Contrary to [`reftype`](ScalarRef::reftype()), this never returns the type of unblessed
+    /// values, even if a package has the same name as a type, such as `"ARRAY"`.
+    pub fn blessed_class(&self) -> Option<String> {
+        if !self.is_blessed() {
+            return None;
+        }
+        let inner = self.dereference()?;
+        Some(inner.reftype(true).to_string())
+    }
+
+    /// Get the name of this value's perl type, such as `"SCALAR"`, `"GLOB"`, `"CODE"`,
+    /// `"LVALUE"`, `"FORMAT"` or `"IO"`, mostly useful for diagnostics.
+    ///
+    /// Unlike [`reftype`](ScalarRef::reftype()) this describes the value itself rather than what
+    /// it references (for a reference this returns `"REF"`).
+    pub fn svtype_name(&self) -> &'static str {
+        let ptr = unsafe { ffi::RSPL_sv_reftype(self.sv(), 0) };
+
+        if ptr.is_null() {
+            "<UNKNOWN>"
+        } else {
+            unsafe {
+                std::ffi::CStr::from_ptr(ptr)
+                    .to_str()
+                    .unwrap_or("<UNKNOWN>")
+            }
+        }
+    }
+
     /// Check whether this value is a substring.
     pub fn is_substr(&self) -> bool {
         unsafe {
@@ -437,6 +744,37 @@ impl ScalarRef {
         }
     }
 
+    /// Invoke the overloaded operator `op` with this value as the left and `other` as the right
+    /// operand. (perlxs `amagic_call`)
+    ///
+    /// Perl's overload rules apply, so the method may also come from `other` (with swapped
+    /// operands) or be derived via `fallback`. Unary operators must not be passed an `other`
+    /// value, while binary operators require one. Fails if neither operand overloads the
+    /// operator, or if the overload method dies.
+    pub fn overload_op(&self, other: Option<&ScalarRef>, op: Overload) -> Result<Value, Error> {
+        if op.is_unary() && other.is_some() {
+            return Err(Error(format!(
+                "overloaded operator '{}' is unary",
+                op.symbol()
+            )));
+        }
+        if !op.is_unary() && other.is_none() {
+            return Err(Error(format!(
+                "overloaded operator '{}' requires a right operand",
+                op.symbol()
+            )));
+        }
+
+        let right = other.map(ScalarRef::sv).unwrap_or(std::ptr::null_mut());
+        let mut error: *mut SV = std::ptr::null_mut();
+        let result =
+            unsafe { ffi::RSPL_amagic_call(self.sv(), right, op as libc::c_int, &mut error) };
+        crate::value::call_result(result, error)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error(format!("operator '{}' is not overloaded", op.symbol())))
+    }
+
     /// Create a substring from a string.
     pub fn substr<I>(&self, index: I) -> Result<Scalar, Error>
     where
@@ -596,6 +934,78 @@ impl ScalarRef {
         this
     }
 
+    /// Check whether this value is a number or a string perl would accept as one without a warning,
+    /// like `Scalar::Util::looks_like_number`.
+    ///
+    /// Contrary to checking the value's type flags, this also recognizes strings such as `"42"`
+    /// which were never used as a number, and contrary to calling [`iv`](ScalarRef::iv()) or
+    /// [`nv`](ScalarRef::nv()) it does not modify the value.
+    pub fn looks_like_number(&self) -> bool {
+        unsafe { ffi::RSPL_looks_like_number(self.sv()) }
+    }
+
+    /// Create a new value containing only the numeric form of this value, like perl's `0 + $x`.
+    ///
+    /// Integral values become integers, anything else a floating point number. Contrary to a copy,
+    /// the result carries no string form, so a dualvar such as `$!` loses its message.
+    pub fn to_number(&self) -> Scalar {
+        unsafe { Scalar::from_raw_move(ffi::RSPL_sv_to_number(self.sv())) }
+    }
+
+    /// Create a new value containing only the string form of this value, like perl's `"$x"`.
+    ///
+    /// Contrary to a copy, the result carries no numeric form, so a dualvar such as `$!` loses its
+    /// number and string comparisons see exactly the value's string.
+    pub fn to_string_scalar(&self) -> Scalar {
+        unsafe { Scalar::from_raw_move(ffi::RSPL_sv_to_string(self.sv())) }
+    }
+
+    /// Compare this value to another as strings, like perl's `cmp` operator.
+    ///
+    /// If either value is an object overloading `cmp`, the overload method is called, which may
+    /// fail.
+    pub fn str_cmp(&self, other: &ScalarRef) -> Result<std::cmp::Ordering, Error> {
+        match self.overloaded(other, Overload::StrCompare)? {
+            Some(order) => Ok(order.iv().cmp(&0)),
+            None => Ok(unsafe { ffi::RSPL_sv_cmp(self.sv(), other.sv()) }.cmp(&0)),
+        }
+    }
+
+    /// Compare this value to another as numbers, like perl's `==` operator.
+    ///
+    /// Integers are compared as integers, so large 64 bit values do not lose precision, and `NaN`
+    /// is never equal to anything. If either value is an object overloading `==` (or one of the
+    /// operators perl derives it from), the overload method is called, which may fail.
+    pub fn numeric_eq(&self, other: &ScalarRef) -> Result<bool, Error> {
+        match self.overloaded(other, Overload::NumEq)? {
+            Some(equal) => Ok(equal.is_true()),
+            None => Ok(unsafe { ffi::RSPL_sv_num_eq(self.sv(), other.sv()) }),
+        }
+    }
+
+    /// Compare this value to another as strings, like perl's `eq` operator.
+    ///
+    /// If either value is an object overloading `eq` (or one of the operators perl derives it
+    /// from), the overload method is called, which may fail.
+    pub fn string_eq(&self, other: &ScalarRef) -> Result<bool, Error> {
+        match self.overloaded(other, Overload::StrEq)? {
+            Some(equal) => Ok(equal.is_true()),
+            None => Ok(unsafe { ffi::RSPL_sv_str_eq(self.sv(), other.sv()) }),
+        }
+    }
+
+    /// Call an overloaded comparison operator, if either value has one.
+    fn overloaded(&self, other: &ScalarRef, op: Overload) -> Result<Option<Value>, Error> {
+        if unsafe { !ffi::RSPL_SvAMAGIC(self.sv()) && !ffi::RSPL_SvAMAGIC(other.sv()) } {
+            return Ok(None);
+        }
+
+        let mut error: *mut SV = std::ptr::null_mut();
+        let result =
+            unsafe { ffi::RSPL_amagic_call(self.sv(), other.sv(), op as libc::c_int, &mut error) };
+        Ok(crate::value::call_result(result, error)?.into_iter().next())
+    }
+
     /// Merges a `Cow<str>` with this value.
     ///
     /// Note that the `Cow` part is not required here.
@@ -635,7 +1045,7 @@ impl std::fmt::Debug for ScalarRef {
             Type::Reference => write!(f, "<*REFERENCE>"),
             Type::Array => write!(f, "<*ARRAY>"),
             Type::Hash => write!(f, "<*HASH>"),
-            Type::Other(_) => write!(f, "<*PERLTYPE>"),
+            Type::Other(_) => write!(f, "<*{}>", self.svtype_name()),
         }
     }
 }
@@ -665,16 +1075,13 @@ impl serde::Serialize for Scalar {
                     serializer.serialize_unit()
                 }
             }
-            Type::Other(other) => Err(S::Error::custom(format!(
-                "cannot serialize weird magic perl values ({other})",
+            Type::Other(_) => Err(S::Error::custom(format!(
+                "cannot serialize weird magic perl values ({})",
+                self.svtype_name(),
             ))),
 
             // These are impossible as they are all handled by different Value enum types:
-            Type::Reference => Value::from(
-                self.dereference()
-                    .ok_or_else(|| S::Error::custom("failed to dereference perl value"))?,
-            )
-            .serialize(serializer),
+            Type::Reference => crate::value::serialize_referenced(self, serializer),
             Type::Array => {
                 let this = unsafe { crate::Array::from_raw_ref(self.sv() as *mut ffi::AV) };
                 this.serialize(serializer)
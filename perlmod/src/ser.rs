@@ -1,13 +1,23 @@
 //! Serde serializer for perl values.
 
+use std::cell::RefCell;
+
 use serde::{ser, Serialize};
 
 use crate::error::Error;
-use crate::Value;
+use crate::scalar::{Flags, Type};
 use crate::{array, hash, raw_value};
+use crate::{Scalar, Value};
 
 /// Perl [`Value`](crate::Value) serializer.
-struct Serializer;
+///
+/// This is copied into the helpers serializing nested values, so its settings apply to the whole
+/// value, but not to other values serialized from within `Serialize` implementations.
+#[derive(Clone, Copy, Default)]
+struct Serializer {
+    /// Serialize empty sequences as `undef`, see [`to_value_empty_as_undef`].
+    empty_as_undef: bool,
+}
 
 /// Check if the `perlmod::Serializer` is currently being used for serialization.
 ///
@@ -17,16 +27,286 @@ pub fn is_active() -> bool {
     raw_value::is_enabled()
 }
 
+/// Produce a substring of one of the `sources` covering `text`.
+fn substr_from_str_sources(sources: &[&Value], text: &str) -> Option<Scalar> {
+    sources.iter().find_map(|source| match source.ty() {
+        Type::Scalar(flags) if flags.contains(Flags::STRING) => {
+            Scalar::substr_from_str_slice(source, text)
+        }
+        _ => None,
+    })
+}
+
 /// Serialize data into a perl [`Value`](crate::Value).
 ///
 /// Note that in theory it should be safe to send such values to different threads as long as their
 /// reference count is exactly one.
+///
+/// Integers which fit into neither a signed nor an unsigned perl integer (eg. 64 bit values on 32
+/// bit builds, or large `i128`/`u128` values) are serialized as decimal strings rather than being
+/// truncated, and floating point values are kept as they are. The deserializer accepts such
+/// strings, but only those out of perl's integer range, for 64 and 128 bit integers, so these
+/// round-trip losslessly. For timestamps, the recommended
+/// representation is therefore an integer such as `u64` nanoseconds since the unix epoch, or
+/// an `f64` of seconds if fractional precision beyond the microsecond is not required.
+///
+/// `f32` and `f64` values, including NaN and the infinities, always become numeric perl scalars
+/// (NVs), never strings. In perl these stringify as `NaN`, `Inf` and `-Inf` respectively, and
+/// [`from_value`](crate::from_value) turns them back into the same `f32` or `f64` value.
+///
+/// Byte data serialized via `serialize_bytes`, such as `Vec<u8>` fields using
+/// `#[serde(with = "serde_bytes")]`, becomes a single (non-UTF-8) byte string, rather than the
+/// array of integers a plain `Vec<u8>` produces.
 pub fn to_value<T>(value: &T) -> Result<Value, Error>
 where
     T: Serialize,
 {
     let _guard = raw_value::guarded(true);
-    value.serialize(&mut Serializer)
+    value.serialize(&mut Serializer::default())
+}
+
+/// Serialize data into a perl [`Value`](crate::Value) like [`to_value`], but turn empty
+/// sequences such as an empty `Vec` into `undef` instead of a reference to an empty array.
+///
+/// This applies to all sequences within the data, for perl APIs which expect "no values" to be
+/// passed as `undef`. Note that this is lossy, as `None` and an empty list can no longer be told
+/// apart. Tuples and tuple structs are not affected.
+pub fn to_value_empty_as_undef<T>(value: &T) -> Result<Value, Error>
+where
+    T: Serialize,
+{
+    let _guard = raw_value::guarded(true);
+    value.serialize(&mut Serializer {
+        empty_as_undef: true,
+    })
+}
+
+/// Serialize the return value of an `#[export]` function.
+///
+/// This is like [`to_value`], except that a tuple struct (or a newtype struct wrapping one) is
+/// serialized into a plain array instead of an array reference, to be returned as a list like a
+/// tuple would be.
+#[doc(hidden)]
+pub fn to_return_value<T>(value: &T) -> Result<Value, Error>
+where
+    T: Serialize,
+{
+    to_return_value_with(value, false, &[])
+}
+
+/// Serialize the return value of an `#[export(empty_as_undef)]` function, like
+/// [`to_return_value`] combined with [`to_value_empty_as_undef`].
+#[doc(hidden)]
+pub fn to_return_value_empty_as_undef<T>(value: &T) -> Result<Value, Error>
+where
+    T: Serialize,
+{
+    to_return_value_with(value, true, &[])
+}
+
+/// Serialize the return value of an `#[export]` function like [`to_return_value`], optionally
+/// with empty sequences turned into `undef`, see [`to_value_empty_as_undef`].
+///
+/// A returned string slice borrowed from one of the `str_sources` becomes a substring of it
+/// instead of a copy, see [`ScalarRef::merge_str_slice`](crate::ScalarRef::merge_str_slice).
+/// This is used by `#[export(substr_return)]` functions with `&str` parameters, so that returning
+/// a `&str` or `Cow<str>` borrowed from one of them does not need to allocate a new string.
+#[doc(hidden)]
+pub fn to_return_value_with<T>(
+    value: &T,
+    empty_as_undef: bool,
+    str_sources: &[&Value],
+) -> Result<Value, Error>
+where
+    T: Serialize,
+{
+    let _guard = raw_value::guarded(true);
+    value.serialize(ReturnValueSerializer {
+        ser: Serializer { empty_as_undef },
+        str_sources,
+    })
+}
+
+/// A list of values to return from an [`#[export]`](macro@crate::export) function, built at
+/// runtime.
+///
+/// Like a tuple, this is returned to perl as a list, but its length can depend on runtime logic.
+/// The values are passed on to perl as they are. As with any list returned from an xsub, perl
+/// uses the last value in scalar context (or `undef` if the list is empty), and discards the list
+/// in void context. Use [`gimme`](crate::ffi::gimme()) to check the context up front to avoid
+/// building values which are not used.
+///
+/// Nested inside other values, this is serialized as an array reference instead.
+///
+/// ```
+/// #[perlmod::package(name = "RSPM::Doc::ReturnList", file = "/dev/null")]
+/// mod export {
+///     use perlmod::{ReturnList, Value};
+///
+///     #[export]
+///     fn divisors(number: u64) -> ReturnList {
+///         let mut list = ReturnList::new();
+///         for divisor in (1..=number).filter(|d| number % d == 0) {
+///             list.push(Value::from(divisor));
+///         }
+///         list
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ReturnList(Vec<Value>);
+
+impl ReturnList {
+    /// Create an empty list.
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Append a value to the list.
+    pub fn push(&mut self, value: Value) {
+        self.0.push(value);
+    }
+
+    /// Get the number of values in the list.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Check whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Get the contained values.
+    pub fn into_inner(self) -> Vec<Value> {
+        self.0
+    }
+}
+
+impl From<Vec<Value>> for ReturnList {
+    fn from(values: Vec<Value>) -> Self {
+        Self(values)
+    }
+}
+
+impl Serialize for ReturnList {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeTupleStruct;
+
+        // top level tuple structs are returned as a list, see `to_return_value`
+        let mut list = serializer.serialize_tuple_struct("ReturnList", self.0.len())?;
+        for value in &self.0 {
+            list.serialize_field(value)?;
+        }
+        list.end()
+    }
+}
+
+/// Return `values` as a list from an [`#[export]`](macro@crate::export) function, see
+/// [`ReturnList`].
+pub fn return_list(values: Vec<Value>) -> ReturnList {
+    ReturnList::from(values)
+}
+
+/// Marks a [`LazyList`] for [`to_return_value`].
+const LAZY_LIST: &str = "$__perlmod_private_LazyList";
+
+/// An iterator to return from an [`#[export]`](macro@crate::export) function, which is only
+/// materialized into a list if the function is called in list context.
+///
+/// In scalar context, the number of items is returned instead, like for a perl array, without
+/// serializing the items. Note that the iterator still has to be run to count them. Nested
+/// inside other values, this is serialized as an array reference regardless of the context.
+///
+/// ```
+/// #[perlmod::package(name = "RSPM::Doc::LazyList", file = "/dev/null")]
+/// mod export {
+///     use perlmod::LazyList;
+///
+///     #[export]
+///     fn squares(count: u64) -> LazyList<impl Iterator<Item = u64>> {
+///         LazyList::new((0..count).map(|i| i * i))
+///     }
+/// }
+/// ```
+pub struct LazyList<I>(RefCell<Option<I>>);
+
+impl<I> LazyList<I> {
+    /// Wrap an iterator.
+    pub fn new(iter: I) -> Self {
+        Self(RefCell::new(Some(iter)))
+    }
+}
+
+impl<I> Serialize for LazyList<I>
+where
+    I: Iterator,
+    I::Item: Serialize,
+{
+    /// Note that this consumes the iterator, so it can only be serialized once.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::{Error, SerializeTupleStruct};
+
+        let iter = self
+            .0
+            .borrow_mut()
+            .take()
+            .ok_or_else(|| S::Error::custom("lazy list was already serialized"))?;
+
+        let (len, _) = iter.size_hint();
+        let mut list = serializer.serialize_tuple_struct(LAZY_LIST, len)?;
+        for item in iter {
+            list.serialize_field(&item)?;
+        }
+        list.end()
+    }
+}
+
+/// Serialize key/value pairs returned from an `#[export(map_as_list)]` function: as a hash
+/// reference, or, if `flat` is set, as an array (not a reference) of the keys and values in their
+/// original order, to be pushed onto the stack via [`stack_push_list`](crate::ffi::stack_push_list).
+#[doc(hidden)]
+pub fn pairs_to_value<K, V>(pairs: &[(K, V)], flat: bool) -> Result<Value, Error>
+where
+    K: Serialize,
+    V: Serialize,
+{
+    if flat {
+        let array = array::Array::new();
+        array.reserve(2 * pairs.len());
+        for (key, value) in pairs {
+            array.push(to_value(key)?);
+            array.push(to_value(value)?);
+        }
+        Ok(Value::Array(array))
+    } else {
+        let hash = hash::Hash::with_capacity(pairs.len());
+        for (key, value) in pairs {
+            hash.insert_by_value(&to_value(key)?, to_value(value)?);
+        }
+        Ok(Value::new_ref(&hash))
+    }
+}
+
+/// Serialize data into a perl [`Value`](crate::Value) and bless it into a package.
+///
+/// The data must serialize into a reference (eg. a struct, map or sequence). Like with
+/// [`Value::bless`](crate::Value::bless()), the package must already exist.
+pub fn to_blessed_value<T>(value: &T, package: &str) -> Result<Value, Error>
+where
+    T: Serialize,
+{
+    let value = to_value(value)?;
+    if !matches!(value, Value::Reference(_)) {
+        return Error::fail("only references can be blessed");
+    }
+    value.bless(package)
 }
 
 enum SerHashMode {
@@ -38,11 +318,13 @@ enum SerHashMode {
 struct SerHash {
     mode: SerHashMode,
     key: Option<Value>,
+    ser: Serializer,
 }
 
 /// Serde sequence serialization helper.
 struct SerArray {
     array: array::Array,
+    ser: Serializer,
 }
 
 /// Serde variant serialization helper.
@@ -64,7 +346,7 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     type SerializeStructVariant = SerVariant<SerHash>;
 
     fn serialize_bool(self, v: bool) -> Result<Value, Error> {
-        Ok(Value::new_uint(usize::from(v)))
+        Ok(Value::bool_value(v))
     }
 
     fn serialize_i8(self, v: i8) -> Result<Value, Error> {
@@ -80,7 +362,23 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     }
 
     fn serialize_i64(self, v: i64) -> Result<Value, Error> {
-        Ok(Value::new_int(v as isize))
+        if let Ok(v) = isize::try_from(v) {
+            Ok(Value::new_int(v))
+        } else if let Ok(v) = usize::try_from(v) {
+            Ok(Value::new_uint(v))
+        } else {
+            Ok(Value::new_string(&v.to_string()))
+        }
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, Error> {
+        if let Ok(v) = i64::try_from(v) {
+            self.serialize_i64(v)
+        } else if let Ok(v) = u64::try_from(v) {
+            self.serialize_u64(v)
+        } else {
+            Ok(Value::new_string(&v.to_string()))
+        }
     }
 
     fn serialize_u8(self, v: u8) -> Result<Value, Error> {
@@ -96,7 +394,17 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     }
 
     fn serialize_u64(self, v: u64) -> Result<Value, Error> {
-        Ok(Value::new_uint(v as usize))
+        match usize::try_from(v) {
+            Ok(v) => Ok(Value::new_uint(v)),
+            Err(_) => Ok(Value::new_string(&v.to_string())),
+        }
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value, Error> {
+        match u64::try_from(v) {
+            Ok(v) => self.serialize_u64(v),
+            Err(_) => Ok(Value::new_string(&v.to_string())),
+        }
     }
 
     fn serialize_f32(self, v: f32) -> Result<Value, Error> {
@@ -104,6 +412,10 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     }
 
     fn serialize_f64(self, v: f64) -> Result<Value, Error> {
+        // don't leak the sign or payload of a NaN into perl
+        if v.is_nan() {
+            return Ok(Value::new_float(f64::NAN));
+        }
         Ok(Value::new_float(v))
     }
 
@@ -164,14 +476,14 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        let value = value.serialize(&mut Serializer)?;
+        let value = value.serialize(self)?;
         let hash = hash::Hash::new();
         hash.insert(variant, value);
         Ok(Value::from(hash))
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
-        Ok(SerArray::new(len))
+        Ok(SerArray::new(len, *self))
     }
 
     fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Error> {
@@ -193,11 +505,11 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleVariant, Error> {
-        Ok(SerVariant::<SerArray>::new(variant, Some(len)))
+        Ok(SerVariant::<SerArray>::new(variant, Some(len), *self))
     }
 
-    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
-        Ok(SerHash::new())
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Ok(SerHash::new(len, *self))
     }
 
     fn serialize_struct(
@@ -206,9 +518,9 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         len: usize,
     ) -> Result<Self::SerializeStruct, Error> {
         if raw_value::is_enabled() && name == raw_value::NAME && len == 1 {
-            Ok(SerHash::raw())
+            Ok(SerHash::raw(*self))
         } else {
-            Ok(SerHash::new())
+            Ok(SerHash::new(Some(len), *self))
         }
     }
 
@@ -217,19 +529,19 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         _name: &'static str,
         _variant_index: u32,
         variant: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeStructVariant, Error> {
-        Ok(SerVariant::<SerHash>::new(variant))
+        Ok(SerVariant::<SerHash>::new(variant, len, *self))
     }
 }
 
 impl SerArray {
-    fn new(len: Option<usize>) -> Self {
+    fn new(len: Option<usize>, ser: Serializer) -> Self {
         let array = array::Array::new();
         if let Some(len) = len {
             array.reserve(len);
         }
-        Self { array }
+        Self { array, ser }
     }
 }
 
@@ -241,11 +553,14 @@ impl ser::SerializeSeq for SerArray {
     where
         T: ?Sized + Serialize,
     {
-        self.array.push(value.serialize(&mut Serializer)?);
+        self.array.push(value.serialize(&mut self.ser)?);
         Ok(())
     }
 
     fn end(self) -> Result<Value, Error> {
+        if self.array.is_empty() && self.ser.empty_as_undef {
+            return Ok(Value::new_undef());
+        }
         Ok(Value::new_ref(&self.array))
     }
 }
@@ -258,7 +573,7 @@ impl ser::SerializeTuple for SerArray {
     where
         T: ?Sized + Serialize,
     {
-        self.array.push(value.serialize(&mut Serializer)?);
+        self.array.push(value.serialize(&mut self.ser)?);
         Ok(())
     }
 
@@ -275,7 +590,7 @@ impl ser::SerializeTupleStruct for SerArray {
     where
         T: ?Sized + Serialize,
     {
-        self.array.push(value.serialize(&mut Serializer)?);
+        self.array.push(value.serialize(&mut self.ser)?);
         Ok(())
     }
 
@@ -285,17 +600,23 @@ impl ser::SerializeTupleStruct for SerArray {
 }
 
 impl SerHash {
-    fn new() -> Self {
+    fn new(len: Option<usize>, ser: Serializer) -> Self {
+        let hash = match len {
+            Some(len) => hash::Hash::with_capacity(len),
+            None => hash::Hash::new(),
+        };
         Self {
-            mode: SerHashMode::Hash(hash::Hash::new()),
+            mode: SerHashMode::Hash(hash),
             key: None,
+            ser,
         }
     }
 
-    fn raw() -> Self {
+    fn raw(ser: Serializer) -> Self {
         Self {
             mode: SerHashMode::Raw(None),
             key: None,
+            ser,
         }
     }
 
@@ -318,7 +639,7 @@ impl ser::SerializeMap for SerHash {
         if self.key.is_some() {
             Error::fail("serialize_key called twice")
         } else {
-            self.key = Some(value.serialize(&mut Serializer)?);
+            self.key = Some(value.serialize(&mut self.ser)?);
             Ok(())
         }
     }
@@ -330,7 +651,7 @@ impl ser::SerializeMap for SerHash {
         match self.key.take() {
             None => Error::fail("serialize_value called without key"),
             Some(key) => {
-                let value = value.serialize(&mut Serializer)?;
+                let value = value.serialize(&mut self.ser)?;
                 self.as_mut_hash()
                     .ok_or_else(|| Error::new("serialize_value called in raw perl value context"))?
                     .insert_by_value(&key, value);
@@ -351,6 +672,187 @@ impl ser::SerializeMap for SerHash {
     }
 }
 
+/// Serializer for [`to_return_value`], forwarding everything but tuple structs to [`Serializer`].
+struct ReturnValueSerializer<'a> {
+    ser: Serializer,
+    /// Values a returned string may be a substring of, see [`to_return_value_with`].
+    str_sources: &'a [&'a Value],
+}
+
+/// Top level tuple struct serialization helper for [`to_return_value`].
+enum SerList {
+    List(SerArray),
+    /// A [`LazyList`] outside of list context only counts its items.
+    Count(usize),
+}
+
+macro_rules! forward_to_serializer {
+    ($( ($name:ident $ty:ty) )+) => {
+        $(
+            fn $name(mut self, v: $ty) -> Result<Value, Error> {
+                ser::Serializer::$name(&mut self.ser, v)
+            }
+        )+
+    };
+}
+
+impl ser::Serializer for ReturnValueSerializer<'_> {
+    type Ok = Value;
+    type Error = Error;
+
+    type SerializeSeq = SerArray;
+    type SerializeTuple = SerArray;
+    type SerializeTupleStruct = SerList;
+    type SerializeTupleVariant = SerVariant<SerArray>;
+    type SerializeMap = SerHash;
+    type SerializeStruct = SerHash;
+    type SerializeStructVariant = SerVariant<SerHash>;
+
+    forward_to_serializer! {
+        (serialize_bool bool)
+        (serialize_i8 i8)
+        (serialize_i16 i16)
+        (serialize_i32 i32)
+        (serialize_i64 i64)
+        (serialize_i128 i128)
+        (serialize_u8 u8)
+        (serialize_u16 u16)
+        (serialize_u32 u32)
+        (serialize_u64 u64)
+        (serialize_u128 u128)
+        (serialize_f32 f32)
+        (serialize_f64 f64)
+        (serialize_char char)
+        (serialize_bytes &[u8])
+        (serialize_unit_struct &'static str)
+    }
+
+    fn serialize_str(mut self, v: &str) -> Result<Value, Error> {
+        match substr_from_str_sources(self.str_sources, v) {
+            Some(substr) => Ok(Value::Scalar(substr)),
+            None => ser::Serializer::serialize_str(&mut self.ser, v),
+        }
+    }
+
+    fn serialize_none(mut self) -> Result<Value, Error> {
+        ser::Serializer::serialize_none(&mut self.ser)
+    }
+
+    fn serialize_some<T>(mut self, value: &T) -> Result<Value, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        ser::Serializer::serialize_some(&mut self.ser, value)
+    }
+
+    fn serialize_unit(mut self) -> Result<Value, Error> {
+        ser::Serializer::serialize_unit(&mut self.ser)
+    }
+
+    fn serialize_unit_variant(
+        mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value, Error> {
+        ser::Serializer::serialize_unit_variant(&mut self.ser, name, variant_index, variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Value, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        ser::Serializer::serialize_newtype_variant(
+            &mut self.ser,
+            name,
+            variant_index,
+            variant,
+            value,
+        )
+    }
+
+    fn serialize_seq(mut self, len: Option<usize>) -> Result<SerArray, Error> {
+        ser::Serializer::serialize_seq(&mut self.ser, len)
+    }
+
+    fn serialize_tuple(mut self, len: usize) -> Result<SerArray, Error> {
+        ser::Serializer::serialize_tuple(&mut self.ser, len)
+    }
+
+    fn serialize_tuple_struct(self, name: &'static str, len: usize) -> Result<SerList, Error> {
+        if name == LAZY_LIST && crate::ffi::gimme() != crate::ffi::Gimme::List {
+            return Ok(SerList::Count(0));
+        }
+        Ok(SerList::List(SerArray::new(Some(len), self.ser)))
+    }
+
+    fn serialize_tuple_variant(
+        mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerVariant<SerArray>, Error> {
+        ser::Serializer::serialize_tuple_variant(&mut self.ser, name, variant_index, variant, len)
+    }
+
+    fn serialize_map(mut self, len: Option<usize>) -> Result<SerHash, Error> {
+        ser::Serializer::serialize_map(&mut self.ser, len)
+    }
+
+    fn serialize_struct(mut self, name: &'static str, len: usize) -> Result<SerHash, Error> {
+        ser::Serializer::serialize_struct(&mut self.ser, name, len)
+    }
+
+    fn serialize_struct_variant(
+        mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerVariant<SerHash>, Error> {
+        ser::Serializer::serialize_struct_variant(&mut self.ser, name, variant_index, variant, len)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerList {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        match self {
+            SerList::List(list) => ser::SerializeTupleStruct::serialize_field(list, value),
+            SerList::Count(count) => {
+                *count += 1;
+                Ok(())
+            }
+        }
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        match self {
+            SerList::List(list) => Ok(Value::Array(list.array)),
+            SerList::Count(count) => Ok(Value::new_uint(count)),
+        }
+    }
+}
+
 struct RawValueSerializer;
 
 macro_rules! fail_impossible {
@@ -512,7 +1014,7 @@ impl ser::SerializeStruct for SerHash {
         T: ?Sized + Serialize,
     {
         match &mut self.mode {
-            SerHashMode::Hash(hash) => hash.insert(field, value.serialize(&mut Serializer)?),
+            SerHashMode::Hash(hash) => hash.insert(field, value.serialize(&mut self.ser)?),
             SerHashMode::Raw(raw) => {
                 if raw.is_some() {
                     return Error::fail("serialize_field called twice in raw context");
@@ -533,8 +1035,8 @@ impl ser::SerializeStruct for SerHash {
 }
 
 impl SerVariant<SerArray> {
-    fn new(variant: &str, len: Option<usize>) -> Self {
-        let inner = SerArray::new(len);
+    fn new(variant: &str, len: Option<usize>, ser: Serializer) -> Self {
+        let inner = SerArray::new(len, ser);
         let hash = hash::Hash::new();
         hash.insert(variant, Value::new_ref(&inner.array));
         Self { hash, inner }
@@ -549,7 +1051,7 @@ impl ser::SerializeTupleVariant for SerVariant<SerArray> {
     where
         T: ?Sized + Serialize,
     {
-        self.inner.array.push(value.serialize(&mut Serializer)?);
+        self.inner.array.push(value.serialize(&mut self.inner.ser)?);
         Ok(())
     }
 
@@ -559,8 +1061,8 @@ impl ser::SerializeTupleVariant for SerVariant<SerArray> {
 }
 
 impl SerVariant<SerHash> {
-    fn new(variant: &str) -> Self {
-        let inner = SerHash::new();
+    fn new(variant: &str, len: usize, ser: Serializer) -> Self {
+        let inner = SerHash::new(Some(len), ser);
         let hash = hash::Hash::new();
         hash.insert(
             variant,
@@ -583,7 +1085,7 @@ impl ser::SerializeStructVariant for SerVariant<SerHash> {
     {
         match &self.inner.mode {
             SerHashMode::Hash(hash) => {
-                hash.insert(field, value.serialize(&mut Serializer)?);
+                hash.insert(field, value.serialize(&mut self.inner.ser)?);
                 Ok(())
             }
             _ => unreachable!(),
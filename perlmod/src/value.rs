@@ -1,6 +1,7 @@
 //! The [`Value`] type is a generic perl value reference distinguishing between its types
 //! automatically.
 
+use std::cell::RefCell;
 use std::fmt;
 
 use serde::{Deserialize, Serialize};
@@ -9,7 +10,7 @@ use crate::ffi::{self, SV};
 use crate::scalar::ScalarRef;
 use crate::Error;
 use crate::{perl_fn, raw_value};
-use crate::{Array, Hash, Scalar};
+use crate::{Array, Hash, MagicSpec, Scalar};
 
 /// A higher level value. This is basically an [`SV`] already cast to [`AV`](crate::ffi::AV) or
 /// [`HV`](crate::ffi::HV) for arrays and hashes.
@@ -42,6 +43,14 @@ impl Value {
         Value::Scalar(Scalar::new_float(v))
     }
 
+    /// Create the canonical representation of a boolean, which is the integer `1` or `0`.
+    ///
+    /// This is what serializing a `bool` and `Value::from(bool)` produce. Unlike perl's
+    /// `PL_sv_yes` and `PL_sv_no`, `false` is a plain `0` rather than an empty string.
+    pub fn bool_value(b: bool) -> Self {
+        Value::new_uint(usize::from(b))
+    }
+
     /// Create a new string value.
     pub fn new_string(s: &str) -> Self {
         Value::Scalar(Scalar::new_string(s))
@@ -52,6 +61,11 @@ impl Value {
         Value::Scalar(Scalar::new_bytes(s))
     }
 
+    /// Create a new latin-1 string, see [`Scalar::new_latin1`].
+    pub fn new_latin1(s: &[u8]) -> Self {
+        Value::Scalar(Scalar::new_latin1(s))
+    }
+
     /// Create a new reference code reference.
     ///
     /// # Safety
@@ -71,6 +85,80 @@ impl Value {
         }
     }
 
+    /// Create a new code reference calling a rust closure.
+    ///
+    /// The closure gets the parameters passed to the sub by perl and the returned values are
+    /// pushed onto perl's stack. On error, the error's message is raised via perl's `die`.
+    ///
+    /// The closure is attached to the sub via magic, and gets dropped when perl frees the sub.
+    ///
+    /// ```
+    /// # use perlmod::{Error, Value};
+    /// fn make_counter(start: isize) -> Value {
+    ///     let counter = std::cell::Cell::new(start);
+    ///     Value::new_closure(move |_args: &[Value]| -> Result<Vec<Value>, Error> {
+    ///         let value = counter.get();
+    ///         counter.set(value + 1);
+    ///         Ok(vec![Value::new_int(value)])
+    ///     })
+    /// }
+    /// ```
+    pub fn new_closure<F>(func: F) -> Self
+    where
+        F: Fn(&[Value]) -> Result<Vec<Value>, Error> + 'static,
+    {
+        let cv = unsafe {
+            Self::from_raw_move(ffi::RSPL_newXS_flags(
+                std::ptr::null(),
+                closure::trampoline as _,
+                std::ptr::null(),
+                std::ptr::null(),
+                0,
+            ) as _)
+        };
+        let func: Box<closure::Closure> = Box::new(Box::new(func));
+        cv.add_magic(closure::MAGIC.with_value(func));
+        Value::new_ref(&cv)
+    }
+
+    /// Create a new code reference returning the items of an iterator one at a time, for perl
+    /// code to consume large amounts of data lazily:
+    ///
+    /// ```perl
+    /// my $iter = RSPM::Something::items();
+    /// while (defined(my $item = $iter->())) {
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// Each call returns the next item, or `undef` once the iterator is exhausted. An error item
+    /// is raised via perl's `die`, after which further items can still be retrieved. The iterator
+    /// is kept in the sub's magic like the closure of [`new_closure`](Value::new_closure()), and
+    /// gets dropped when perl frees the sub.
+    ///
+    /// ```
+    /// # use perlmod::{Error, Value};
+    /// fn lines(text: String) -> Value {
+    ///     let lines: Vec<String> = text.lines().map(str::to_string).collect();
+    ///     Value::new_iterator(lines.into_iter().map(|line| Ok(Value::new_string(&line))))
+    /// }
+    /// ```
+    pub fn new_iterator<I>(iter: I) -> Self
+    where
+        I: Iterator<Item = Result<Value, Error>> + 'static,
+    {
+        let iter = RefCell::new(iter.fuse());
+        Self::new_closure(move |_args: &[Value]| -> Result<Vec<Value>, Error> {
+            let mut iter = iter
+                .try_borrow_mut()
+                .map_err(|_| Error::new("iterator called recursively"))?;
+            Ok(vec![iter
+                .next()
+                .transpose()?
+                .unwrap_or_else(Value::new_undef)])
+        })
+    }
+
     /// If the value is an array, returns the associated [`Array`].
     pub fn as_array(&self) -> Option<&Array> {
         match self {
@@ -118,6 +206,31 @@ impl Value {
         Value::Reference(unsafe { Scalar::from_raw_move(ffi::RSPL_newRV_inc(value.sv())) })
     }
 
+    /// Create a reference to a value, consuming it. Contrary to [`new_ref`](Value::new_ref()),
+    /// this moves the value's reference count into the reference instead of taking a new one, so
+    /// the value is freed along with the reference if there are no other references to it.
+    ///
+    /// This is the `newRV_noinc` counterpart to `new_ref`'s `newRV_inc`: the reference count held
+    /// by the consumed value now belongs to the reference, and the referenced value's count stays
+    /// the same. Other references to the value are not affected. Calling `new_ref` and dropping
+    /// the value afterwards yields the same counts, but goes through a transient extra count in
+    /// between.
+    ///
+    /// ```
+    /// # fn code() -> perlmod::Value {
+    /// let list = perlmod::Array::new();
+    /// list.push(perlmod::Value::new_int(1));
+    /// perlmod::Value::new_ref_owned(list)
+    /// # }
+    /// ```
+    pub fn new_ref_owned<T>(value: T) -> Self
+    where
+        T: Into<Scalar>,
+    {
+        let value = value.into().into_raw();
+        Value::Reference(unsafe { Scalar::from_raw_move(ffi::RSPL_newRV_noinc(value)) })
+    }
+
     /// Create a new empty hash.
     pub fn new_hash() -> Self {
         Value::Hash(Hash::new())
@@ -253,6 +366,125 @@ impl Value {
         }
     }
 
+    /// Get the reference count of this value, see [`ScalarRef::refcnt`].
+    ///
+    /// Note that for a [`Value::Reference`] this is the count of the *reference* itself. To find
+    /// out how many references point to a blessed object (eg. to check whether an object graph
+    /// gets released), [`dereference`](Value::dereference()) it first and subtract the one
+    /// reference held by the dereferenced `Value`.
+    pub fn ref_count(&self) -> usize {
+        self.refcnt()
+    }
+
+    /// Create an independent deep copy of this value, similar to `Storable::dclone`.
+    ///
+    /// Contrary to [`clone_ref`](Value::clone_ref()), the contents of arrays and hashes are copied
+    /// recursively, as are the values behind references. Values referenced multiple times within
+    /// the structure, including reference cycles, are also shared within the copy. Scalars are
+    /// copied after processing their get-magic, so the copy holds a plain snapshot of them.
+    ///
+    /// Blessed references, arrays or hashes with magic (such as tied ones or perlmod's magic
+    /// objects) and values like code references or globs cannot be copied safely and produce an
+    /// error.
+    pub fn deep_clone(&self) -> Result<Value, Error> {
+        self.deep_clone_with(&mut std::collections::HashMap::new())
+    }
+
+    fn deep_clone_with(
+        &self,
+        copies: &mut std::collections::HashMap<*mut SV, Option<Value>>,
+    ) -> Result<Value, Error> {
+        let sv = self.sv();
+        if let Some(copy) = copies.get(&sv) {
+            return match copy {
+                Some(copy) => Ok(copy.clone_ref()),
+                None => Error::fail("cannot deep-copy a reference referencing itself"),
+            };
+        }
+
+        if unsafe { ffi::RSPL_SvOBJECT(sv) } {
+            return Err(Error(format!(
+                "cannot deep-copy a value blessed into '{}'",
+                self.reftype(true)
+            )));
+        }
+
+        let copy = match self {
+            // `ty()` treats code as undef, so check the actual type:
+            Value::Scalar(scalar) => match scalar.svtype_name() {
+                "SCALAR" | "LVALUE" | "VSTRING" => {
+                    Value::Scalar(unsafe { Scalar::from_raw_move(ffi::RSPL_newSVsv(sv)) })
+                }
+                other => return Err(Error(format!("cannot deep-copy {other} values"))),
+            },
+            Value::Reference(_) => {
+                let target = self
+                    .dereference()
+                    .ok_or_else(|| Error::new("failed to dereference value"))?;
+                copies.insert(sv, None);
+                let copy = Value::new_ref(&target.deep_clone_with(copies)?);
+                copies.insert(sv, Some(copy.clone_ref()));
+                return Ok(copy);
+            }
+            Value::Array(array) => {
+                if unsafe { ffi::RSPL_SvMAGICAL(sv) } {
+                    return Error::fail("cannot deep-copy an array with magic");
+                }
+                let copy = Array::new();
+                copies.insert(sv, Some(Value::Array(copy.clone_ref())));
+                copy.reserve(array.len());
+                for item in array {
+                    copy.push(item.deep_clone_with(copies)?);
+                }
+                return Ok(Value::Array(copy));
+            }
+            Value::Hash(hash) => {
+                if unsafe { ffi::RSPL_SvMAGICAL(sv) } {
+                    return Error::fail("cannot deep-copy a hash with magic");
+                }
+                let copy = Hash::new();
+                copies.insert(sv, Some(Value::Hash(copy.clone_ref())));
+                // collect the entries first, recursing may use the hash's iterator again
+                let entries: Vec<(Scalar, Value)> = hash.iter_sv().collect();
+                for (key, value) in entries {
+                    copy.insert_by_value(&Value::Scalar(key), value.deep_clone_with(copies)?);
+                }
+                return Ok(Value::Hash(copy));
+            }
+        };
+
+        copies.insert(sv, Some(copy.clone_ref()));
+        Ok(copy)
+    }
+
+    /// Get the file descriptor of a perl file handle, such as a glob reference from `open`, or
+    /// `None` if this is not an open file handle.
+    ///
+    /// The descriptor is still owned by the perl handle: it must not be closed, and wrapping it in
+    /// a [`File`](std::fs::File) requires [`ManuallyDrop`](std::mem::ManuallyDrop) or
+    /// `into_raw_fd`. Perl's `PerlIO` layer also keeps its own buffers, so data already buffered
+    /// for reading is not visible on the descriptor, and pending perl writes should be flushed
+    /// before writing to it directly.
+    pub fn as_raw_fd(&self) -> Option<std::os::unix::io::RawFd> {
+        let fd = unsafe { ffi::RSPL_sv_fileno(self.sv()) };
+        (fd >= 0).then_some(fd)
+    }
+
+    /// Write all of `data` to a perl file handle open for writing, such as a glob reference from
+    /// `open`.
+    ///
+    /// Contrary to writing to its [`as_raw_fd`](Value::as_raw_fd()) descriptor, this goes through
+    /// perl's `PerlIO` layers like `print` does, so the data is buffered along with other perl
+    /// writes, layers such as `:crlf` or `:encoding(...)` apply, and in-memory handles opened on a
+    /// scalar work as well. Note that the data is not flushed.
+    pub fn write_all(&self, data: &[u8]) -> Result<(), Error> {
+        match unsafe { ffi::RSPL_sv_write_all(self.sv(), data.as_ptr().cast(), data.len()) } {
+            0 => Ok(()),
+            -1 => Err(Error::new("not a file handle open for writing")),
+            _ => Err(Error::new("failed to write to file handle")),
+        }
+    }
+
     /// Dereference this reference value.
     pub fn dereference(&self) -> Option<Value> {
         match self {
@@ -290,6 +522,93 @@ impl Value {
         }
     }
 
+    /// Serialize this value into a JSON string.
+    ///
+    /// References are followed, so blessed objects are serialized by their contents. Reference
+    /// cycles result in an error.
+    #[cfg(feature = "serde_json")]
+    pub fn to_json_string(&self) -> Result<String, Error> {
+        let _guard = raw_value::guarded(false);
+        serde_json::to_string(self).map_err(|err| Error(err.to_string()))
+    }
+
+    /// Get a [`Debug`](fmt::Debug) implementation printing the contents of arrays and hashes,
+    /// following references up to `max_depth` levels of nested arrays and hashes.
+    ///
+    /// Deeper levels are printed as `[...]` or `{...}`, and references back to a value which is
+    /// currently being printed (cycles) are printed as `<cycle>`.
+    ///
+    /// This uses the internal iterator of hashes, but puts back its previous state afterwards,
+    /// except for tied hashes, see [`Hash::shared_iter`](crate::Hash::shared_iter()).
+    pub fn debug_nested(&self, max_depth: usize) -> DebugNested {
+        DebugNested {
+            value: self.clone_ref(),
+            max_depth,
+        }
+    }
+
+    /// Look up a value in a nested structure of hashes and arrays, like `$data->{a}[2]{b}` in
+    /// perl. References are followed automatically.
+    ///
+    /// Returns `None` if an element does not exist or does not have the expected type.
+    ///
+    /// ```no_run
+    /// use perlmod::value::PathSeg;
+    /// use perlmod::Value;
+    ///
+    /// fn third_b(data: &Value) -> Option<Value> {
+    ///     data.get_path(&[PathSeg::Key("a"), PathSeg::Index(2), PathSeg::Key("b")])
+    /// }
+    /// ```
+    pub fn get_path(&self, path: &[PathSeg]) -> Option<Value> {
+        let mut current = self.clone_ref();
+        for seg in path {
+            while let Value::Reference(_) = current {
+                current = current.dereference()?;
+            }
+            current = match (seg, &current) {
+                (PathSeg::Key(key), Value::Hash(hash)) => hash.get(key)?,
+                (PathSeg::Index(index), Value::Array(array)) => array.get(*index)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    /// Get the class name from the invocant of a method call: either a string such as the `Foo` in
+    /// `Foo->new()`, or the package a reference such as the `$obj` in `$obj->new()` is blessed
+    /// into.
+    pub fn class_name(&self) -> Result<&str, Error> {
+        match self {
+            Value::Reference(_) => {
+                if !self.is_blessed() {
+                    return Err(Error::new(
+                        "expected a class name or an object, got an unblessed reference",
+                    ));
+                }
+                let inner = self
+                    .dereference()
+                    .ok_or_else(|| Error::new("not a reference"))?;
+                Ok(inner.reftype(true))
+            }
+            Value::Scalar(scalar) if !scalar.is_undef() => Ok(scalar.pv_string_utf8()),
+            _ => Err(Error::new("expected a class name or an object")),
+        }
+    }
+
+    /// Check whether this object or class name does the role `role`, like `$obj->DOES($role)` in
+    /// perl.
+    ///
+    /// By default this is the same as `isa`, so it includes classes inherited via `@ISA`, but role
+    /// systems such as `Moose` or `Role::Tiny` override `DOES` to also report composed roles.
+    /// Unblessed references and `undef` yield `false`.
+    ///
+    /// The method is called inside an `eval`: if `DOES` dies, its error is discarded and `false`
+    /// is returned. The caller's `$@` is left untouched either way.
+    pub fn does(&self, role: &str) -> bool {
+        unsafe { ffi::RSPL_sv_does(self.sv(), role.as_ptr().cast(), role.len()) }
+    }
+
     /// Check that the value is a reference and if so, assume it is a reference to a boxed rust
     /// type and return a reference to it.
     ///
@@ -388,6 +707,58 @@ impl Value {
         Ok(this)
     }
 
+    /// Create a hash reference blessed into `class` with `value` attached to the hash as magic.
+    ///
+    /// This is the function equivalent of
+    /// [`instantiate_magic_result!`](crate::instantiate_magic_result), and the safer alternative
+    /// to [`bless_box`](Value::bless_box()), as the value is dropped along with the perl object
+    /// without the need for a `DESTROY` sub. See the [`magic`](crate::magic) module documentation
+    /// for how to declare the `spec` and access the value again.
+    ///
+    /// ```
+    /// #[perlmod::package(name = "RSPM::Doc::BlessBoxMagic", file = "/dev/null")]
+    /// mod export {
+    ///     use perlmod::{Error, Value};
+    ///
+    ///     perlmod::declare_magic!(Box<Thing> : &Thing as "RSPM::Doc::BlessBoxMagic");
+    ///
+    ///     struct Thing {
+    ///         stuff: String,
+    ///     }
+    ///
+    ///     #[export(raw_return)]
+    ///     fn new(class: &str, stuff: String) -> Result<Value, Error> {
+    ///         Value::bless_box_magic(class, Box::new(Thing { stuff }), &MAGIC)
+    ///     }
+    /// }
+    /// ```
+    pub fn bless_box_magic<T>(
+        class: &str,
+        value: Box<T>,
+        spec: &MagicSpec<'static, 'static, Box<T>>,
+    ) -> Result<Value, Error> {
+        let hash = Value::new_hash();
+        let this = Value::new_ref(&hash);
+        this.bless(class)?;
+        hash.add_magic(spec.with_value(value));
+        Ok(this)
+    }
+
+    /// Call this value as a perl sub in list context and return the values it returned.
+    ///
+    /// This value can be a code reference or anything else perl's `call_sv` accepts, such as a
+    /// sub name.
+    ///
+    /// If the sub `die`s, the error is caught and its stringified message is returned as an
+    /// [`Error`].
+    pub fn call(&self, args: &[Value]) -> Result<Vec<Value>, Error> {
+        let args: Vec<*mut SV> = args.iter().map(|arg| arg.sv()).collect();
+        let mut error: *mut SV = std::ptr::null_mut();
+        let result =
+            unsafe { ffi::RSPL_call_sv_list(self.sv(), args.as_ptr(), args.len(), &mut error) };
+        call_result(result, error)
+    }
+
     /// Attempt to create a substring, provided the contained value is actually a string.
     pub fn substr<I>(&self, index: I) -> Result<Value, Error>
     where
@@ -400,6 +771,77 @@ impl Value {
     }
 }
 
+/// An element of the path passed to [`Value::get_path`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PathSeg<'a> {
+    /// A hash key.
+    Key(&'a str),
+
+    /// An array index.
+    Index(usize),
+}
+
+/// Support code for [`Value::new_closure`].
+mod closure {
+    use crate::ffi::{self, CV, SV};
+    use crate::magic::{MagicSpec, MagicTag};
+    use crate::{Error, ScalarRef, Value};
+
+    pub type Closure = Box<dyn Fn(&[Value]) -> Result<Vec<Value>, Error> + 'static>;
+
+    pub static MAGIC: MagicSpec<Box<Closure>> = unsafe {
+        static TAG: MagicTag<Box<Closure>> = MagicTag::<Box<Closure>>::DEFAULT;
+        MagicSpec::new_static(&TAG)
+    };
+
+    perl_fn! {
+        pub extern "C" fn trampoline(cv: *mut CV) {
+            unsafe {
+                match call(cv) {
+                    Ok(values) => {
+                        for sv in values {
+                            ffi::stack_push_raw(sv);
+                        }
+                    }
+                    Err(sv) => ffi::croak(sv),
+                }
+            }
+        }
+    }
+
+    /// This returns mortal values, so no rust values requiring a `Drop` are alive when `croak`ing.
+    #[inline(never)]
+    fn call(cv: *mut CV) -> Result<Vec<*mut SV>, *mut SV> {
+        let argmark = unsafe { ffi::pop_arg_mark() };
+        let args: Vec<Value> = argmark.iter().map(Value::from).collect();
+        unsafe {
+            argmark.set_stack();
+        }
+
+        let cv = unsafe { &*(cv as *const ScalarRef) };
+        let func = match cv.find_magic(&MAGIC) {
+            Some(func) => func,
+            None => {
+                return Err(
+                    Value::new_string("closure called without its magic pointer\n")
+                        .into_mortal()
+                        .into_raw(),
+                )
+            }
+        };
+
+        match func(&args) {
+            Ok(values) => Ok(values
+                .into_iter()
+                .map(|value| value.into_mortal().into_raw())
+                .collect()),
+            Err(err) => Err(Value::new_string(&format!("{err}\n"))
+                .into_mortal()
+                .into_raw()),
+        }
+    }
+}
+
 impl From<Scalar> for Value {
     fn from(scalar: Scalar) -> Self {
         unsafe {
@@ -428,6 +870,83 @@ impl From<Array> for Value {
     }
 }
 
+/// Turn the results of `RSPL_call_sv_list` or `RSPL_eval_sv` into values, or the stored `$@` into
+/// an error.
+pub(crate) fn call_result(result: *mut ffi::AV, error: *mut SV) -> Result<Vec<Value>, Error> {
+    if result.is_null() {
+        let error = unsafe { Scalar::from_raw_move(error) };
+        let message = error.pv_string_utf8();
+        return Err(Error::new(message.strip_suffix('\n').unwrap_or(message)));
+    }
+
+    Ok(unsafe { Array::from_raw_move(result) }
+        .into_iter()
+        .collect())
+}
+
+macro_rules! impl_from_primitive {
+    ($($ty:ty => $new:ident as $cast:ty),* $(,)?) => {$(
+        impl From<$ty> for Value {
+            fn from(v: $ty) -> Self {
+                Value::$new(v as $cast)
+            }
+        }
+    )*};
+}
+
+impl_from_primitive! {
+    i8 => new_int as isize,
+    i16 => new_int as isize,
+    i32 => new_int as isize,
+    isize => new_int as isize,
+    u8 => new_uint as usize,
+    u16 => new_uint as usize,
+    u32 => new_uint as usize,
+    usize => new_uint as usize,
+    f32 => new_float as f64,
+    f64 => new_float as f64,
+}
+
+/// Values out of perl's integer range become decimal strings, like when serializing them.
+impl From<i64> for Value {
+    fn from(v: i64) -> Self {
+        match isize::try_from(v) {
+            Ok(v) => Value::new_int(v),
+            Err(_) => Value::new_string(&v.to_string()),
+        }
+    }
+}
+
+/// Values out of perl's integer range become decimal strings, like when serializing them.
+impl From<u64> for Value {
+    fn from(v: u64) -> Self {
+        match usize::try_from(v) {
+            Ok(v) => Value::new_uint(v),
+            Err(_) => Value::new_string(&v.to_string()),
+        }
+    }
+}
+
+/// Produces the canonical representation of a boolean, see
+/// [`Value::bool_value`](Value::bool_value()).
+impl From<bool> for Value {
+    fn from(v: bool) -> Self {
+        Value::bool_value(v)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::new_string(s)
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::new_string(&s)
+    }
+}
+
 impl From<raw_value::RawValue> for Value {
     fn from(value: raw_value::RawValue) -> Self {
         value.into_inner()
@@ -446,6 +965,88 @@ impl fmt::Debug for Value {
     }
 }
 
+/// The depth up to which the [`Debug`](fmt::Debug) implementations of [`Array`] and [`Hash`] print
+/// nested values.
+pub const DEBUG_DEPTH: usize = 8;
+
+/// Recursive [`Debug`](fmt::Debug) output for a value, see [`Value::debug_nested`].
+pub struct DebugNested {
+    value: Value,
+    max_depth: usize,
+}
+
+impl fmt::Debug for DebugNested {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ancestors = RefCell::new(Vec::new());
+        fmt_nested(&self.value, self.max_depth, &ancestors, f)
+    }
+}
+
+/// A nested value within [`DebugNested`], sharing the list of values currently being printed.
+struct Nested<'a> {
+    value: Value,
+    depth: usize,
+    ancestors: &'a RefCell<Vec<*mut SV>>,
+}
+
+impl fmt::Debug for Nested<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_nested(&self.value, self.depth, self.ancestors, f)
+    }
+}
+
+fn fmt_nested(
+    value: &Value,
+    depth: usize,
+    ancestors: &RefCell<Vec<*mut SV>>,
+    f: &mut fmt::Formatter,
+) -> fmt::Result {
+    if let Value::Scalar(value) = value {
+        return fmt::Debug::fmt(value, f);
+    }
+
+    let sv = value.sv();
+    if ancestors.borrow().contains(&sv) {
+        return f.write_str("<cycle>");
+    }
+
+    let nested = |value| Nested {
+        value,
+        depth: depth - 1,
+        ancestors,
+    };
+
+    ancestors.borrow_mut().push(sv);
+    let result = match value {
+        Value::Scalar(_) => unreachable!(),
+        Value::Reference(_) => match value.dereference() {
+            Some(inner @ (Value::Array(_) | Value::Hash(_))) => {
+                fmt_nested(&inner, depth, ancestors, f)
+            }
+            Some(inner) => {
+                f.write_str("\\")?;
+                fmt_nested(&inner, depth, ancestors, f)
+            }
+            None => f.write_str("<*REFERENCE>"),
+        },
+        Value::Array(_) if depth == 0 => f.write_str("[...]"),
+        Value::Hash(_) if depth == 0 => f.write_str("{...}"),
+        Value::Array(array) => f.debug_list().entries(array.iter().map(nested)).finish(),
+        Value::Hash(hash) => {
+            // don't disturb an iteration over the hash which is in progress
+            let _saved = hash.save_iter();
+            f.debug_map()
+                .entries(
+                    hash.shared_iter()
+                        .map(|(key, value)| (String::from_utf8_lossy(key), nested(value))),
+                )
+                .finish()
+        }
+    };
+    ancestors.borrow_mut().pop();
+    result
+}
+
 impl core::ops::Deref for Value {
     type Target = ScalarRef;
 
@@ -489,18 +1090,12 @@ impl Serialize for Value {
     where
         S: serde::Serializer,
     {
-        use serde::ser::Error;
-
         if raw_value::is_enabled() {
             raw_value::serialize_raw(self, serializer)
         } else {
             match self {
                 Value::Scalar(this) => this.serialize(serializer),
-                Value::Reference(this) => Value::from(
-                    this.dereference()
-                        .ok_or_else(|| S::Error::custom("failed to dereference perl value"))?,
-                )
-                .serialize(serializer),
+                Value::Reference(this) => serialize_referenced(this, serializer),
                 Value::Array(value) => value.serialize(serializer),
                 Value::Hash(value) => value.serialize(serializer),
             }
@@ -508,6 +1103,57 @@ impl Serialize for Value {
     }
 }
 
+thread_local! {
+    /// The values behind the references currently being serialized by [`serialize_referenced`].
+    static SERIALIZING: RefCell<Vec<*mut SV>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Removes a value from [`SERIALIZING`] when it is done being serialized.
+struct SerializingGuard;
+
+impl Drop for SerializingGuard {
+    fn drop(&mut self) {
+        SERIALIZING.with(|stack| stack.borrow_mut().pop());
+    }
+}
+
+/// Serialize the value behind a reference.
+///
+/// Since this is the only way to recurse into nested values, this fails on reference cycles
+/// (like `$a->{self} = $a`) instead of recursing until the stack overflows. Values which are
+/// merely referenced multiple times are serialized each time.
+pub(crate) fn serialize_referenced<S>(
+    reference: &ScalarRef,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use serde::ser::Error;
+
+    let value = reference
+        .dereference()
+        .ok_or_else(|| S::Error::custom("failed to dereference perl value"))?;
+
+    let sv = value.sv();
+    let is_cycle = SERIALIZING.with(|stack| {
+        let mut stack = stack.borrow_mut();
+        if stack.contains(&sv) {
+            return true;
+        }
+        stack.push(sv);
+        false
+    });
+    if is_cycle {
+        return Err(S::Error::custom(
+            "cannot serialize reference cycle in perl value",
+        ));
+    }
+
+    let _guard = SerializingGuard;
+    Value::from(value).serialize(serializer)
+}
+
 impl<'de> Deserialize<'de> for Value {
     fn deserialize<D>(deserializer: D) -> Result<Value, D::Error>
     where
@@ -526,7 +1172,7 @@ impl<'de> Deserialize<'de> for Value {
 
             #[inline]
             fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
-                Ok(Value::new_int(isize::from(value)))
+                Ok(Value::bool_value(value))
             }
 
             #[inline]
@@ -645,10 +1291,97 @@ impl<'de> Deserialize<'de> for Value {
             }
         }
 
+        // The perlmod deserializer answers this with the raw value, while other deserializers,
+        // like the buffered content used by `#[serde(flatten)]`, visit the newtype and we build a
+        // copy of the value from whatever they contain.
+        struct RawOrCopyVisitor;
+
+        impl<'de> Visitor<'de> for RawOrCopyVisitor {
+            type Value = Value;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("any valid PERL value")
+            }
+
+            fn visit_map<V>(self, visitor: V) -> Result<Value, V::Error>
+            where
+                V: serde::de::MapAccess<'de>,
+            {
+                raw_value::RawValue::visit_raw(visitor).map(Value::from)
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Value, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                deserializer.deserialize_any(ValueVisitor)
+            }
+        }
+
         if raw_value::is_enabled() {
-            raw_value::RawValue::deserialize(deserializer).map(Value::from)
+            deserializer.deserialize_newtype_struct(raw_value::NAME, RawOrCopyVisitor)
         } else {
             deserializer.deserialize_any(ValueVisitor)
         }
     }
 }
+
+/// A wrapper around a [`Value`] implementing [`Ord`] via perl's `cmp` operator, so that values
+/// can be sorted with the standard library's sorting functions.
+///
+/// Values are always compared as strings, just like perl's `sort` does by default. This means
+/// numbers are compared by their string representation, so `10` sorts before `9`.
+///
+/// Objects overloading `cmp` are compared via [`str_cmp`](crate::ScalarRef::str_cmp()). Since
+/// `Ord` cannot fail, an overload method which dies makes the values compare as equal; use
+/// `str_cmp` directly where such errors need to be reported. Other magic, such as the `FETCH` of
+/// tied values or objects only overloading `""`, is run outside of an `eval`, so this should only
+/// be used for plain values and objects overloading `cmp`.
+///
+/// ```no_run
+/// use perlmod::value::SortableValue;
+/// use perlmod::Value;
+///
+/// fn sorted(values: Vec<Value>) -> Vec<Value> {
+///     let mut values: Vec<SortableValue> = values.into_iter().map(SortableValue).collect();
+///     values.sort();
+///     values.into_iter().map(SortableValue::into_inner).collect()
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct SortableValue(pub Value);
+
+impl SortableValue {
+    /// Get the contained value.
+    pub fn into_inner(self) -> Value {
+        self.0
+    }
+}
+
+impl From<Value> for SortableValue {
+    fn from(value: Value) -> Self {
+        Self(value)
+    }
+}
+
+impl PartialEq for SortableValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for SortableValue {}
+
+impl PartialOrd for SortableValue {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SortableValue {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0
+            .str_cmp(&other.0)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    }
+}
@@ -130,3 +130,1183 @@ my $err = $@;
 die "test_deserialized_error error is not a hash\n" if ref($err) ne 'HASH';
 die "structured error has invalid fields\n" if join(',', sort(keys(%$err))) ne 'a,b';
 print('error type: { a: ', $err->{a}, ', b: ', $err->{b}, " }\n");
+
+$! = 0;
+eval { RSPM::Foo142::test_errno_from_error(13) };
+die "test_errno_from_error did not fail\n" if !$@;
+die "test_errno_from_error failed to set errno from error value\n" if $! != 13;
+print("error with errno: $@");
+
+print("Testing reference counts\n");
+my $child = {};
+my $root = { child => $child, also => [$child] };
+my $count = RSPM::Foo142::test_ref_count($child);
+print("child referenced $count times\n");
+undef $root;
+$count = RSPM::Foo142::test_ref_count($child);
+print("child referenced $count times after dropping root\n");
+
+print("Testing closures\n");
+my $counter = RSPM::Foo142::test_closure(5);
+for (1..3) {
+    my ($value, $double) = $counter->();
+    print("counter: $value, $double\n");
+}
+eval { $counter->(1) };
+print("closure error: $@");
+undef $counter;
+
+print("Testing blessed serialization\n");
+my $blessed = RSPM::Foo142::test_to_blessed_value('blessed');
+print('blessed into ', ref($blessed), ": $blessed->{name}, $blessed->{count}\n");
+
+print("Testing callbacks\n");
+my $accepted = RSPM::Foo142::test_callback(sub {
+    my ($item) = @_;
+    print("streamed item $item->{count}: $item->{name}\n");
+    return $item->{count} != 1 ? ('accepted') : ();
+});
+print("accepted $accepted items\n");
+eval { RSPM::Foo142::test_callback(sub { die "stop streaming\n" }) };
+print("callback error: $@");
+eval { RSPM::Foo142::test_callback('not code') };
+print("callback error: $@");
+eval { die "original error\n" };
+RSPM::Foo142::test_callback(sub { () });
+print("\$@ after callback: $@");
+
+print("Testing utf8 flag\n");
+my $utf8_bytes = "\xc3\xa4\xc3\xb6";
+my $fixed = RSPM::Foo142::test_utf8_flag($utf8_bytes);
+print('utf8 flag: ', length($utf8_bytes), ' bytes => ', length($fixed), " characters, ",
+    ($fixed eq "\x{e4}\x{f6}" ? 'matching' : 'NOT matching'), "\n");
+eval { RSPM::Foo142::test_utf8_flag("\xff\xfe") };
+print("utf8 flag error: $@");
+
+print("Testing warnings\n");
+{
+    my $warning;
+    local $SIG{__WARN__} = sub { $warning = $_[0] };
+
+    RSPM::Foo142::test_warn('warned'); my $warn_line = __LINE__;
+    print('warn location: ', ($warning eq "warned at $0 line $warn_line.\n" ? 'ok' : "wrong: $warning"), "\n");
+
+    RSPM::Foo142::test_warn("no location\n");
+    print("warn with newline: $warning");
+
+    my $carping = sub { RSPM::Foo142::test_carp('carped') };
+    $carping->(); my $carp_line = __LINE__;
+    print('carp location: ', ($warning eq "carped at $0 line $carp_line.\n" ? 'ok' : "wrong: $warning"), "\n");
+
+    local $SIG{__WARN__} = sub { die "dying warning handler\n" };
+    for my $func (\&RSPM::Foo142::test_warn, \&RSPM::Foo142::test_carp) {
+        my $result = eval { $func->('fatal'); 1 };
+        print($result ? "unexpected success\n" : $@);
+    }
+}
+
+print("Testing is_empty on tied aggregates\n");
+{
+    package TiedCounter;
+    sub TIEHASH { my ($class, $count) = @_; return bless { count => $count }, $class }
+    sub TIEARRAY { my ($class, $count) = @_; return bless { count => $count }, $class }
+    sub SCALAR { return $_[0]->{count} }
+    sub FETCHSIZE { return $_[0]->{count} }
+    sub FIRSTKEY { return undef }
+    sub FETCH { return undef }
+}
+for my $count (0, 2) {
+    tie my %tied_hash, 'TiedCounter', $count;
+    tie my @tied_array, 'TiedCounter', $count;
+    print("tied hash with $count entries empty: ",
+        (RSPM::Foo142::test_is_empty(\%tied_hash) ? 'yes' : 'no'), "\n");
+    print("tied array with $count entries empty: ",
+        (RSPM::Foo142::test_is_empty(\@tied_array) ? 'yes' : 'no'), "\n");
+}
+print('plain hash empty: ', (RSPM::Foo142::test_is_empty({}) ? 'yes' : 'no'), "\n");
+
+print("Testing reference prototypes\n");
+{
+    my @list = (1, 2, 3);
+    my %map = (a => 1, b => 2);
+    print('prototype: ', prototype(\&RSPM::Foo142::test_aggregate_refs), "\n");
+    print(RSPM::Foo142::test_aggregate_refs(@list, %map, @list), "\n");
+    print(RSPM::Foo142::test_aggregate_refs(@list, %map, %map), "\n");
+    eval { &RSPM::Foo142::test_aggregate_refs(\%map, \%map, \@list) };
+    print("aggregate error: $@");
+}
+
+print("Testing borrowed hash keys\n");
+{
+    my %keys = ('plain' => 1, "\x{e4}latin1" => 2, "utf8\x{263a}" => 3);
+    my $keys = RSPM::Foo142::test_borrowed_keys(\%keys);
+    binmode(STDOUT, ':utf8');
+    print("borrowed keys: ", join(', ', @$keys), "\n");
+    binmode(STDOUT, ':raw');
+}
+
+print("Testing pseudo_block\n");
+{
+    package Reapable;
+    sub DESTROY { print("reaped object $_[0]->{id}\n") }
+}
+RSPM::Foo142::test_pseudo_block('Reapable', 3);
+
+print("Testing svtype names\n");
+for my $value (\1, \*STDOUT, sub {}, *STDOUT{IO}, \substr(my $lv = 'abc', 1)) {
+    print('svtype: ', RSPM::Foo142::test_svtype_name($value), "\n");
+}
+eval { RSPM::Foo142::foo142(\*STDOUT, 1) };
+print("svtype error: $@");
+
+print("Testing return_ok_true\n");
+if (RSPM::Foo142::test_return_ok_true(0)) {
+    print("return_ok_true: succeeded with a true value\n");
+}
+if (!eval { RSPM::Foo142::test_return_ok_true(1) }) {
+    print("return_ok_true error: $@");
+}
+
+print("Testing Array::extend_from_slice\n");
+my $extended = RSPM::Foo142::test_extend_from_slice([3, -1, 42]);
+print('extended array: ', join(', ', @$extended), "\n");
+$_ = 'changed' for @$extended[-2, -1];
+print('modified booleans: ', join(', ', @$extended[-2, -1]), "\n");
+
+print("Testing integer conversions\n");
+for my $value (42, 42.0, -7, 3.9, 1e30, 2**40) {
+    my $result = eval { RSPM::Foo142::test_integer($value) };
+    print("integer from $value: ", ($@ ? "failed with $@" : "$result\n"));
+}
+{
+    my $used_as_int = 3.9;
+    my $truncated = int($used_as_int) + ($used_as_int | 0);
+    my $result = eval { RSPM::Foo142::test_integer($used_as_int) };
+    print("integer from $used_as_int after integer use: ", ($@ ? "failed with $@" : "$result\n"));
+}
+
+print("Testing Array::retain and Array::dedup_by\n");
+{
+    my $elem = {};
+    my @list = (1, $elem, 2, 3, 4, 5, 6);
+    print('element referenced ', RSPM::Foo142::test_ref_count($elem), " times before retaining\n");
+    RSPM::Foo142::test_retain(@list, 1);
+    print('retained all: ', scalar(@list), ' elements, element referenced ',
+        RSPM::Foo142::test_ref_count($elem), " times\n");
+    RSPM::Foo142::test_retain(@list, 0);
+    print('retained even: ', join(', ', map { ref($_) ? 'HASH' : $_ } @list), ', element referenced ',
+        RSPM::Foo142::test_ref_count($elem), " times\n");
+
+    my @sparse = (1);
+    $sparse[3] = 4;
+    RSPM::Foo142::test_retain(@sparse, 0);
+    $sparse[0] = 'x';
+    print('retained sparse: ', join(', ', map { $_ // 'undef' } @sparse), "\n");
+
+    my @dupes = qw(a a b c c c a);
+    RSPM::Foo142::test_dedup(@dupes);
+    print('deduplicated: ', join(', ', @dupes), "\n");
+}
+
+print("Testing ensure_package\n");
+my $undeclared = RSPM::Foo142::test_bless_undeclared('RSPM::Never::Declared');
+print('blessed into undeclared package: ', ref($undeclared), "\n");
+
+print("Testing Hash::clear and Array::clear\n");
+{
+    my $elem = {};
+    my $hash = { a => 1, b => $elem };
+    my $same_hash = $hash;
+    my $array = [1, 2, $elem];
+    my $same_array = $array;
+    print('element referenced ', RSPM::Foo142::test_ref_count($elem), " times before clearing\n");
+    print('cleared hash length: ', RSPM::Foo142::test_clear($hash), ', other reference sees ',
+        scalar(keys %$same_hash), " keys\n");
+    print('cleared array length: ', RSPM::Foo142::test_clear($array), ', other reference sees ',
+        scalar(@$same_array), " elements\n");
+    print('element referenced ', RSPM::Foo142::test_ref_count($elem), " times after clearing\n");
+}
+
+print("Testing manually registered exports\n");
+my ($manual_xs_name, $manual_proto) = RSPM::Foo142::test_register_manual();
+print("registered $manual_xs_name with prototype '$manual_proto'\n");
+print('RSPM::Manual::add_one(41) = ', RSPM::Manual::add_one(41), ', prototype: ',
+    prototype('RSPM::Manual::add_one'), "\n");
+
+print("Testing canonical booleans\n");
+for my $b (1, 0) {
+    my ($serialized, $manual) = RSPM::Foo142::test_bool_values($b);
+    print("bool $b: serialized '$serialized', manual '$manual', ",
+        ($serialized eq $manual && $serialized == $manual ? 'consistent' : 'INCONSISTENT'), "\n");
+}
+
+print("Testing option_as_list\n");
+{
+    my @list = RSPM::Foo142::test_option_as_list(undef);
+    my $scalar = RSPM::Foo142::test_option_as_list(undef);
+    print('None in list context: ', scalar(@list), ' values, in scalar context: ',
+        (defined($scalar) ? 'defined' : 'undef'), "\n");
+    @list = RSPM::Foo142::test_option_as_list('x');
+    $scalar = RSPM::Foo142::test_option_as_list('x');
+    print("Some in list context: @list, in scalar context: $scalar\n");
+}
+
+print("Testing eval\n");
+for my $test (['1 + 2', 'scalar'], ['(1, 2, 3)', 'list'], ['(4, 5, 6)', 'scalar'], ['7', 'void']) {
+    my ($code, $context) = @$test;
+    my $results = RSPM::Foo142::test_eval($code, $context);
+    print("eval '$code' in $context context: [@$results]\n");
+}
+eval { RSPM::Foo142::test_eval('die "boom\n"', 'scalar') };
+print("eval die: $@");
+eval { RSPM::Foo142::test_eval('1 +', 'scalar') };
+print('eval syntax error: ', ($@ =~ /syntax error at \(eval \d+\) line 1/ ? 'caught' : "unexpected: $@"), "\n");
+
+print("Testing from_borrowed_value\n");
+print(RSPM::Foo142::test_borrowed_value('top', { a => { name => 'first', size => 1 }, b => { name => 'second', size => 2 } }), "\n");
+
+print("Testing combined_bootstrap\n");
+# Any perlmod generated .pm file's bootstrap helper will do to load the combined symbol:
+RSPM::Foo142::bootstrap('RSPM::Combined');
+print('RSPM::CombinedA::name() = ', RSPM::CombinedA::name(), ', RSPM::CombinedB::name() = ',
+    RSPM::CombinedB::name(), "\n");
+
+print("Testing hash_key\n");
+{
+    my $upgraded = "\xe9";
+    utf8::upgrade($upgraded);
+    my $encoded = "\xe9";
+    utf8::encode($encoded);
+    my %keys = map { $_ => 1 } ("\xe9", $upgraded, $encoded, "\x{263a}");
+    for my $test (['byte', "\xe9"], ['upgraded', $upgraded], ['encoded', $encoded], ['wide', "\x{263a}"]) {
+        my ($name, $value) = @$test;
+        print("$name: ", RSPM::Foo142::test_hash_key($value), "\n");
+    }
+    print('distinct perl hash keys: ', scalar(keys %keys), "\n");
+}
+
+print("Testing flatten\n");
+print(RSPM::Foo142::test_flatten({ name => 'flat', count => 3, list => [1, 2], nested => { a => 1 } }), "\n");
+
+print("Testing deep_clone\n");
+{
+    my $shared = [1, 2];
+    my $orig = { list => $shared, again => $shared, nested => { name => 'orig' } };
+    $orig->{self} = $orig;
+    my $copy = RSPM::Foo142::test_deep_clone($orig);
+    $copy->{nested}->{name} = 'copy';
+    push $copy->{list}->@*, 3;
+    print("orig name: $orig->{nested}->{name}, copy name: $copy->{nested}->{name}\n");
+    print("orig list: [@$shared], copy list: [$copy->{list}->@*], copy shares list: ",
+        ($copy->{list} == $copy->{again} ? 1 : 0), "\n");
+    print('copy cycle points to copy: ', ($copy->{self} == $copy ? 1 : 0), "\n");
+    delete $orig->{self};
+    delete $copy->{self};
+
+    eval { RSPM::Foo142::test_deep_clone({ obj => bless({}, 'Some::Class') }) };
+    print("blessed: $@");
+    eval { RSPM::Foo142::test_deep_clone([sub { 1 }]) };
+    print("code: $@");
+}
+
+print("Testing as_raw_fd\n");
+{
+    open(my $fh, '<', 'test.pl') or die "failed to open test.pl: $!\n";
+    print('first line via fd: ', RSPM::Foo142::test_raw_fd($fh));
+    print('handle still open: ', (defined(fileno($fh)) ? 1 : 0), "\n");
+    close($fh);
+    print('closed handle: ', RSPM::Foo142::test_raw_fd($fh) // 'undef', "\n");
+    print('not a handle: ', RSPM::Foo142::test_raw_fd([]) // 'undef', "\n");
+}
+
+print("Testing export aliases\n");
+print('test_aliased(21) = ', RSPM::Foo142::test_aliased(21), ', test_aliased_old(21) = ',
+    RSPM::Foo142::test_aliased_old(21), "\n");
+
+print("Testing map_as_list\n");
+{
+    my %hash = RSPM::Foo142::test_map_as_list(2);
+    my $ref = RSPM::Foo142::test_map_as_list(2);
+    my @empty = RSPM::Foo142::test_map_as_list(0);
+    print('list context: ', join(', ', map { "$_=$hash{$_}" } sort keys %hash), "\n");
+    print('scalar context: ', ref($ref), ' with ', join(', ', map { "$_=$ref->{$_}" } sort keys %$ref), "\n");
+    print('empty map in list context: ', scalar(@empty), " values\n");
+}
+
+print("Testing new_latin1\n");
+{
+    my $latin1 = RSPM::Foo142::test_latin1();
+    print('length: ', length($latin1), ', utf8 flag: ', (utf8::is_utf8($latin1) ? 1 : 0),
+        ', equals "caf\xe9": ', ($latin1 eq "caf\xe9" ? 1 : 0), "\n");
+}
+{
+    my @pairs = RSPM::Foo142::test_pairs_as_list();
+    my $ref = RSPM::Foo142::test_pairs_as_list();
+    print("pairs in list context: @pairs\n");
+    print('pairs in scalar context: ', ref($ref), ' with ', join(', ', map { "$_=$ref->{$_}" } sort keys %$ref), "\n");
+    my @list = RSPM::Foo142::test_list_not_flattened();
+    print('map_as_list with a list: ', scalar(@list), ' ', ref($list[0]), "\n");
+}
+
+print("Testing SortableValue\n");
+{
+    my @list = ('pear', 10, 'apple', 9, 'Zebra', 1.5);
+    my $sorted = RSPM::Foo142::test_sort(@list);
+    print("sorted: @$sorted\n");
+    print('matches perl sort: ', ("@$sorted" eq join(' ', sort @list) ? 1 : 0), "\n");
+
+    package RSPM::Reversed {
+        use overload 'cmp' => sub { my ($a, $b, $swap) = @_; my $r = "$b" cmp $$a; $swap ? -$r : $r },
+            '""' => sub { ${$_[0]} };
+    }
+    package RSPM::BrokenCmp {
+        use overload 'cmp' => sub { die "broken cmp\n" }, '""' => sub { 'broken' };
+    }
+    my @objects = map { bless(\(my $name = $_), 'RSPM::Reversed') } qw(a c b);
+    $sorted = RSPM::Foo142::test_sort(@objects);
+    print("sorted by overloaded cmp: @$sorted\n");
+    print('str_cmp via overload: ', RSPM::Foo142::test_str_cmp($objects[0], 'b'), "\n");
+    eval { RSPM::Foo142::test_str_cmp(bless({}, 'RSPM::BrokenCmp'), 'x') };
+    print("str_cmp error: $@");
+}
+
+print("Testing is_true\n");
+{
+    package RSPM::False {
+        use overload 'bool' => sub { 0 };
+    }
+}
+# unlike perl, is_true ignores the `bool` overload of RSPM::False
+for my $value (undef, 0, '0', '', '0.0', '00', 1, 'a', [], {}, bless({}, 'RSPM::False')) {
+    my $repr = !defined($value) ? 'undef' : ref($value) ? ref($value) : "'$value'";
+    print("$repr: ", (RSPM::Foo142::test_is_true($value) ? 'true' : 'false'),
+        ', perl: ', ($value ? 'true' : 'false'), "\n");
+}
+
+print("Testing from_value_strict_bool\n");
+for my $value (1, 0, '1', '0', '', !!1, !!0, 2, 'yes', undef) {
+    my $repr = defined($value) ? "'$value'" : 'undef';
+    my $result = eval { RSPM::Foo142::test_strict_bool($value) ? 'true' : 'false' };
+    if (defined($result)) {
+        print("$repr: $result\n");
+    } else {
+        print("$repr: $@");
+    }
+}
+
+print("Testing install_xsub\n");
+{
+    my $code = RSPM::Foo142::test_install_xsub();
+    print('installed: ', (defined(&RSPM::Runtime::double) ? 1 : 0), ', prototype: ',
+        prototype('RSPM::Runtime::double'), "\n");
+    print('RSPM::Runtime::double(21) = ', RSPM::Runtime::double(21), ', via ref: ', $code->(4),
+        ', same sub: ', ($code == \&RSPM::Runtime::double ? 1 : 0), "\n");
+}
+
+print("Testing tuple struct returns\n");
+{
+    my @pair = RSPM::Foo142::test_tuple_struct();
+    my $last = RSPM::Foo142::test_tuple_struct();
+    my @wrapped = RSPM::Foo142::test_wrapped_tuple_struct();
+    print("tuple struct: @pair, scalar context: $last, wrapped: @wrapped\n");
+}
+
+print("Testing save_scalar\n");
+{
+    no warnings 'once';
+    $RSPM::Foo142::LOCALIZED = 'global';
+    RSPM::Foo142::test_local(sub {
+        print('in callback: ', $RSPM::Foo142::LOCALIZED // 'undef', "\n");
+    });
+    print("after: $RSPM::Foo142::LOCALIZED\n");
+
+    # perl leaves the guard's scope when the xsub returns, dropping it later does nothing
+    RSPM::Foo142::test_keep_local(1);
+    print("guard kept: $RSPM::Foo142::LOCALIZED\n");
+    RSPM::Foo142::test_keep_local(0);
+    print("guard dropped: $RSPM::Foo142::LOCALIZED\n");
+
+    # dropping guards out of order warns and leaves the outer scope to perl
+    local $SIG{__WARN__} = sub { print('warning: ', $_[0] =~ s/ at \S+ line \d+\.$//r) };
+    $RSPM::Foo142::LOCALIZED_INNER = 'global';
+    RSPM::Foo142::test_local_out_of_order(sub {
+        print("in callback: $RSPM::Foo142::LOCALIZED, $RSPM::Foo142::LOCALIZED_INNER\n");
+    });
+    print("after: $RSPM::Foo142::LOCALIZED, $RSPM::Foo142::LOCALIZED_INNER\n");
+}
+
+print("Testing raw_return with Option<Value>\n");
+print('Some: ', RSPM::Foo142::test_raw_option(1), ', None: ',
+    RSPM::Foo142::test_raw_option(0) // 'undef', "\n");
+
+print("Testing get_path\n");
+{
+    my $data = { a => [ {}, 'scalar', { b => 'found' } ] };
+    print('$data->{a}[2]{b}: ', RSPM::Foo142::test_get_path($data, 2) // 'undef', "\n");
+    print('$data->{a}[1]{b}: ', RSPM::Foo142::test_get_path($data, 1) // 'undef', "\n");
+    print('$data->{a}[5]{b}: ', RSPM::Foo142::test_get_path($data, 5) // 'undef', "\n");
+    print('ref to ref: ', RSPM::Foo142::test_get_path(\$data, 2) // 'undef', "\n");
+}
+
+print("Testing debug_nested\n");
+{
+    my $cyclic = [1, 'two', { three => [3] }, \'four'];
+    push @$cyclic, $cyclic;
+    print('depth 8: ', RSPM::Foo142::test_debug_nested($cyclic, 8), "\n");
+    print('depth 1: ', RSPM::Foo142::test_debug_nested($cyclic, 1), "\n");
+    my $self_ref;
+    $self_ref = \$self_ref;
+    print('self reference: ', RSPM::Foo142::test_debug_nested($self_ref, 8), "\n");
+
+    # printing a hash must not disturb an ongoing `each` loop over it
+    my %hash = map { ($_ => [$_]) } 1..5;
+    my $seen = 0;
+    while (my ($key) = each %hash) {
+        RSPM::Foo142::test_debug_nested(\%hash, 8);
+        last if ++$seen > 10;
+    }
+    print("each while printing: $seen\n");
+    $seen = 0;
+    while (my ($key) = each %hash) {
+        delete $hash{$key};
+        RSPM::Foo142::test_debug_nested(\%hash, 8);
+        last if ++$seen > 10;
+    }
+    print('each while deleting and printing: ', $seen, ', left: ', scalar(keys %hash), "\n");
+}
+
+print("Testing lossless numbers\n");
+{
+    my $ts = RSPM::Foo142::test_timestamps({
+        unix_nanos => 1700000000123456789,
+        fractional_secs => 1700000000.25,
+        wide => '-85070591730234615865843651857942052864',
+    });
+    print("unix_nanos: $ts->{unix_nanos}, fractional_secs: $ts->{fractional_secs}, wide: $ts->{wide}\n");
+    $ts = RSPM::Foo142::test_timestamps($ts);
+    print("round trip: unix_nanos: $ts->{unix_nanos}, wide: $ts->{wide}\n");
+    eval { RSPM::Foo142::test_timestamps({ %$ts, unix_nanos => '42' }) };
+    print("in-range string for a u64: ", ($@ ? 'rejected' : 'accepted'), "\n");
+}
+
+print("Testing to_json_string\n");
+{
+    # single keys only, perl's hash order is not stable
+    print(RSPM::Foo142::test_json({ data => { list => [1, 'two', undef] } }), "\n");
+    print(RSPM::Foo142::test_json({ nested => bless({ inner => 1.5 }, 'RSPM::Blessed') }), "\n");
+    print(RSPM::Foo142::test_json('plain string'), "\n");
+    print(RSPM::Foo142::test_json([1, { key => 'value' }]), "\n");
+}
+
+print("Testing looks_like_number\n");
+for my $value ('42', ' 1.5e3 ', 'abc', '', '0x10', 'Inf', 17) {
+    my ($number, $string_only) = RSPM::Foo142::test_looks_like_number($value);
+    print("'$value': ", ($number ? 'number' : 'not a number'), ", string only: ", ($string_only ? 1 : 0), "\n");
+}
+
+print("Testing set_undef\n");
+{
+    my $out = 'populated';
+    my $ref = \$out;
+    RSPM::Foo142::test_set_undef($ref);
+    print('defined: ', (defined($out) ? 1 : 0), ', same scalar: ', ($ref == \$out ? 1 : 0), "\n");
+}
+
+print("Testing float NaN/Inf round-trip\n");
+{
+    require B;
+    my $inf = 9**9**9;
+    for my $value ($inf, -$inf, $inf - $inf, 0 + 'NaN', 0 + '-Inf', 1.5, 0.25) {
+        my ($f64, $f32, $same) = RSPM::Foo142::test_float_round_trip($value);
+        my $kind = (B::svref_2object(\$f64)->FLAGS & B::SVf_POK()) ? 'string' : 'number';
+        print("'$value': f64=$f64 f32=$f32 ($kind), round-trip: ", ($same ? 'ok' : 'FAILED'), "\n");
+    }
+    eval { RSPM::Foo142::test_float_round_trip('1.5') };
+    print("numeric string for a float: ", ($@ ? 'rejected' : 'accepted'), "\n");
+}
+
+print("Testing Array::into_vec and Hash::into_hashmap\n");
+{
+    my @list = (1, undef, 3);
+    my %map = (a => [1, 2], b => [], c => [3]);
+    print(RSPM::Foo142::test_collect(@list, %map), "\n");
+    my $result = eval { RSPM::Foo142::test_collect(@list, %{{ a => 'not a list' }}) };
+    print(defined($result) ? "unexpected success\n" : $@);
+}
+
+print("Testing overload_op\n");
+{
+    package RSPM::Num {
+        use overload
+            '+' => sub { RSPM::Num->new($_[0]{v} + (ref($_[1]) ? $_[1]{v} : $_[1])) },
+            '<=>' => sub {
+                my ($a, $b, $swapped) = @_;
+                my $r = $a->{v} <=> (ref($b) ? $b->{v} : $b);
+                $swapped ? -$r : $r
+            },
+            '""' => sub { die "cannot stringify negative numbers\n" if $_[0]{v} < 0; "Num($_[0]{v})" };
+        sub new { bless { v => $_[1] }, $_[0] }
+    }
+
+    my ($sum, $cmp, $str) = RSPM::Foo142::test_overload(RSPM::Num->new(3), RSPM::Num->new(4));
+    print("sum: $sum, cmp: $cmp, string: $str\n");
+    ($sum, $cmp, $str) = RSPM::Foo142::test_overload(RSPM::Num->new(5), 2);
+    print("sum: $sum, cmp: $cmp, string: $str\n");
+    my $result = eval { RSPM::Foo142::test_overload(RSPM::Num->new(-1), 2); 1 };
+    print($result ? "unexpected success\n" : $@);
+    $result = eval { RSPM::Foo142::test_overload(1, 2); 1 };
+    print($result ? "unexpected success\n" : $@);
+    $result = eval { RSPM::Foo142::test_overload_without_operand(RSPM::Num->new(1)); 1 };
+    print($result ? "unexpected success\n" : $@);
+}
+
+print("Testing Array::into_ref and Hash::into_ref\n");
+{
+    my ($list, $map) = RSPM::Foo142::test_into_ref(3);
+    print(ref($list), ': ', join(', ', @$list), "\n");
+    print(ref($map), ': ', join(', ', map { "$_=$map->{$_}" } sort keys %$map), "\n");
+}
+
+print("Testing serializer state after a panic during deserialization\n");
+{
+    my ($panicked, $before, $after, $lenient) = RSPM::Foo142::test_panic_in_deserialize();
+    print("panicked: ", ($panicked ? 1 : 0), ", raw values active before: ", ($before ? 1 : 0),
+        ", after: ", ($after ? 1 : 0), ", strict bool reset: ", ($lenient ? 1 : 0), "\n");
+}
+
+print("Testing Value::bless_box_magic\n");
+{
+    my $direct = RSPM::Magic->new_direct('direct magic');
+    print('class: ', ref($direct), "\n");
+    $direct->call();
+    test_unsafe_clone(Storable::dclone($direct));
+    undef $direct;
+    print("direct magic dropped\n");
+}
+
+print("Testing #[validate] parameters\n");
+for my $args (
+    [{ min => 1, max => 5 }],
+    [{ min => 1, max => 5 }, { min => 2, max => 3 }],
+    [{ min => 5, max => 1 }],
+    [{ min => 1, max => 5 }, { min => 3, max => 2 }],
+) {
+    my $result = eval { &RSPM::Foo142::test_validate(@$args) };
+    print(defined($result) ? "ok: $result\n" : $@);
+}
+
+print("Testing Value::write_all\n");
+{
+    open(my $fh, '>', \my $buffer) or die "failed to open in-memory file handle: $!\n";
+    print {$fh} 'perl, ';
+    RSPM::Foo142::test_write_all($fh, "rust\nmore rust\n");
+    close($fh);
+    print("plain: ", join('|', split(/\n/, $buffer)), "\n");
+
+    open($fh, '>:crlf', \my $crlf) or die "failed to open in-memory file handle: $!\n";
+    RSPM::Foo142::test_write_all($fh, "a\nb\n");
+    close($fh);
+    print("crlf: ", ($crlf eq "a\r\nb\r\n" ? 'ok' : 'wrong'), "\n");
+
+    open($fh, '<', \'read only') or die "failed to open in-memory file handle: $!\n";
+    eval { RSPM::Foo142::test_write_all($fh, "x") };
+    print($@);
+    eval { RSPM::Foo142::test_write_all('not a handle', "x") };
+    print($@);
+}
+
+print("Testing ReturnList\n");
+{
+    my @list = RSPM::Foo142::test_return_list(3);
+    print('list: ', join(', ', @list), "\n");
+    my $scalar = RSPM::Foo142::test_return_list(3);
+    print("scalar: $scalar\n");
+    @list = RSPM::Foo142::test_return_list(0);
+    $scalar = RSPM::Foo142::test_return_list(0);
+    print('empty: ', scalar(@list), ' values, scalar: ', ($scalar // 'undef'), "\n");
+    RSPM::Foo142::test_return_list(3);
+    @list = RSPM::Foo142::test_return_list_fn([1, [2], { three => 3 }]);
+    print('return_list: ', join(', ', map { ref($_) || $_ } @list), "\n");
+}
+
+print("Testing xs_file package attribute\n");
+{
+    require B;
+    print('RSPM::Magic::call: ', B::svref_2object(\&RSPM::Magic::call)->FILE, "\n");
+    print('RSPM::Foo142::foo142: ', B::svref_2object(\&RSPM::Foo142::foo142)->FILE, "\n");
+    # the generated .pm files quote the same file names for their bootstrap xsubs
+    for my $pm ($INC{'RSPM/Magic.pm'}, $INC{'RSPM/Foo142.pm'}) {
+        open(my $fh, '<', $pm) or die "failed to open $pm: $!\n";
+        print("$pm: $_\n") for map { /dl_install_xsub\(.*, (.*)\);/ ? $1 : () } <$fh>;
+    }
+}
+
+print("Testing Value::new_ref_owned\n");
+{
+    my ($borrowed, $owned, $ref) = RSPM::Foo142::test_new_ref_owned();
+    print("new_ref: $borrowed, new_ref_owned: $owned, in perl: ", Internals::SvREFCNT(@$ref),
+        ", contents: @$ref\n");
+}
+
+print("Testing #[class] parameters\n");
+{
+    print(RSPM::Foo142->test_class(), "\n");
+    print(bless({}, 'RSPM::Foo142')->test_class(), "\n");
+    print(RSPM::Foo142::test_class(bless({}, 'HASH')), "\n");
+    for my $invocant ([], undef) {
+        eval { RSPM::Foo142::test_class($invocant) };
+        print($@);
+    }
+}
+
+print("Testing LazyList\n");
+{
+    my @list = RSPM::Foo142::test_lazy_list(3);
+    print('list: ', join(', ', @list), "\n");
+    my $count = RSPM::Foo142::test_lazy_list(3);
+    print("scalar: $count\n");
+    @list = RSPM::Foo142::test_lazy_list(0);
+    $count = RSPM::Foo142::test_lazy_list(0);
+    print('empty: ', scalar(@list), " values, scalar: $count\n");
+}
+
+print("Testing Hash::rename_key\n");
+{
+    my %hash = (old => [1, 2], other => 'x');
+    my $value = $hash{old};
+    my $renamed = RSPM::Foo142::test_rename_key(%hash, 'old', 'new');
+    print("renamed: ", ($renamed ? 1 : 0), ', keys: ', join(', ', sort keys %hash),
+        ', same value: ', ($hash{new} == $value ? 1 : 0), "\n");
+    $renamed = RSPM::Foo142::test_rename_key(%hash, 'missing', 'other');
+    print("renamed: ", ($renamed ? 1 : 0), ', keys: ', join(', ', sort keys %hash), ", other: $hash{other}\n");
+    $renamed = RSPM::Foo142::test_rename_key(%hash, 'other', 'new');
+    print("renamed: ", ($renamed ? 1 : 0), ', keys: ', join(', ', sort keys %hash), ", new: $hash{new}\n");
+}
+
+print("Testing ScalarRef::get_magic and set_magic\n");
+{
+    package RSPM::TiedCounter {
+        sub TIESCALAR { my $count = 0; bless \$count, $_[0] }
+        sub FETCH { my $self = shift; ++$$self; print("FETCH $$self\n"); "fetched $$self" }
+        sub STORE { my ($self, $value) = @_; print("STORE '$value'\n") }
+    }
+
+    tie my $tied, 'RSPM::TiedCounter';
+    RSPM::Foo142::test_magic_triggers(\$tied);
+}
+
+print("Testing Value::does\n");
+{
+    package RSPM::Role::Base { }
+    package RSPM::Shape { our @ISA = ('RSPM::Role::Base'); }
+    package RSPM::Circle {
+        our @ISA = ('RSPM::Shape');
+        my %roles = ('RSPM::Role::Drawable' => 1);
+        sub DOES { my ($self, $role) = @_; $roles{$role} || $self->SUPER::DOES($role) }
+    }
+    package RSPM::Broken { sub DOES { die "broken DOES\n" } }
+
+    my $circle = bless {}, 'RSPM::Circle';
+    for my $role (qw(RSPM::Circle RSPM::Role::Base RSPM::Role::Drawable RSPM::Role::Missing)) {
+        print("circle does $role: ", (RSPM::Foo142::test_does($circle, $role) ? 1 : 0), "\n");
+    }
+    print("class does: ", (RSPM::Foo142::test_does('RSPM::Circle', 'RSPM::Role::Drawable') ? 1 : 0), "\n");
+    print("unblessed does: ", (RSPM::Foo142::test_does({}, 'HASH') ? 1 : 0), "\n");
+    print("broken does: ", (RSPM::Foo142::test_does(bless({}, 'RSPM::Broken'), 'RSPM::Broken') ? 1 : 0), "\n");
+    eval { die "original error\n" };
+    RSPM::Foo142::test_does(bless({}, 'RSPM::Broken'), 'RSPM::Broken');
+    print("\$@ after broken does: $@");
+}
+
+print("Testing PerlCallbackQueue\n");
+{
+    my $log = RSPM::Foo142::test_callback_queue(sub {
+        my ($hash, $message) = @_;
+        print("callback: from $hash->{from}, $message\n");
+    });
+    print("$_\n") for @$log;
+}
+
+print("Testing Array::element\n");
+{
+    my @counters = (0, 10, 20, 30);
+    my $ref = \$counters[3];
+    RSPM::Foo142::test_array_element(@counters, 3, 'five');
+    RSPM::Foo142::test_array_element(@counters, 3, 'five');
+    print('counters: ', join(', ', map { $_ // 'undef' } @counters), ", alias: $$ref\n");
+
+    my @strings = ("caf\x{e9}");
+    RSPM::Foo142::test_array_element_bytes(@strings, 0);
+    print('set_bytes: length ', length($strings[0]),
+        ', utf8: ', (utf8::is_utf8($strings[0]) ? 1 : 0), "\n");
+}
+
+print("Testing empty_as_undef\n");
+{
+    my $show;
+    $show = sub {
+        my ($value) = @_;
+        return 'undef' if !defined($value);
+        return '[' . join(', ', map { $show->($_) } @$value) . ']' if ref($value);
+        return $value;
+    };
+    print('default: ', $show->(RSPM::Foo142::test_empty_lists(2)), "\n");
+    print('empty_as_undef: ', $show->(RSPM::Foo142::test_empty_as_undef(2)), "\n");
+    print('empty_as_undef: ', $show->(RSPM::Foo142::test_empty_as_undef(0)), "\n");
+    my ($default, $undef) = RSPM::Foo142::test_to_value_empty_as_undef();
+    print('to_value: ', $show->($default), ', to_value_empty_as_undef: ', $show->($undef), "\n");
+    print('nested to_value: ', $show->(RSPM::Foo142::test_nested_to_value_empty_as_undef()), "\n");
+}
+
+print("Testing in-place scalar setters\n");
+{
+    my $show = sub {
+        my ($value) = @_;
+        return 'undef' if !defined($value);
+        $value =~ s/([^\x00-\x7f])/sprintf('\\x{%x}', ord($1))/ge;
+        return "'$value'";
+    };
+
+    package RSPM::TiedStore {
+        sub TIESCALAR { my $value; bless \$value, $_[0] }
+        sub FETCH { ${$_[0]} }
+        sub STORE {
+            my ($self, $value) = @_;
+            $$self = $value;
+            print('STORE ', $show->($value), "\n");
+        }
+    }
+
+    tie my $tied, 'RSPM::TiedStore';
+    my $plain = 'original';
+    my $alias = \$plain;
+    for my $kind (qw(int uint float string bytes copy undef)) {
+        RSPM::Foo142::test_set_in_place(\$tied, $kind);
+        RSPM::Foo142::test_set_in_place(\$plain, $kind);
+        print("plain $kind: ", $show->($$alias),
+            ', utf8: ', (utf8::is_utf8($plain) ? 1 : 0), "\n");
+    }
+}
+
+print("Testing CachedCall\n");
+{
+    my %calls;
+    my $values = RSPM::Foo142::test_cached_call(sub {
+        my ($key) = @_;
+        ++$calls{$key};
+        return "value of $key";
+    }, ['a', 'b', 'a', 'a', 'b', 'a']);
+    print('values: ', join(', ', @$values), "\n");
+    print('calls: ', join(', ', map { "$_=$calls{$_}" } sort keys %calls), "\n");
+}
+
+print("Testing Value::new_iterator\n");
+{
+    my $iter = RSPM::Foo142::test_iterator(3, undef);
+    while (defined(my $item = $iter->())) {
+        print("got $item\n");
+    }
+    print('exhausted: ', (defined($iter->()) ? 'no' : 'yes'), "\n");
+    undef $iter;
+
+    $iter = RSPM::Foo142::test_iterator(3, 1);
+    for (1..4) {
+        my $item = eval { $iter->() };
+        print(defined($item) ? "got $item\n" : $@ ? "error: $@" : "end\n");
+    }
+    undef $iter;
+}
+
+print("Testing warnings from the default magic drop handler\n");
+{
+    local $SIG{__WARN__} = sub {
+        my ($message) = @_;
+        my $located = $message =~ s/ at test\.pl line \d+\.\n$/\n/;
+        print("caught warning: $message");
+        print('located at the caller: ', ($located ? 1 : 0), "\n");
+    };
+    RSPM::Foo142::test_null_magic_drop();
+}
+
+print("Testing ScalarRef::bytes_mut\n");
+{
+    my $buffer = 'perl buffer';
+    my ($edited, $number, $shared, $perl, $utf8) = @{RSPM::Foo142::test_bytes_mut(\$buffer)};
+    print("edited: $edited, number: $number\n");
+    print("editable: shared=$shared, perl variable=$perl, utf8=$utf8\n");
+}
+
+print("Testing reference cycles during serialization\n");
+{
+    my $cyclic = { name => 'root' };
+    $cyclic->{self} = $cyclic;
+    eval { RSPM::Foo142::test_json($cyclic) };
+    print("cyclic hash: $@");
+    delete $cyclic->{self};
+
+    my $list = [1];
+    push @$list, \$list;
+    eval { RSPM::Foo142::test_json($list) };
+    print("cyclic array: $@");
+    pop @$list;
+
+    my $shared = [1, 2];
+    print('shared: ', RSPM::Foo142::test_json([$shared, $shared]), "\n");
+}
+
+print("Testing accessors\n");
+{
+    my $show = sub { defined($_[0]) ? "'$_[0]'" : 'undef' };
+    my $obj = bless { label => 'initial' }, 'RSPM::Foo142';
+    print('get: ', $show->($obj->label), "\n");
+    print('set: ', $show->($obj->label('changed')), ', get: ', $show->($obj->label), "\n");
+    print('set undef: ', $show->($obj->label(undef)), ', exists: ',
+        (exists($obj->{label}) ? 1 : 0), "\n");
+    eval { $obj->label(1, 2) };
+    print($@);
+    print('rust name installed: ', (defined(&RSPM::Foo142::test_accessor) ? 1 : 0), "\n");
+}
+
+print("Testing from_value_lenient\n");
+{
+    my ($settings, $warnings) = RSPM::Foo142::test_lenient(
+        { count => '42', name => 7, enabled => 'yes', ratio => '0.5' });
+    print("$settings\n");
+    print("warning: $_\n") for sort @$warnings;
+    ($settings, $warnings) = RSPM::Foo142::test_lenient(
+        { count => 42, name => 'seven', enabled => 1, ratio => 0.5 });
+    print("$settings, warnings: ", scalar(@$warnings), "\n");
+    eval { RSPM::Foo142::test_lenient({ count => 'many', name => '', enabled => 0, ratio => 1 }) };
+    print($@);
+}
+
+print("Testing arrays of pairs\n");
+{
+    for my $value ([[b => 2], [a => 1], [c => 3]], { b => 2, a => 1, c => 3 }, [[a => 1], 'b']) {
+        my ($map, $list) = RSPM::Foo142::test_pairs($value);
+        print("map: $map\nlist: $list\n");
+    }
+}
+
+print("Testing Gimme::map and Gimme::void_map\n");
+{
+    my $ctx;
+    RSPM::Foo142::test_context(\$ctx);
+    print("void: $ctx\n");
+    my $scalar = RSPM::Foo142::test_context(\$ctx);
+    print("scalar: $ctx, returned '$scalar'\n");
+    my @list = RSPM::Foo142::test_context(\$ctx);
+    print("list: $ctx, returned '@list'\n");
+}
+
+print("Testing ScalarRef::string_hash\n");
+{
+    use Hash::Util ();
+    my $latin1 = "caf\xe9";
+    my $upgraded = $latin1;
+    utf8::upgrade($upgraded);
+    for my $value ('key', 42, $latin1, $upgraded, "\x{100}") {
+        print(RSPM::Foo142::test_string_hash($value) == Hash::Util::hash_value($value) ? 1 : 0);
+    }
+    print(' ', (RSPM::Foo142::test_string_hash($upgraded) == Hash::Util::hash_value($latin1))
+        ? 1 : 0, "\n");
+}
+
+print("Testing make_package!\n");
+{
+    RSPM::Foo142::bootstrap('RSPM::Made');
+    print('double: ', RSPM::Made::double(21), ', half: ', RSPM::Made::half(84),
+        ', halve: ', RSPM::Made::halve(84), ', add_one: ', RSPM::Made::add_one(41), "\n");
+    print('prototype: ', prototype('RSPM::Made::double'), "\n");
+}
+
+print("Testing from_blessed_value\n");
+{
+    for my $object (
+        bless({ radius => 1.5 }, 'RSPM::Circle'),
+        bless([[0, 0], [2, 0], [1, 1]], 'RSPM::Polygon'),
+        bless({ radius => 1 }, 'RSPM::Square'),
+        { radius => 1 },
+        'RSPM::Circle',
+    ) {
+        print(RSPM::Foo142::test_blessed_enum($object), "\n");
+    }
+}
+
+print("Testing Hash::insert_if_absent\n");
+{
+    my $show = sub { defined($_[0]) ? "'$_[0]'" : 'undef' };
+    my $config = { port => 8006, user => undef };
+    my $inserted = RSPM::Foo142::test_insert_if_absent(%$config);
+    print("inserted: @$inserted\n");
+    print(join(', ', map { "$_ => " . $show->($config->{$_}) } sort keys %$config), "\n");
+    $inserted = RSPM::Foo142::test_insert_if_absent(%$config);
+    print('inserted again: ', scalar(@$inserted), "\n");
+
+    package InsertTie {
+        sub TIEHASH { bless { data => {}, log => [] }, shift }
+        sub EXISTS { push @{$_[0]{log}}, "EXISTS $_[1]"; exists $_[0]{data}{$_[1]} }
+        sub STORE { push @{$_[0]{log}}, "STORE $_[1]"; $_[0]{data}{$_[1]} = $_[2] }
+        sub FETCH { $_[0]{data}{$_[1]} }
+    }
+    tie my %tied, 'InsertTie';
+    $tied{port} = 1;
+    $inserted = RSPM::Foo142::test_insert_if_absent(%tied);
+    print("tied inserted: @$inserted, log: @{(tied %tied)->{log}}\n");
+}
+
+print("Testing ScalarRef::to_number and to_string_scalar\n");
+{
+    no warnings 'numeric';
+    require B;
+    require Scalar::Util;
+    my $flags = sub {
+        my $f = B::svref_2object(\$_[0])->FLAGS;
+        join('', ($f & B::SVf_IOK() ? 'I' : ''), ($f & B::SVf_NOK() ? 'N' : ''),
+            ($f & B::SVf_POK() ? 'P' : ''));
+    };
+    for my $value (Scalar::Util::dualvar(5, 'five'), '42', ' 1.5 ', 7, 2.5, "caf\x{e9}\x{263a}") {
+        my $out = RSPM::Foo142::test_single_typed($value);
+        my ($number, $string) = @$out;
+        print("number: $number (", $flags->($out->[0]), "), string: '",
+            ($string =~ s/([^\x00-\x7f])/sprintf('\\x{%x}', ord($1))/ger), "' (",
+            $flags->($out->[1]), ")\n");
+    }
+}
+
+print("Testing Array::append\n");
+{
+    my @target = ('a', 'b');
+    my @other = (1, 2, 3);
+    print('length: ', RSPM::Foo142::test_append(@target, @other), ", elements: @target\n");
+    $target[2] = 'changed';
+    print("shared element: $other[0], other: @other\n");
+    my @sparse = (1);
+    $sparse[2] = 3;
+    RSPM::Foo142::test_append(@target, @sparse);
+    print('sparse: ', join(', ', map { $_ // 'undef' } @target[5..7]), "\n");
+    my @twice = (1, 2);
+    print('self: ', RSPM::Foo142::test_append(@twice, @twice), ", elements: @twice\n");
+}
+
+print("Testing multiplicity\n");
+{
+    require Config;
+    my ($const, $runtime) = RSPM::Foo142::test_multiplicity();
+    my $perl = ($Config::Config{usemultiplicity} // '') eq 'define' ? 1 : 0;
+    print('const matches glue: ', ($const == $runtime ? 1 : 0), ', matches perl: ',
+        ($runtime == $perl ? 1 : 0), "\n");
+}
+
+print("Testing ScalarRef::is_blessed and blessed_class\n");
+{
+    for my $value (bless({}, 'RSPM::Object'), bless([], 'ARRAY'), [], \1, 'RSPM::Object', undef) {
+        my ($blessed, $class) = RSPM::Foo142::test_blessed_class($value);
+        print(ref($value) || 'plain', ': blessed ', ($blessed ? 1 : 0), ', class ',
+            $class // 'undef', "\n");
+    }
+}
+
+print("Testing borrowed string returns\n");
+{
+    require B;
+    my $text = "  caf\x{e9} cr\x{e8}me ";
+    for my $result (
+        \(RSPM::Foo142::test_borrowed_str_return($text)),
+        \(RSPM::Foo142::test_borrowed_cow_return($text)),
+        \(RSPM::Foo142::test_borrowed_cow_return('copy:abc')),
+    ) {
+        my $shown = $$result =~ s/([^\x20-\x7e])/sprintf('\\x{%x}', ord($1))/ger;
+        print("[$shown] ", B::class(B::svref_2object($result)), "\n");
+    }
+    my $ascii = " plain ";
+    print('[', RSPM::Foo142::test_borrowed_str_return($ascii), "] [$ascii]\n");
+
+    # without substr_return, the result is a copy not affecting the parameter
+    for my $name (qw(test_copied_str_return test_borrowed_str_return)) {
+        my $func = \&{"RSPM::Foo142::$name"};
+        my $written = ' abc ';
+        for my $result ($func->($written)) {
+            $result = 'X';
+        }
+        my $changed = ' def ';
+        my $result = \($func->($changed));
+        $changed = 'zzzzzzz';
+        print("$name: written back: [$written], parameter changed later: [$$result]\n");
+    }
+}
+
+print("Testing serde_bytes fields\n");
+{
+    my $blob = RSPM::Foo142::test_serde_bytes({ name => 'blob', data => "a\0b\xff" });
+    my $data = $blob->{data};
+    print("name: $blob->{name}, ref: '", ref($data), "', utf8: ", (utf8::is_utf8($data) ? 1 : 0),
+        ', data: ', join(' ', map { sprintf('%02x', ord) } split(//, $data)), "\n");
+}
+
+print("Testing ScalarRef::numeric_eq and string_eq\n");
+{
+    package RSPM::EqOverload {
+        use overload
+            '==' => sub { die "no numbers here\n" if $_[0]{die}; $_[0]{id} == $_[1] },
+            'eq' => sub { "$_[0]{id}" eq "$_[1]" },
+            '""' => sub { "id-$_[0]{id}" };
+    }
+    my $nan = 9**9**9 / 9**9**9;
+    my $ref = [];
+    my $object = bless({ id => 3 }, 'RSPM::EqOverload');
+    my @cases = (
+        ['1', '1.0'],
+        ['abc', 'abc'],
+        [18446744073709551615, 18446744073709551614],
+        [-1, 18446744073709551615],
+        [$nan, $nan],
+        [$ref, $ref],
+        [[], []],
+        [$object, 3],
+        [$object, 'id-3'],
+    );
+    for my $case (@cases) {
+        my ($left, $right) = @$case;
+        no warnings 'numeric';
+        my ($num, $str) = RSPM::Foo142::test_perl_eq($left, $right);
+        my ($perl_num, $perl_str) = ($left == $right, $left eq $right);
+        print('numeric: ', ($num ? 1 : 0), ' (perl ', ($perl_num ? 1 : 0), '), string: ',
+            ($str ? 1 : 0), ' (perl ', ($perl_str ? 1 : 0), ")\n");
+    }
+    my $dies = bless({ id => 1, die => 1 }, 'RSPM::EqOverload');
+    eval { RSPM::Foo142::test_perl_eq($dies, 1) };
+    print($@);
+}
+
+print("Testing hashes modified during deserialization\n");
+{
+    package RSPM::HashMutator {
+        sub TIESCALAR {
+            my ($class, $hash, $action) = @_;
+            return bless { hash => $hash, action => $action }, $class;
+        }
+        sub FETCH { my ($self) = @_; $self->{action}->($self->{hash}); return 'fetched' }
+        sub STORE {}
+    }
+    my %actions = (
+        none => sub {},
+        insert => sub { $_[0]->{added} = 1 },
+        clear => sub { %{$_[0]} = () },
+        refill => sub { %{$_[0]} = (a => 'x', b => 'y', c => 'z') },
+        reset => sub { keys %{$_[0]} },
+    );
+    for my $action (sort keys %actions) {
+        my %hash = (a => 'x', b => 'y', c => 'z');
+        tie $hash{b}, 'RSPM::HashMutator', \%hash, $actions{$action};
+        my $result = eval { RSPM::Foo142::test_hash_modified(\%hash) };
+        print("$action: ", ($result ? join(',', map { "$_=$result->{$_}" } sort keys %$result) : $@),
+            ($@ ? '' : "\n"));
+    }
+}
+
+print("Testing #[context] parameters\n");
+{
+    my $ctx;
+    RSPM::Foo142::test_context_param(\$ctx);
+    print("void: $ctx\n");
+    my $scalar = RSPM::Foo142::test_context_param(\$ctx);
+    print("scalar: $ctx\n");
+    my @list = RSPM::Foo142::test_context_param(\$ctx);
+    print("list: $ctx\n");
+    eval { &RSPM::Foo142::test_context_param(\$ctx, 1) };
+    print($@);
+}
+
+print("Testing Array::insert and Array::swap_remove\n");
+{
+    my @list = qw(a b c d);
+    my $ref = \$list[2];
+    RSPM::Foo142::test_array_insert(@list, 1, 'x');
+    RSPM::Foo142::test_array_insert(@list, 5, 'end');
+    RSPM::Foo142::test_array_insert(@list, 0, 'start');
+    print("inserted: @list, moved: ", ($ref == \$list[4] ? 1 : 0), "\n");
+    eval { RSPM::Foo142::test_array_insert(@list, 8, 'far') };
+    print($@);
+    my $removed = RSPM::Foo142::test_array_swap_remove(@list, 1);
+    print("removed: $removed, left: @list\n");
+    $removed = RSPM::Foo142::test_array_swap_remove(@list, $#list);
+    print("removed: $removed, left: @list\n");
+    $removed = RSPM::Foo142::test_array_swap_remove(@list, 6);
+    print('out of bounds: ', $removed // 'undef', ", left: @list\n");
+    my @sparse = (1);
+    $sparse[3] = 4;
+    RSPM::Foo142::test_array_insert(@sparse, 1, 'x');
+    print('sparse: ', join(', ', map { $_ // 'undef' } @sparse), "\n");
+    require Tie::Array;
+    tie my @tied, 'Tie::StdArray';
+    @tied = (1, 2, 3);
+    RSPM::Foo142::test_array_insert(@tied, 1, 'x');
+    $removed = RSPM::Foo142::test_array_swap_remove(@tied, 0);
+    print("tied: removed $removed, left: @tied\n");
+}
+
+print("Testing FileHandle\n");
+{
+    my $data = "line 1\nline 2\n";
+    open(my $in, '<', \$data) or die "failed to open in-memory file: $!\n";
+    print(RSPM::Foo142::test_file_handle_read($in));
+    open($in, '<', \$data) or die "failed to open in-memory file: $!\n";
+    my $first = <$in>;
+    print('after readline: ', RSPM::Foo142::test_file_handle_read(*$in));
+    my $written = '';
+    open(my $out, '>', \$written) or die "failed to open in-memory file: $!\n";
+    RSPM::Foo142::test_file_handle_write({ prefix => '> ', handle => $out }, 'text');
+    RSPM::Foo142::test_file_handle_write({ prefix => '> ', handle => *$out{IO} }, ' more');
+    close($out);
+    print("written: $written\n");
+    for my $bad (undef, 'STDIN', []) {
+        eval { RSPM::Foo142::test_file_handle_read($bad) };
+        print($@);
+    }
+    eval { RSPM::Foo142::test_file_handle_write({ prefix => '', handle => 'no' }, '') };
+    print($@);
+    eval { RSPM::Foo142::test_file_handle_read($out) };
+    print($@);
+}
+
+print("Testing Hash::with_capacity\n");
+{
+    require Hash::Util;
+    my $buckets = sub { (split(m!/!, Hash::Util::bucket_ratio(%{$_[0]})))[1] };
+    for my $capacity (0, 100, 1000) {
+        my $hash = RSPM::Foo142::test_hash_with_capacity($capacity);
+        print("capacity $capacity: enough buckets: ", ($buckets->($hash) >= $capacity ? 1 : 0),
+            ', keys: ', join(',', keys %$hash), "\n");
+    }
+    my $serialized = RSPM::Foo142::test_serialized_hash_size(500);
+    print('serialized: ', scalar(keys %$serialized), ' keys, enough buckets: ',
+        ($buckets->($serialized) >= 500 ? 1 : 0), "\n");
+}
+
+print("Testing perlmod::enter_scope\n");
+{
+    package RSPM::ScopeObject {
+        sub DESTROY { print("  destroyed $_[0]->{id}\n") }
+    }
+    for my $scoped (1, 0) {
+        print($scoped ? "scoped:\n" : "unscoped:\n");
+        RSPM::Foo142::test_enter_scope(sub {
+            print("  called with $_[0]\n");
+            return bless({ id => $_[0] }, 'RSPM::ScopeObject');
+        }, $scoped);
+        print("  returned\n");
+    }
+    # perl leaves the guard's scope when the xsub returns, dropping it later does nothing
+    RSPM::Foo142::test_keep_scope(1);
+    print("kept guard\n");
+    RSPM::Foo142::test_keep_scope(0);
+    print("dropped guard\n");
+}
+
+print("Testing Hash::iter_sv\n");
+{
+    # perl stores keys which fit into latin1 without the utf8 flag
+    my %hash = (plain => 1, "caf\x{e9}" => 2, "\x{2603}" => 3);
+    print("$_\n") for @{RSPM::Foo142::test_hash_iter_sv(%hash)};
+}